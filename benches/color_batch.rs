@@ -0,0 +1,111 @@
+// Batch color kernels against the per-pixel `Color` path.
+//
+//   cargo bench --bench color_batch                         # scalar powf
+//   cargo bench --bench color_batch --features srgb_lut     # scalar lookup tables
+//   RUSTFLAGS="-C target-cpu=native" cargo bench --bench color_batch   # AVX on x86_64
+//
+// The `simd` groups don't change between the first two, so comparing runs shows
+// both the SIMD speedup and where the lookup tables stand.
+
+use std::hint::black_box;
+
+use codimate::color::{
+    ColorFloat,
+    model::{BlendMode, Color},
+    simd,
+};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+
+// one 1080p row of RGBA8
+const PIXELS: usize = 1920;
+
+fn row(seed: u32) -> Vec<u8> {
+    let mut state = seed;
+    (0..PIXELS * 4)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+fn decode(c: &mut Criterion) {
+    let bytes = row(1);
+    let mut lin: Vec<ColorFloat> = vec![0.0; bytes.len()];
+    let mut g = c.benchmark_group("decode_srgb");
+    g.throughput(Throughput::Elements(bytes.len() as u64));
+
+    g.bench_function("scalar", |b| {
+        b.iter(|| {
+            for (px, out) in bytes.chunks_exact(4).zip(lin.chunks_exact_mut(4)) {
+                let c = Color::from_rgba([px[0], px[1], px[2], px[3]]);
+                out.copy_from_slice(&black_box(c).into_linear());
+            }
+        })
+    });
+    g.bench_function("simd", |b| {
+        b.iter(|| simd::decode_srgb_slice(black_box(&bytes), &mut lin))
+    });
+    g.finish();
+}
+
+fn encode(c: &mut Criterion) {
+    let mut lin: Vec<ColorFloat> = vec![0.0; PIXELS * 4];
+    simd::decode_srgb_slice(&row(2), &mut lin);
+    let mut bytes = vec![0; lin.len()];
+    let mut g = c.benchmark_group("encode_srgb");
+    g.throughput(Throughput::Elements(lin.len() as u64));
+
+    g.bench_function("scalar", |b| {
+        b.iter(|| {
+            for (px, out) in lin.chunks_exact(4).zip(bytes.chunks_exact_mut(4)) {
+                let c = Color::from_linear([px[0], px[1], px[2], px[3]]);
+                out.copy_from_slice(&black_box(c).into_rgba());
+            }
+        })
+    });
+    g.bench_function("simd", |b| {
+        b.iter(|| simd::encode_srgb_slice(black_box(&lin), &mut bytes))
+    });
+    g.finish();
+}
+
+fn blend(c: &mut Criterion) {
+    let (src, dst) = (row(3), row(4));
+    let mut out = dst.clone();
+    let mut g = c.benchmark_group("blend_row");
+    g.throughput(Throughput::Elements(PIXELS as u64));
+
+    for mode in [
+        BlendMode::Normal,
+        BlendMode::Multiply,
+        BlendMode::SoftLight,
+        BlendMode::Hue,
+    ] {
+        g.bench_function(format!("scalar/{mode:?}"), |b| {
+            b.iter(|| {
+                for ((s, d), o) in src
+                    .chunks_exact(4)
+                    .zip(dst.chunks_exact(4))
+                    .zip(out.chunks_exact_mut(4))
+                {
+                    let s = Color::from_rgba([s[0], s[1], s[2], s[3]]);
+                    let d = Color::from_rgba([d[0], d[1], d[2], d[3]]);
+                    o.copy_from_slice(&black_box(s).blend_over(d, mode).into_rgba());
+                }
+            })
+        });
+        g.bench_function(format!("simd/{mode:?}"), |b| {
+            b.iter(|| {
+                out.copy_from_slice(&dst);
+                simd::blend_row(mode, black_box(&src), &mut out);
+            })
+        });
+    }
+    g.finish();
+}
+
+criterion_group!(benches, decode, encode, blend);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+"""Programmatic code animations, rendered in Rust."""
+
+from codimate._codimate import (
+    Animation,
+    BlendMode,
+    Circle,
+    Code,
+    Color,
+    FadeIn,
+    FadeOut,
+    FadeToColor,
+    Frame,
+    Line,
+    MoveTo,
+    Object,
+    Polygon,
+    Rect,
+    RenderError,
+    Renderer,
+    Rotate,
+    ScaleInPlace,
+    Shift,
+    Text,
+    Timeline,
+)
+from codimate.scene import Scene
+
+__all__ = [
+    "Animation",
+    "BlendMode",
+    "Circle",
+    "Code",
+    "Color",
+    "FadeIn",
+    "FadeOut",
+    "FadeToColor",
+    "Frame",
+    "Line",
+    "MoveTo",
+    "Object",
+    "Polygon",
+    "Rect",
+    "RenderError",
+    "Renderer",
+    "Rotate",
+    "ScaleInPlace",
+    "Scene",
+    "Shift",
+    "Text",
+    "Timeline",
+]
@@ -0,0 +1,359 @@
+# Type stubs for the Rust extension module (src/python). tests/python/test_stubs.py checks
+# them against the built module, so a binding that changes without this file fails there.
+
+from os import PathLike
+from typing import ClassVar, Literal, final
+
+from typing_extensions import TypeAlias
+
+ColorLike: TypeAlias = Color | str
+"""A `Color` or any CSS color string."""
+Point: TypeAlias = tuple[float, float]
+Easing: TypeAlias = Literal["linear", "ease_in", "ease_out", "ease_in_out", "hold"]
+Property: TypeAlias = Literal["position", "x", "y", "opacity", "scale", "rotation", "color"]
+Value: TypeAlias = float | Point | ColorLike
+
+class RenderError(Exception):
+    """Rendering a scene or writing the output failed."""
+
+# colors
+
+@final
+class BlendMode:
+    """How `Color.blend_over` mixes a color into its backdrop, as in CSS `mix-blend-mode`."""
+
+    NORMAL: ClassVar[BlendMode]
+    MULTIPLY: ClassVar[BlendMode]
+    SCREEN: ClassVar[BlendMode]
+    OVERLAY: ClassVar[BlendMode]
+    DARKEN: ClassVar[BlendMode]
+    LIGHTEN: ClassVar[BlendMode]
+    COLOR_DODGE: ClassVar[BlendMode]
+    COLOR_BURN: ClassVar[BlendMode]
+    HARD_LIGHT: ClassVar[BlendMode]
+    SOFT_LIGHT: ClassVar[BlendMode]
+    DIFFERENCE: ClassVar[BlendMode]
+    EXCLUSION: ClassVar[BlendMode]
+    HUE: ClassVar[BlendMode]
+    SATURATION: ClassVar[BlendMode]
+    COLOR: ClassVar[BlendMode]
+    LUMINOSITY: ClassVar[BlendMode]
+    def __int__(self) -> int: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+@final
+class Color:
+    """An sRGB color with 8 bits per channel."""
+
+    TRANSPARENT: ClassVar[Color]
+    BLACK: ClassVar[Color]
+    WHITE: ClassVar[Color]
+    RED: ClassVar[Color]
+    GREEN: ClassVar[Color]
+    BLUE: ClassVar[Color]
+    def __init__(self, r: int, g: int, b: int, a: int = 255) -> None: ...
+    @staticmethod
+    def parse(css: str) -> Color:
+        """Parse any CSS color: hex, `rgb()`, `hsl()`, `oklch()`, names..."""
+    @property
+    def r(self) -> int: ...
+    @property
+    def g(self) -> int: ...
+    @property
+    def b(self) -> int: ...
+    @property
+    def a(self) -> int: ...
+    def with_alpha(self, a: int) -> Color: ...
+    def mix(self, other: ColorLike, t: float) -> Color:
+        """Blend towards `other` in OKLCH, `t` from 0.0 (this color) to 1.0 (`other`)."""
+    def over(self, backdrop: ColorLike) -> Color:
+        """Composite this color over `backdrop` (source-over, in linear light)."""
+    def blend_over(self, backdrop: ColorLike, mode: BlendMode = ...) -> Color:
+        """Blend this color over `backdrop` with a blend mode (in linear light)."""
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+# frames and drawing
+
+@final
+class Frame:
+    """An RGBA8 image."""
+
+    def __init__(self, width: int, height: int) -> None:
+        """A transparent `width` x `height` frame."""
+    @property
+    def width(self) -> int: ...
+    @property
+    def height(self) -> int: ...
+    def get_pixel(self, x: int, y: int) -> Color | None:
+        """The color at (`x`, `y`), `None` outside the frame."""
+    def to_bytes(self) -> bytes:
+        """The pixels, row by row, 4 bytes (RGBA) each."""
+    def save(self, path: str | PathLike[str], *, quality: int = 80) -> None:
+        """Save as a PNG. `quality` (0-100) only trades file size for encoding time."""
+
+@final
+class Renderer:
+    """Draws into frames of one size."""
+
+    def __init__(self, width: int, height: int) -> None: ...
+    @property
+    def width(self) -> int: ...
+    @property
+    def height(self) -> int: ...
+    def new_frame(self) -> Frame:
+        """A transparent frame this renderer's size."""
+    def clear(self, frame: Frame, color: ColorLike) -> None:
+        """Fill the whole frame."""
+    def set_pixel(self, frame: Frame, x: int, y: int, color: ColorLike) -> None: ...
+    def rect(
+        self, frame: Frame, x: int, y: int, width: int, height: int, color: ColorLike
+    ) -> None:
+        """Fill a rectangle (no blending), clipped to the frame."""
+    def blit_over(self, frame: Frame, source: Frame, x: int, y: int) -> None:
+        """Draw `source` over `frame` with its top left corner at (`x`, `y`)."""
+    def render(self, timeline: Timeline, time: float) -> Frame:
+        """The frame `timeline` shows at `time` seconds, scaled to fit this renderer's size."""
+
+# scene objects
+
+class Object:
+    """Something that can be put in a scene. Subclasses pick the shape.
+
+    Adding an object to a timeline copies it, so changes made afterwards only apply to
+    timelines it's added to later. Animate it instead.
+    """
+
+    @property
+    def kind(self) -> Literal["text", "code", "rect", "circle", "line", "polygon"]:
+        """What kind of shape this is."""
+    @property
+    def name(self) -> str | None: ...
+    @name.setter
+    def name(self, name: str | None) -> None: ...
+    @property
+    def position(self) -> Point: ...
+    @position.setter
+    def position(self, position: Point) -> None: ...
+    @property
+    def color(self) -> Color: ...
+    @color.setter
+    def color(self, color: ColorLike) -> None: ...
+    @property
+    def opacity(self) -> float: ...
+    @opacity.setter
+    def opacity(self, opacity: float) -> None: ...
+    @property
+    def scale(self) -> float: ...
+    @scale.setter
+    def scale(self, scale: float) -> None: ...
+    @property
+    def rotation(self) -> float:
+        """Clockwise, in degrees."""
+    @rotation.setter
+    def rotation(self, rotation: float) -> None: ...
+
+@final
+class Text(Object):
+    """Text with its top left corner at `position`. `size` is the line height."""
+
+    def __init__(
+        self,
+        text: str,
+        size: float = 32.0,
+        *,
+        position: Point = (0.0, 0.0),
+        color: ColorLike | None = None,
+        opacity: float = 1.0,
+        name: str | None = None,
+    ) -> None: ...
+
+@final
+class Code(Object):
+    """A code block, optionally on a `background` panel with `padding` around the code."""
+
+    def __init__(
+        self,
+        source: str,
+        size: float = 24.0,
+        *,
+        background: ColorLike | None = None,
+        padding: float | None = None,
+        position: Point = (0.0, 0.0),
+        color: ColorLike | None = None,
+        opacity: float = 1.0,
+        name: str | None = None,
+    ) -> None: ...
+
+@final
+class Rect(Object):
+    """A `width` x `height` rectangle with its top left corner at `position`."""
+
+    def __init__(
+        self,
+        width: float,
+        height: float,
+        *,
+        radius: float = 0.0,
+        position: Point = (0.0, 0.0),
+        color: ColorLike | None = None,
+        opacity: float = 1.0,
+        name: str | None = None,
+    ) -> None: ...
+
+@final
+class Circle(Object):
+    """A circle centered on `position`."""
+
+    def __init__(
+        self,
+        radius: float,
+        *,
+        position: Point = (0.0, 0.0),
+        color: ColorLike | None = None,
+        opacity: float = 1.0,
+        name: str | None = None,
+    ) -> None: ...
+
+@final
+class Line(Object):
+    """A line from `position` to `position + end`."""
+
+    def __init__(
+        self,
+        end: Point,
+        width: float = 2.0,
+        *,
+        position: Point = (0.0, 0.0),
+        color: ColorLike | None = None,
+        opacity: float = 1.0,
+        name: str | None = None,
+    ) -> None: ...
+
+@final
+class Polygon(Object):
+    """A closed polygon through `points`, relative to `position`."""
+
+    def __init__(
+        self,
+        points: list[Point],
+        *,
+        position: Point = (0.0, 0.0),
+        color: ColorLike | None = None,
+        opacity: float = 1.0,
+        name: str | None = None,
+    ) -> None: ...
+
+# animations
+
+class Animation:
+    """A change to one object, played by `Timeline.play`."""
+
+    def __init__(
+        self,
+        target: Object,
+        property: Property,
+        to: Value,
+        *,
+        from_: Value | None = None,
+        easing: Easing = "ease_in_out",
+    ) -> None:
+        """Animate any property to `to`, jumping to `from_` first if it's given."""
+    @property
+    def target(self) -> Object:
+        """The object being animated."""
+
+@final
+class FadeIn(Animation):
+    """Fade an object in from transparent, adding it to the scene if it isn't yet."""
+
+    def __init__(self, target: Object, *, easing: Easing = "ease_in_out") -> None: ...
+
+@final
+class FadeOut(Animation):
+    """Fade an object out. Adding it again brings it back."""
+
+    def __init__(self, target: Object, *, easing: Easing = "ease_in_out") -> None: ...
+
+@final
+class MoveTo(Animation):
+    """Move an object to `position`."""
+
+    def __init__(self, target: Object, position: Point, *, easing: Easing = "ease_in_out") -> None: ...
+
+@final
+class Shift(Animation):
+    """Move an object by `offset` from wherever it is."""
+
+    def __init__(self, target: Object, offset: Point, *, easing: Easing = "ease_in_out") -> None: ...
+
+@final
+class Rotate(Animation):
+    """Rotate an object by `degrees` (clockwise) around its center."""
+
+    def __init__(self, target: Object, degrees: float, *, easing: Easing = "ease_in_out") -> None: ...
+
+@final
+class ScaleInPlace(Animation):
+    """Scale an object by `factor` around its center."""
+
+    def __init__(self, target: Object, factor: float, *, easing: Easing = "ease_in_out") -> None: ...
+
+@final
+class FadeToColor(Animation):
+    """Blend an object's color to `color` (in OKLCH)."""
+
+    def __init__(self, target: Object, color: ColorLike, *, easing: Easing = "ease_in_out") -> None: ...
+
+# the timeline
+
+class Timeline:
+    """A scene being built up call by call, with a playhead (`time`) that `play` and `wait`
+    move forward. `codimate.Scene` is the class to subclass."""
+
+    def __init__(
+        self,
+        width: int = 1280,
+        height: int = 720,
+        fps: float = 30.0,
+        background: ColorLike | None = None,
+    ) -> None: ...
+    @property
+    def width(self) -> int: ...
+    @property
+    def height(self) -> int: ...
+    @property
+    def fps(self) -> float: ...
+    @fps.setter
+    def fps(self, fps: float) -> None: ...
+    @property
+    def background(self) -> Color: ...
+    @background.setter
+    def background(self, background: ColorLike) -> None: ...
+    @property
+    def time(self) -> float:
+        """Where the playhead is, in seconds. It's also how long the scene is so far."""
+    def add(self, *objects: Object) -> None:
+        """Show objects from now on. Adding one that's already showing does nothing."""
+    def remove(self, *objects: Object) -> None:
+        """Hide objects from now on."""
+    def play(self, *animations: Animation, run_time: float = 1.0) -> None:
+        """Play animations together over `run_time` seconds, then move the playhead past them."""
+    def wait(self, duration: float = 1.0) -> None:
+        """Hold still for `duration` seconds."""
+    def clear(self) -> None:
+        """Start over with an empty timeline, keeping the canvas settings."""
+    def export(
+        self,
+        output: str | PathLike[str],
+        *,
+        quality: int = 80,
+        size: tuple[int, int] | None = None,
+    ) -> int:
+        """Render what's on the timeline to a video, GIF or PNG sequence (picked from
+        `output`, like the `codimate render` command). An empty timeline renders a single
+        frame.
+
+        Returns how many frames were written.
+        """
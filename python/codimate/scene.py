@@ -0,0 +1,52 @@
+"""The manim style entry point: subclass `Scene`, build it up in `construct()`, `render()`."""
+
+from __future__ import annotations
+
+import os
+
+from codimate._codimate import Timeline
+
+
+class Scene(Timeline):
+    """A scene written as code.
+
+    Override `construct()` and build the scene there with `add()`, `play()`, `wait()` and
+    `remove()`, then call `render()`::
+
+        class Intro(Scene):
+            def construct(self):
+                title = Text("Binary search", 64, position=(80, 60))
+                self.play(FadeIn(title), run_time=0.5)
+                self.wait()
+                self.play(Shift(title, (0, -40)), FadeOut(title))
+
+        Intro(1280, 720, fps=30, background="#1e1e2e").render("intro.mp4")
+
+    The canvas is set when the scene is made: `Scene(width=1280, height=720, fps=30.0,
+    background=None)`. Everything is drawn by the Rust renderer.
+    """
+
+    def construct(self) -> None:
+        """Build the scene. Subclasses have to override this."""
+        raise NotImplementedError(
+            f"{type(self).__name__} needs a construct() method that builds the scene"
+        )
+
+    def render(
+        self,
+        output: str | os.PathLike[str],
+        *,
+        quality: int = 80,
+        size: tuple[int, int] | None = None,
+    ) -> int:
+        """Run `construct()` on a fresh timeline and render it.
+
+        `output` picks the format like `codimate render` does: `.mp4`, `.mov`, `.mkv` or
+        `.webm` (needs ffmpeg), `.gif`, `.png`, or for a PNG sequence a directory that exists
+        or a string ending in `/`. `size`
+        scales the scene to fit a different output size. Returns how many frames were
+        written, and raises `RenderError` if that fails.
+        """
+        self.clear()
+        self.construct()
+        return self.export(os.fspath(output), quality=quality, size=size)
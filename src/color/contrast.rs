@@ -0,0 +1,244 @@
+#![allow(dead_code)]
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::color::{ColorFloat, model::Color};
+
+/// A contrast requirement a foreground/background pair has to meet.
+///
+/// # Variants
+///
+/// - `WcagAA` - WCAG 2 AA for body text, a 4.5:1 ratio.
+/// - `WcagAALarge` - WCAG 2 AA for large text (24px, or 18.66px bold), 3:1.
+/// - `WcagAAA` - WCAG 2 AAA for body text, 7:1.
+/// - `WcagAAALarge` - WCAG 2 AAA for large text, 4.5:1.
+/// - `Apca` - An APCA lightness contrast of at least this absolute Lc
+///   (90 preferred for body text, 75 body text, 60 content, 45 headings, 30 non-text).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContrastLevel {
+    WcagAA,
+    WcagAALarge,
+    WcagAAA,
+    WcagAAALarge,
+    Apca(ColorFloat),
+}
+
+impl ContrastLevel {
+    /// The number a pair has to reach: a ratio for WCAG, an absolute Lc for APCA.
+    #[must_use]
+    pub fn threshold(self) -> ColorFloat {
+        match self {
+            ContrastLevel::WcagAA | ContrastLevel::WcagAAALarge => 4.5,
+            ContrastLevel::WcagAALarge => 3.0,
+            ContrastLevel::WcagAAA => 7.0,
+            ContrastLevel::Apca(lc) => lc.abs(),
+        }
+    }
+
+    /// Whether text in `fg` on `bg` meets the level. A translucent `fg` is
+    /// composited over `bg` first, since that's what ends up on screen.
+    #[must_use]
+    pub fn passes(self, fg: Color, bg: Color) -> bool {
+        self.measure(fg.over(bg), bg) >= self.threshold()
+    }
+
+    #[inline]
+    fn measure(self, fg: Color, bg: Color) -> ColorFloat {
+        match self {
+            ContrastLevel::Apca(_) => fg.apca_contrast(bg).abs(),
+            _ => fg.contrast_ratio(bg),
+        }
+    }
+}
+
+impl fmt::Display for ContrastLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContrastLevel::WcagAA => write!(f, "WCAG AA (4.5:1)"),
+            ContrastLevel::WcagAALarge => write!(f, "WCAG AA large text (3:1)"),
+            ContrastLevel::WcagAAA => write!(f, "WCAG AAA (7:1)"),
+            ContrastLevel::WcagAAALarge => write!(f, "WCAG AAA large text (4.5:1)"),
+            ContrastLevel::Apca(lc) => write!(f, "APCA Lc {}", lc.abs()),
+        }
+    }
+}
+
+/// A foreground/background pair that doesn't meet a contrast level.
+///
+/// # Fields
+///
+/// - `foreground` (`&str`) - The foreground's name in the theme.
+/// - `background` (`&str`) - The background's name in the theme.
+/// - `fg` (`Color`) - The foreground as it lands on the background (alpha composited).
+/// - `bg` (`Color`) - The background.
+/// - `level` (`ContrastLevel`) - The level that was missed.
+/// - `ratio` (`ColorFloat`) - The pair's WCAG contrast ratio.
+/// - `apca` (`ColorFloat`) - The pair's APCA Lc (signed, negative for light on dark).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContrastIssue<'a> {
+    pub foreground: &'a str,
+    pub background: &'a str,
+    pub fg: Color,
+    pub bg: Color,
+    pub level: ContrastLevel,
+    pub ratio: ColorFloat,
+    pub apca: ColorFloat,
+}
+
+impl fmt::Display for ContrastIssue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} on {}: {:.2}:1, Lc {:.1} fails {}",
+            self.foreground, self.background, self.ratio, self.apca, self.level
+        )
+    }
+}
+
+/// Check every foreground against every background of a theme.
+///
+/// # Arguments
+///
+/// - `foregrounds` (`&[(&str, Color)]`) - Named text colors (syntax tokens, UI text).
+/// - `backgrounds` (`&[(&str, Color)]`) - Named surfaces they're drawn on.
+/// - `levels` (`&[ContrastLevel]`) - Every level each pair has to meet.
+///
+/// # Returns
+///
+/// - `Vec<ContrastIssue>` - One issue per failing pair and level, in input order.
+///   Empty if the whole theme passes.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::color::{Color, contrast::{ContrastLevel, check_theme}};
+///
+/// let tokens = [("keyword", Color::new(203, 166, 247, 255)), ("comment", Color::new(88, 91, 112, 255))];
+/// let surfaces = [("editor", Color::new(30, 30, 46, 255))];
+/// for issue in check_theme(&tokens, &surfaces, &[ContrastLevel::WcagAA, ContrastLevel::Apca(60.0)]) {
+///     println!("{issue}");
+/// }
+/// ```
+#[must_use]
+#[cfg(feature = "alloc")]
+pub fn check_theme<'a>(
+    foregrounds: &[(&'a str, Color)],
+    backgrounds: &[(&'a str, Color)],
+    levels: &[ContrastLevel],
+) -> Vec<ContrastIssue<'a>> {
+    let mut issues = Vec::new();
+    for &(background, bg) in backgrounds {
+        for &(foreground, fg) in foregrounds {
+            let fg = fg.over(bg);
+            let (ratio, apca) = (fg.contrast_ratio(bg), fg.apca_contrast(bg));
+            for &level in levels {
+                if level.measure(fg, bg) < level.threshold() {
+                    issues.push(ContrastIssue {
+                        foreground,
+                        background,
+                        fg,
+                        bg,
+                        level,
+                        ratio,
+                        apca,
+                    });
+                }
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn apca_matches_reference_values() {
+        // values from the apca-w3 reference implementation
+        let lc = |fg: [u8; 3], bg: [u8; 3]| Color::from_rgb(fg).apca_contrast(Color::from_rgb(bg));
+        assert!((lc([0; 3], [255; 3]) - 106.04).abs() < 0.05);
+        assert!((lc([255; 3], [0; 3]) + 107.88).abs() < 0.05);
+        assert!((lc([0x88; 3], [255; 3]) - 63.06).abs() < 0.1);
+        assert_eq!(lc([0x44; 3], [0x44; 3]), 0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn theme_report_lists_failing_pairs() {
+        let fgs = [
+            ("text", Color::new(205, 214, 244, 255)),
+            ("comment", Color::new(88, 91, 112, 255)),
+            ("faded", Color::new(255, 255, 255, 40)),
+        ];
+        let bgs = [("editor", Color::new(30, 30, 46, 255))];
+        let issues = check_theme(
+            &fgs,
+            &bgs,
+            &[ContrastLevel::WcagAA, ContrastLevel::Apca(75.0)],
+        );
+
+        let failing: Vec<_> = issues.iter().map(|i| (i.foreground, i.level)).collect();
+        assert_eq!(
+            failing,
+            [
+                ("comment", ContrastLevel::WcagAA),
+                ("comment", ContrastLevel::Apca(75.0)),
+                ("faded", ContrastLevel::WcagAA),
+                ("faded", ContrastLevel::Apca(75.0)),
+            ]
+        );
+        assert!(issues[0].to_string().starts_with("comment on editor: 2."));
+        assert!(ContrastLevel::WcagAAA.passes(fgs[0].1, bgs[0].1));
+    }
+
+    fn color() -> impl proptest::strategy::Strategy<Value = Color> {
+        proptest::prelude::any::<[u8; 3]>().prop_map(Color::from_rgb)
+    }
+
+    fn hue_gap(a: Color, b: Color) -> ColorFloat {
+        let d = (a.into_oklch()[2] - b.into_oklch()[2]).abs() % 360.0;
+        d.min(360.0 - d)
+    }
+
+    proptest! {
+        #[test]
+        fn ensure_contrast_reaches_reachable_targets(fg in color(), bg in color(), target in 1.0..4.5f32) {
+            let target = target as ColorFloat;
+            let out = fg.ensure_contrast(bg, target);
+            // black or white always reach 4.58:1 against something, so this is always possible
+            prop_assert!(out.contrast_ratio(bg) >= target, "{out:?} on {bg:?}");
+            // already good colors are left alone, adjusted ones are stable
+            prop_assert_eq!(out.ensure_contrast(bg, target), out);
+            if fg.contrast_ratio(bg) >= target {
+                prop_assert_eq!(out, fg);
+            }
+        }
+
+        #[test]
+        fn ensure_contrast_keeps_hue(fg in color(), bg in color(), target in 1.0..7.0f32) {
+            let out = fg.ensure_contrast(bg, target as ColorFloat);
+            // gamut mapping may clip up to a just noticeable difference (0.02) sideways,
+            // and 8 bit rounding a little more, so measure off-hue drift in the a/b plane
+            if fg.into_oklch()[1] > 0.05 {
+                let drift = out.into_oklch()[1] * hue_gap(fg, out).to_radians().sin().abs();
+                prop_assert!(drift < 0.03, "{fg:?} -> {out:?}: {drift}");
+            }
+        }
+
+        #[test]
+        fn ensure_apca_contrast_reaches_reachable_targets(fg in color(), bg in color(), target in 15.0..90.0f32) {
+            let target = target as ColorFloat;
+            let out = fg.ensure_apca_contrast(bg, target);
+            let best = Color::BLACK.apca_contrast(bg).abs().max(Color::WHITE.apca_contrast(bg).abs());
+            if best >= target + 1.0 {
+                prop_assert!(out.apca_contrast(bg).abs() >= target, "{out:?} on {bg:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,358 @@
+#![allow(dead_code)]
+
+use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Sub, SubAssign};
+
+use crate::color::{
+    ColorFloat,
+    model::{BlendMode, Color},
+};
+
+/// A linear sRGB color with float channels and straight alpha.
+///
+/// `Color` quantizes to 8 bits after every operation, so chaining blends piles up rounding
+/// error. Keep intermediate results as `LinearColor` and convert to `Color` once at output.
+/// Channels aren't clamped, so values above 1.0 (or below 0.0) survive until then.
+///
+/// # Fields
+///
+/// - `r` (`ColorFloat`) - Linear red.
+/// - `g` (`ColorFloat`) - Linear green.
+/// - `b` (`ColorFloat`) - Linear blue.
+/// - `a` (`ColorFloat`) - Alpha, 0.0-1.0.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::color::{Color, linear::LinearColor};
+///
+/// let bg = LinearColor::from(Color::new(30, 30, 46, 255));
+/// let glow = LinearColor::from(Color::new(137, 180, 250, 40));
+///
+/// // three layers, one rounding
+/// let out: Color = glow.over(glow.over(glow.over(bg))).into();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LinearColor {
+    pub r: ColorFloat,
+    pub g: ColorFloat,
+    pub b: ColorFloat,
+    pub a: ColorFloat,
+}
+
+impl LinearColor {
+    pub const TRANSPARENT: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+    pub const WHITE: Self = Self::new(1.0, 1.0, 1.0, 1.0);
+
+    /// Create a linear color from its channels.
+    ///
+    /// # Arguments
+    ///
+    /// - `r` (`ColorFloat`) - Linear red.
+    /// - `g` (`ColorFloat`) - Linear green.
+    /// - `b` (`ColorFloat`) - Linear blue.
+    /// - `a` (`ColorFloat`) - Alpha, 0.0-1.0.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The new color.
+    #[must_use]
+    pub const fn new(r: ColorFloat, g: ColorFloat, b: ColorFloat, a: ColorFloat) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Multiply the color channels by alpha.
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`LinearColor`) - A color with straight alpha.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The color with premultiplied alpha.
+    #[must_use]
+    #[inline]
+    pub fn premultiply(self) -> Self {
+        Self::new(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+
+    /// Divide the color channels by alpha. Fully transparent colors become transparent black.
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`LinearColor`) - A color with premultiplied alpha.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The color with straight alpha.
+    #[must_use]
+    #[inline]
+    pub fn unpremultiply(self) -> Self {
+        if self.a <= 0.0 {
+            return Self::TRANSPARENT;
+        }
+        Self::new(self.r / self.a, self.g / self.a, self.b / self.a, self.a)
+    }
+
+    /// Clamp every channel to 0.0-1.0.
+    #[must_use]
+    #[inline]
+    pub fn clamp(self) -> Self {
+        let c = |v: ColorFloat| v.clamp(0.0, 1.0);
+        Self::new(c(self.r), c(self.g), c(self.b), c(self.a))
+    }
+
+    /// Linearly interpolate every channel (alpha included) between two colors.
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`LinearColor`) - The color at `t = 0.0`.
+    /// - `other` (`LinearColor`) - The color at `t = 1.0`.
+    /// - `t` (`ColorFloat`) - How far to go, not clamped.
+    ///
+    /// # Returns
+    ///
+    /// - `LinearColor` - The interpolated color.
+    #[must_use]
+    #[inline]
+    pub fn lerp(self, other: LinearColor, t: ColorFloat) -> LinearColor {
+        self + (other - self) * t
+    }
+
+    /// Perform a Porter-Duff over operation, the same math as `Color::over` without rounding.
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`LinearColor`) - The source color.
+    /// - `bg` (`LinearColor`) - The backdrop color.
+    ///
+    /// # Returns
+    ///
+    /// - `LinearColor` - The blended color, with straight alpha.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use codimate::color::linear::LinearColor;
+    ///
+    /// let half_white = LinearColor::new(1.0, 1.0, 1.0, 0.5);
+    /// assert_eq!(half_white.over(LinearColor::BLACK), LinearColor::new(0.5, 0.5, 0.5, 1.0));
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn over(self, bg: LinearColor) -> LinearColor {
+        let (fa, ba) = (self.a, bg.a);
+        let out_a = fa + ba * (1.0 - fa);
+        if out_a <= 0.0 {
+            return Self::new(0.0, 0.0, 0.0, out_a);
+        }
+
+        let mix = |f: ColorFloat, b: ColorFloat| (f * fa + b * ba * (1.0 - fa)) / out_a;
+        Self::new(
+            mix(self.r, bg.r),
+            mix(self.g, bg.g),
+            mix(self.b, bg.b),
+            out_a,
+        )
+    }
+
+    /// Blend a color over a backdrop using a blend mode, the same math as
+    /// `Color::blend_over` without rounding.
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`LinearColor`) - The source color.
+    /// - `bg` (`LinearColor`) - The backdrop color.
+    /// - `mode` (`BlendMode`) - The blend mode to use.
+    ///
+    /// # Returns
+    ///
+    /// - `LinearColor` - The blended color, with straight alpha.
+    #[must_use]
+    #[inline]
+    pub fn blend_over(self, bg: LinearColor, mode: BlendMode) -> LinearColor {
+        if self.a <= 0.0 {
+            return bg;
+        }
+        if matches!(mode, BlendMode::Normal) || bg.a <= 0.0 {
+            return self.over(bg);
+        }
+
+        let (sa, da) = (self.a, bg.a);
+        let [br, bg_, bb] =
+            Color::blend_channel(mode, [self.r, self.g, self.b], [bg.r, bg.g, bg.b]);
+
+        // Porter–Duff combination in premultiplied form
+        let a_out = sa + da - sa * da;
+        let mix = |s: ColorFloat, d: ColorFloat, blended: ColorFloat| {
+            d * da * (1.0 - sa) + s * sa * (1.0 - da) + sa * da * blended
+        };
+        Self::new(
+            mix(self.r, bg.r, br),
+            mix(self.g, bg.g, bg_),
+            mix(self.b, bg.b, bb),
+            a_out,
+        )
+        .unpremultiply()
+    }
+}
+
+impl From<Color> for LinearColor {
+    /// Decodes sRGB. Every `Color` maps to a distinct `LinearColor` and back.
+    fn from(color: Color) -> Self {
+        color.into_linear().into()
+    }
+}
+
+impl From<LinearColor> for Color {
+    /// Encodes to sRGB, clamping and rounding to 8 bits.
+    fn from(color: LinearColor) -> Self {
+        Color::from_linear(color.into())
+    }
+}
+
+impl From<[ColorFloat; 4]> for LinearColor {
+    fn from([r, g, b, a]: [ColorFloat; 4]) -> Self {
+        Self::new(r, g, b, a)
+    }
+}
+
+impl From<LinearColor> for [ColorFloat; 4] {
+    fn from(c: LinearColor) -> Self {
+        [c.r, c.g, c.b, c.a]
+    }
+}
+
+// arithmetic is per channel, alpha included
+
+impl Add for LinearColor {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(
+            self.r + rhs.r,
+            self.g + rhs.g,
+            self.b + rhs.b,
+            self.a + rhs.a,
+        )
+    }
+}
+
+impl Sub for LinearColor {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(
+            self.r - rhs.r,
+            self.g - rhs.g,
+            self.b - rhs.b,
+            self.a - rhs.a,
+        )
+    }
+}
+
+impl Mul for LinearColor {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.r * rhs.r,
+            self.g * rhs.g,
+            self.b * rhs.b,
+            self.a * rhs.a,
+        )
+    }
+}
+
+impl Mul<ColorFloat> for LinearColor {
+    type Output = Self;
+    fn mul(self, rhs: ColorFloat) -> Self {
+        Self::new(self.r * rhs, self.g * rhs, self.b * rhs, self.a * rhs)
+    }
+}
+
+impl Div<ColorFloat> for LinearColor {
+    type Output = Self;
+    fn div(self, rhs: ColorFloat) -> Self {
+        Self::new(self.r / rhs, self.g / rhs, self.b / rhs, self.a / rhs)
+    }
+}
+
+impl AddAssign for LinearColor {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for LinearColor {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign<ColorFloat> for LinearColor {
+    fn mul_assign(&mut self, rhs: ColorFloat) {
+        *self = *self * rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_color_round_trips() {
+        for v in 0..=255u8 {
+            for c in [Color::new(v, 255 - v, v / 3, v), Color::new(0, v, 0, 255)] {
+                assert_eq!(Color::from(LinearColor::from(c)), c);
+            }
+        }
+    }
+
+    #[test]
+    fn matches_the_8_bit_blends() {
+        let fg = Color::new(200, 40, 90, 150);
+        let bg = Color::new(20, 160, 220, 255);
+        let lin = |c: Color| LinearColor::from(c);
+
+        assert_eq!(Color::from(lin(fg).over(lin(bg))), fg.over(bg));
+        for mode in [
+            BlendMode::Multiply,
+            BlendMode::Overlay,
+            BlendMode::Hue,
+            BlendMode::Difference,
+        ] {
+            assert_eq!(
+                Color::from(lin(fg).blend_over(lin(bg), mode)),
+                fg.blend_over(bg, mode),
+                "{mode:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn chained_blends_quantize_once() {
+        // forty faint shadows: rounding each one to 8 bits drifts, keeping floats doesn't
+        let layer = Color::new(0, 0, 0, 3);
+        let mut quantized = Color::WHITE;
+        let mut exact = LinearColor::WHITE;
+        for _ in 0..40 {
+            quantized = layer.over(quantized);
+            exact = LinearColor::from(layer).over(exact);
+        }
+
+        let expected = (1.0 - 3.0 / 255.0 as ColorFloat).powi(40);
+        assert!((exact.r - expected).abs() < 1e-4, "{}", exact.r);
+        assert_eq!(Color::from(exact).into_rgb(), [207; 3]);
+        assert_eq!(quantized.into_rgb(), [215; 3]);
+    }
+
+    #[test]
+    fn premultiply_round_trips() {
+        let c = LinearColor::new(0.8, 0.4, 0.2, 0.5);
+        assert_eq!(c.premultiply(), LinearColor::new(0.4, 0.2, 0.1, 0.5));
+        assert_eq!(c.premultiply().unpremultiply(), c);
+        assert_eq!(
+            LinearColor::new(1.0, 1.0, 1.0, 0.0).unpremultiply(),
+            LinearColor::TRANSPARENT
+        );
+        assert_eq!(c.lerp(LinearColor::WHITE, 0.5).a, 0.75);
+    }
+}
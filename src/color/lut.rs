@@ -5,51 +5,108 @@
 
 #![allow(dead_code)]
 
-use std::sync::OnceLock;
-
 /// linear -> sRGB table size
 const N_ENC: usize = 4096;
 
-static SRGB_TO_LINEAR_F32: OnceLock<[f32; 256]> = OnceLock::new();
-static LINEAR_TO_SRGB_U8: OnceLock<[u8; N_ENC]> = OnceLock::new();
+/// What the linear -> sRGB table stores. 8 bit codes are off by up to a code value after
+/// interpolating, `srgb_lut_16` keeps 16 bits so only exact rounding ties can come out different.
+#[cfg(not(feature = "srgb_lut_16"))]
+type EncEntry = u8;
+#[cfg(feature = "srgb_lut_16")]
+type EncEntry = u16;
 
-#[inline]
-fn build_srgb_to_linear_f32() -> [f32; 256] {
+const ENC_MAX: f64 = EncEntry::MAX as f64;
+
+// built by the compiler, so there's no lazy init to check on every lookup and no std needed
+static SRGB_TO_LINEAR_F32: [f32; 256] = build_srgb_to_linear_f32();
+static LINEAR_TO_SRGB: [EncEntry; N_ENC] = build_linear_to_srgb();
+
+// powf isn't a const fn, so here's just enough of one for building the tables.
+// f64 all the way through, then rounded once, keeps them as close as the runtime math
+
+const LN_2: f64 = core::f64::consts::LN_2;
+
+/// natural log for x > 0 (normal floats only, which is all the tables need)
+const fn ln(x: f64) -> f64 {
+    // x = m * 2^e with m in 1..2
+    let bits = x.to_bits();
+    let e = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    let m = f64::from_bits((bits & ((1 << 52) - 1)) | (1023 << 52));
+    // ln(m) = 2 atanh(z), z <= 1/3 so the series is done in a few dozen terms
+    let z = (m - 1.0) / (m + 1.0);
+    let z2 = z * z;
+    let (mut term, mut sum, mut k) = (z, 0.0, 1);
+    while k < 60 {
+        sum += term / k as f64;
+        term *= z2;
+        k += 2;
+    }
+    2.0 * sum + e as f64 * LN_2
+}
+
+/// e^x for the small magnitudes the tables need
+const fn exp(x: f64) -> f64 {
+    // x = k ln2 + r with |r| <= ln2 / 2, then e^r by taylor
+    let k = x / LN_2;
+    let k = if k < 0.0 {
+        (k - 0.5) as i64
+    } else {
+        (k + 0.5) as i64
+    };
+    let r = x - k as f64 * LN_2;
+    let (mut term, mut sum, mut n) = (1.0, 1.0, 1);
+    while n < 30 {
+        term *= r / n as f64;
+        sum += term;
+        n += 1;
+    }
+    sum * f64::from_bits(((k + 1023) as u64) << 52)
+}
+
+const fn powf(x: f64, y: f64) -> f64 {
+    if x <= 0.0 { 0.0 } else { exp(y * ln(x)) }
+}
+
+const fn build_srgb_to_linear_f32() -> [f32; 256] {
     let mut t = [0.0f32; 256];
-    for (v, item) in t.iter_mut().enumerate() {
-        let x = (v as f32) / 255.0;
-        *item = if x <= 0.04045 {
+    let mut v = 0;
+    while v < 256 {
+        let x = v as f64 / 255.0;
+        t[v] = if x <= 0.04045 {
             x / 12.92
         } else {
-            ((x + 0.055) / 1.055).powf(2.4)
-        };
+            powf((x + 0.055) / 1.055, 2.4)
+        } as f32;
+        v += 1;
     }
     t
 }
 
-#[inline]
-fn build_linear_to_srgb_u8() -> [u8; N_ENC] {
-    let mut t = [0u8; N_ENC];
-    for (i, item) in t.iter_mut().enumerate() {
-        let x = (i as f32) / (N_ENC as f32 - 1.0); // 0..1
+const fn build_linear_to_srgb() -> [EncEntry; N_ENC] {
+    let mut t = [0; N_ENC];
+    let mut i = 0;
+    while i < N_ENC {
+        let x = i as f64 / (N_ENC as f64 - 1.0); // 0..1
         let y = if x <= 0.003_130_8 {
             12.92 * x
         } else {
-            1.055 * x.powf(1.0 / 2.4) - 0.055
+            1.055 * powf(x, 1.0 / 2.4) - 0.055
         };
-        *item = ((y.clamp(0.0, 1.0) * 255.0) + 0.5).floor() as u8;
+        // y is never negative, so truncating is the floor
+        t[i] = (y.clamp(0.0, 1.0) * ENC_MAX + 0.5) as EncEntry;
+        i += 1;
     }
     t
 }
 
 #[inline]
 fn get_srgb_to_linear_f32() -> &'static [f32; 256] {
-    SRGB_TO_LINEAR_F32.get_or_init(build_srgb_to_linear_f32)
+    &SRGB_TO_LINEAR_F32
 }
 
 #[inline]
-fn get_linear_to_srgb_u8() -> &'static [u8; N_ENC] {
-    LINEAR_TO_SRGB_U8.get_or_init(build_linear_to_srgb_u8)
+fn get_linear_to_srgb() -> &'static [EncEntry; N_ENC] {
+    &LINEAR_TO_SRGB
 }
 
 // we'll hide the public api behind a feature
@@ -63,16 +120,113 @@ pub(crate) fn decode_srgb_lut_f32(v: u8) -> f32 {
 #[cfg(feature = "srgb_lut")]
 #[inline]
 pub(crate) fn encode_srgb_lut_f32(x: f32) -> u8 {
+    // table entries -> 8 bit codes, 1.0 for 8 bit tables
+    const TO_U8: f32 = (255.0 / ENC_MAX) as f32;
+
+    let t = get_linear_to_srgb();
     let x = x.clamp(0.0, 1.0);
     let idx = x * (N_ENC as f32 - 1.0);
     let i = idx as usize;
     if i >= N_ENC - 1 {
-        return get_linear_to_srgb_u8()[N_ENC - 1];
+        return (t[N_ENC - 1] as f32 * TO_U8 + 0.5) as u8;
     }
     let f = idx - i as f32;
-    let a = get_linear_to_srgb_u8()[i] as u16;
-    let b = get_linear_to_srgb_u8()[i + 1] as u16;
-    // linear interp in integer space, then round
-    let y = a as f32 + (b as f32 - a as f32) * f;
-    (y + 0.5).floor() as u8
+    let (a, b) = (t[i] as f32, t[i + 1] as f32);
+    // linear interp in table space, then round
+    let y = a + (b - a) * f;
+    // y is never negative, so truncating is the floor
+    (y * TO_U8 + 0.5) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{ColorFloat, model::Color};
+
+    /// the exact curve in f64, as a real number of code values (not rounded)
+    fn encode_exact(x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        let y = if x <= 0.003_130_8 {
+            12.92 * x
+        } else {
+            1.055 * x.powf(1.0 / 2.4) - 0.055
+        };
+        y * 255.0
+    }
+
+    #[test]
+    fn decoding_matches_the_curve() {
+        for v in 0..=255u8 {
+            let lut = decode_srgb_lut_f32(v);
+            let exact = Color::decode_srgb_analytic(v);
+            assert!(
+                (lut as ColorFloat - exact).abs() < 1e-6,
+                "{v}: {lut} vs {exact}"
+            );
+            assert_eq!(Color::encode_srgb_analytic(lut as ColorFloat), v);
+        }
+    }
+
+    fn encode_analytic(x: f32) -> u8 {
+        Color::encode_srgb_analytic(x as ColorFloat)
+    }
+
+    /// The bits of the smallest float in 0.0..=1.0 that `encode` turns into `code` or more.
+    /// Both encoders only ever go up, so a binary search finds it.
+    fn first_reaching(code: u8, encode: fn(f32) -> u8) -> u32 {
+        let (mut lo, mut hi) = (0, 1.0f32.to_bits());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if encode(f32::from_bits(mid)) >= code {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        lo
+    }
+
+    #[test]
+    fn encoding_is_within_a_code_value() {
+        // both encoders are step functions that only go up, so the gap between them only
+        // changes where one of them steps. both sides of every step of either one covers
+        // every float in 0.0..=1.0, the 16 bit grid is there to count exact matches
+        let grid = (0..=u16::MAX).map(|k| k as f32 / u16::MAX as f32);
+        let steps = (1..=255u8).flat_map(|code| {
+            [encode_srgb_lut_f32, encode_analytic]
+                .map(|encode| first_reaching(code, encode))
+                .into_iter()
+                .flat_map(|bits| [f32::from_bits(bits - 1), f32::from_bits(bits)])
+        });
+
+        let mut exact = 0;
+        for x in grid.chain(steps) {
+            let lut = encode_srgb_lut_f32(x);
+            let analytic = encode_analytic(x);
+            assert!(lut.abs_diff(analytic) <= 1, "{x}: {lut} vs {analytic}");
+            if lut == analytic {
+                exact += 1;
+            } else if cfg!(feature = "srgb_lut_16") {
+                // 16 bit entries only disagree right on a rounding tie
+                let y = encode_exact(x as f64);
+                assert!(
+                    (y.fract() - 0.5).abs() < 5e-3,
+                    "{x}: {lut} vs {analytic} ({y})"
+                );
+            }
+        }
+        assert!(exact > 60_000, "{exact}");
+    }
+
+    /// Every f32 from 0.0 to 1.0, about a billion of them, to back up the step argument
+    /// above. Slow, run it with `cargo test --release --features srgb_lut -- --ignored`.
+    #[test]
+    #[ignore]
+    fn encoding_every_float_is_within_a_code_value() {
+        for bits in 0..=1.0f32.to_bits() {
+            let x = f32::from_bits(bits);
+            let (lut, analytic) = (encode_srgb_lut_f32(x), encode_analytic(x));
+            assert!(lut.abs_diff(analytic) <= 1, "{x}: {lut} vs {analytic}");
+        }
+    }
 }
@@ -0,0 +1,136 @@
+// float math for no_std builds. core doesn't have powf, sqrt, sin and friends, so
+// without std the color code imports this trait and gets the libm versions under the
+// same method names. with std the inherent methods win and this goes unused, and so do
+// test builds, which always link std (see lib.rs). std can also get pulled in by a
+// dependency without our std feature (`--all-targets` turns on serde's std through the
+// dev-dependencies), hence the allow.
+//
+//     #[cfg(not(any(feature = "std", test)))]
+//     #[allow(unused_imports)]
+//     use crate::color::math::Float as _;
+
+#![allow(dead_code)]
+
+pub(crate) trait Float: Sized {
+    fn powf(self, n: Self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn sqrt(self) -> Self;
+    fn cbrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn floor(self) -> Self;
+    fn round(self) -> Self;
+    fn abs(self) -> Self;
+    fn copysign(self, sign: Self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_float {
+    ($t:ty, $pow:ident, $sqrt:ident, $cbrt:ident, $sin:ident, $cos:ident, $atan2:ident,
+     $floor:ident, $round:ident, $abs:ident, $copysign:ident, $fmod:ident) => {
+        impl Float for $t {
+            #[inline]
+            fn powf(self, n: Self) -> Self {
+                libm::$pow(self, n)
+            }
+            #[inline]
+            fn powi(self, n: i32) -> Self {
+                // exact for the small powers the color math uses
+                let mut out = 1.0;
+                for _ in 0..n.unsigned_abs() {
+                    out *= self;
+                }
+                if n < 0 { 1.0 / out } else { out }
+            }
+            #[inline]
+            fn sqrt(self) -> Self {
+                libm::$sqrt(self)
+            }
+            #[inline]
+            fn cbrt(self) -> Self {
+                libm::$cbrt(self)
+            }
+            #[inline]
+            fn sin(self) -> Self {
+                libm::$sin(self)
+            }
+            #[inline]
+            fn cos(self) -> Self {
+                libm::$cos(self)
+            }
+            #[inline]
+            fn atan2(self, other: Self) -> Self {
+                libm::$atan2(self, other)
+            }
+            #[inline]
+            fn floor(self) -> Self {
+                libm::$floor(self)
+            }
+            #[inline]
+            fn round(self) -> Self {
+                libm::$round(self)
+            }
+            #[inline]
+            fn abs(self) -> Self {
+                libm::$abs(self)
+            }
+            #[inline]
+            fn copysign(self, sign: Self) -> Self {
+                libm::$copysign(self, sign)
+            }
+            #[inline]
+            fn rem_euclid(self, rhs: Self) -> Self {
+                let r = libm::$fmod(self, rhs);
+                if r < 0.0 { r + libm::$abs(rhs) } else { r }
+            }
+        }
+    };
+}
+
+impl_float!(
+    f32, powf, sqrtf, cbrtf, sinf, cosf, atan2f, floorf, roundf, fabsf, copysignf, fmodf
+);
+impl_float!(
+    f64, pow, sqrt, cbrt, sin, cos, atan2, floor, round, fabs, copysign, fmod
+);
+
+#[cfg(test)]
+mod tests {
+    use super::Float;
+
+    #[test]
+    fn libm_matches_std() {
+        let mut state = 0x2545_f491u32;
+        for _ in 0..2000 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let x = state as f32 / u32::MAX as f32 * 4.0 - 2.0;
+            let y = x * 0.37 + 0.5;
+
+            let close = |a: f32, b: f32| {
+                (a - b).abs() <= 1e-6 * b.abs().max(1.0) || (a.is_nan() && b.is_nan())
+            };
+            assert!(close(Float::powf(x.abs(), 2.4), x.abs().powf(2.4)));
+            assert!(close(Float::powi(x, 3), x.powi(3)));
+            assert!(close(Float::powi(y, -2), y.powi(-2)));
+            assert!(close(Float::sqrt(x.abs()), x.abs().sqrt()));
+            assert!(close(Float::cbrt(x), x.cbrt()));
+            assert!(close(Float::sin(x), x.sin()));
+            assert!(close(Float::cos(x), x.cos()));
+            assert!(close(Float::atan2(x, y), x.atan2(y)));
+            assert!(close(Float::floor(x), x.floor()));
+            assert!(close(Float::round(x), x.round()));
+            assert!(close(Float::copysign(y, x), y.copysign(x)));
+            assert!(close(
+                Float::rem_euclid(x * 400.0, 360.0),
+                (x * 400.0).rem_euclid(360.0)
+            ));
+            assert!(close(
+                Float::rem_euclid(x as f64, 1.0) as f32,
+                (x as f64).rem_euclid(1.0) as f32
+            ));
+        }
+    }
+}
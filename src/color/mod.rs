@@ -1,9 +1,20 @@
-#![cfg_attr(not(feature = "std"), no_std)]
-
+pub mod contrast;
+pub mod cvd;
+pub mod linear;
 #[cfg(feature = "srgb_lut")]
 pub mod lut;
+mod math;
 pub mod model;
+pub mod named;
+pub mod palette;
 pub mod parse;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod simd;
+pub mod space;
+pub mod wide;
+
+pub use model::{BlendMode, Color};
 
 #[cfg(feature = "color_double_precision")]
 pub type ColorFloat = f64;
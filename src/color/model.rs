@@ -1,11 +1,11 @@
 #![allow(dead_code)]
 
-use std::fmt::{self};
+use core::fmt;
 
-#[cfg(feature = "alloc")]
-extern crate alloc;
-
-use crate::color::ColorFloat;
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::color::math::Float as _;
+use crate::color::{ColorFloat, cvd::Cvd, linear::LinearColor, space::ColorSpace, wide::WideColor};
 
 /// An enum naming the supported color blending modes.
 /// Most descriptions and implementations of these blend modes
@@ -116,7 +116,6 @@ pub enum BlendMode {
 /// - `g` (`u8`) - The green value.
 /// - `b` (`u8`) - The blue value.
 /// - `a` (`u8`) - The alpha value.
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Color {
     r: u8,
@@ -182,7 +181,7 @@ impl Color {
     ///
     /// let dark_gray = Color::new(169, 169, 169, 255);
     /// let steel_blue = Color::new(70, 130, 180, 255);
-    /// let interpolated = dark_gray.lerp(steel_blue);
+    /// let interpolated = dark_gray.lerp(steel_blue, 0.5);
     /// ```
     #[must_use]
     #[inline]
@@ -222,7 +221,7 @@ impl Color {
     ///
     /// let dark_slate_gray = Color::new(47, 79, 79, 255);
     /// let misty_rose = Color::new(255, 228, 225, 255);
-    /// let interpolated = dark_slate_gray.lerp_linear(misty_rose);
+    /// let interpolated = dark_slate_gray.lerp_linear(misty_rose, 0.5);
     /// ```
     #[must_use]
     #[inline]
@@ -260,7 +259,7 @@ impl Color {
     ///
     /// let magenta = Color::new(255, 0, 255, 255);
     /// let green_yellow = Color::new(173, 255, 47, 255);
-    /// let interpolated = magenta.lerp_oklch(green_yellow);
+    /// let interpolated = magenta.lerp_oklch(green_yellow, 0.5);
     /// ```
     #[must_use]
     #[inline]
@@ -333,22 +332,7 @@ impl Color {
     #[must_use]
     #[inline]
     pub fn over(self, bg: Color) -> Color {
-        let fg = self.into_linear();
-        let bg = bg.into_linear();
-        let (fr, fg_, fb, fa) = (fg[0], fg[1], fg[2], fg[3]);
-        let (br, bgc, bb, ba) = (bg[0], bg[1], bg[2], bg[3]);
-
-        let out_a = fa + ba * (1.0 - fa);
-        let (out_r, out_g, out_b) = if out_a > 0.0 {
-            let r = (fr * fa + br * ba * (1.0 - fa)) / out_a;
-            let g = (fg_ * fa + bgc * ba * (1.0 - fa)) / out_a;
-            let b = (fb * fa + bb * ba * (1.0 - fa)) / out_a;
-            (r, g, b)
-        } else {
-            (0.0, 0.0, 0.0)
-        };
-
-        Color::from_linear([out_r, out_g, out_b, out_a])
+        LinearColor::from(self).over(bg.into()).into()
     }
 
     /// Blend a color over a backdrop using a blend mode.
@@ -377,35 +361,7 @@ impl Color {
     #[must_use]
     #[inline]
     pub fn blend_over(self, bg: Color, mode: BlendMode) -> Color {
-        use BlendMode::*;
-
-        if self.a == 0 {
-            return bg;
-        }
-        if matches!(mode, Normal) || bg.a == 0 {
-            return self.over(bg);
-        }
-
-        let [sr, sg, sb, sa] = self.into_linear();
-        let [dr, dg, db, da] = bg.into_linear();
-
-        let [br, bg_, bb] = Self::blend_channel(mode, [sr, sg, sb], [dr, dg, db]);
-
-        // Porter–Duff combination in premultiplied form
-        let a_out = sa + da - sa * da;
-        let cr_p = dr * da * (1.0 - sa) + sr * sa * (1.0 - da) + sa * da * br;
-        let cg_p = dg * da * (1.0 - sa) + sg * sa * (1.0 - da) + sa * da * bg_;
-        let cb_p = db * da * (1.0 - sa) + sb * sa * (1.0 - da) + sa * da * bb;
-
-        // Un-pre-multiply (if alpha zero, return transparent black)
-        let (cr, cg, cb, ca) = if a_out > 0.0 {
-            (cr_p / a_out, cg_p / a_out, cb_p / a_out, a_out)
-        } else {
-            (0.0, 0.0, 0.0, 0.0)
-        };
-
-        // Encode back to sRGB u8 (your encoders already clamp)
-        Self::from_linear([cr, cg, cb, ca])
+        LinearColor::from(self).blend_over(bg.into(), mode).into()
     }
 
     /// A faster but slightly less accurate Porter-Duff over in sRGB space.
@@ -523,6 +479,157 @@ impl Color {
         (l1 + 0.05) / (l2 + 0.05)
     }
 
+    /// Calculate the APCA lightness contrast (Lc) of text in this color on a background.
+    ///
+    /// Unlike the WCAG ratio this depends on polarity: dark text on light backgrounds
+    /// is positive, light text on dark backgrounds is negative. Compare the absolute
+    /// value against the APCA levels (75 for body text, 60 for content, 45 for headings).
+    ///
+    /// Source: https://github.com/Myndex/apca-w3 (0.0.98G-4g constants)
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`Color`) - The text color.
+    /// - `bg` (`Color`) - The background color.
+    ///
+    /// # Returns
+    ///
+    /// - `ColorFloat` - The Lc value, roughly -108 to 106.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use codimate::color::Color;
+    ///
+    /// let lc = Color::BLACK.apca_contrast(Color::WHITE);
+    /// assert!((lc - 106.04).abs() < 0.01);
+    /// ```
+    #[must_use]
+    pub fn apca_contrast(self, bg: Color) -> ColorFloat {
+        const BLK_THRS: ColorFloat = 0.022;
+        const BLK_CLMP: ColorFloat = 1.414;
+        const SCALE: ColorFloat = 1.14;
+        const LO_OFFSET: ColorFloat = 0.027;
+        const LO_CLIP: ColorFloat = 0.1;
+        const DELTA_Y_MIN: ColorFloat = 0.0005;
+
+        // APCA uses a plain 2.4 gamma instead of the piecewise sRGB curve
+        let y = |c: Color| {
+            let lin = |v: u8| (v as ColorFloat / 255.0).powf(2.4);
+            let y = 0.2126729 * lin(c.r) + 0.7151522 * lin(c.g) + 0.0721750 * lin(c.b);
+            if y < BLK_THRS {
+                y + (BLK_THRS - y).powf(BLK_CLMP)
+            } else {
+                y
+            }
+        };
+        let (txt, bg) = (y(self), y(bg));
+        if (bg - txt).abs() < DELTA_Y_MIN {
+            return 0.0;
+        }
+
+        let lc = if bg > txt {
+            let sapc = (bg.powf(0.56) - txt.powf(0.57)) * SCALE;
+            if sapc < LO_CLIP {
+                0.0
+            } else {
+                sapc - LO_OFFSET
+            }
+        } else {
+            let sapc = (bg.powf(0.65) - txt.powf(0.62)) * SCALE;
+            if sapc > -LO_CLIP {
+                0.0
+            } else {
+                sapc + LO_OFFSET
+            }
+        };
+        lc * 100.0
+    }
+
+    /// Adjust a color's lightness until it has a WCAG contrast ratio of at least
+    /// `target_ratio` against a background.
+    ///
+    /// Hue is kept and chroma is only reduced where the lighter/darker color doesn't
+    /// fit in sRGB. Of lightening and darkening, whichever reaches the target with the
+    /// smaller change wins. If the target can't be reached with the color's chroma, a
+    /// grey is used instead, and if even black or white can't reach it, the most
+    /// contrasting of them is returned. Alpha is kept.
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`Color`) - The color to adjust.
+    /// - `bg` (`Color`) - The color it has to stand out against.
+    /// - `target_ratio` (`ColorFloat`) - The contrast ratio to reach (4.5 for WCAG AA text).
+    ///
+    /// # Returns
+    ///
+    /// - `Color` - `self` if it already passes, otherwise the adjusted color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use codimate::color::Color;
+    ///
+    /// let editor = Color::new(30, 30, 46, 255);
+    /// let comment = Color::new(88, 91, 112, 255).ensure_contrast(editor, 4.5);
+    /// assert!(comment.contrast_ratio(editor) >= 4.5);
+    /// ```
+    #[must_use]
+    pub fn ensure_contrast(self, bg: Color, target_ratio: ColorFloat) -> Color {
+        self.adjust_lightness(target_ratio, |c| c.contrast_ratio(bg))
+    }
+
+    /// Adjust a color's lightness until it reaches an APCA lightness contrast of at
+    /// least `target_lc` (absolute) as text on a background. Works like `ensure_contrast`.
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`Color`) - The text color to adjust.
+    /// - `bg` (`Color`) - The background color.
+    /// - `target_lc` (`ColorFloat`) - The Lc to reach (75 for body text, 60 for content).
+    ///
+    /// # Returns
+    ///
+    /// - `Color` - `self` if it already passes, otherwise the adjusted color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use codimate::color::Color;
+    ///
+    /// let paper = Color::new(239, 241, 245, 255);
+    /// let link = Color::new(114, 135, 253, 255).ensure_apca_contrast(paper, 60.0);
+    /// assert!(link.apca_contrast(paper).abs() >= 60.0);
+    /// ```
+    #[must_use]
+    pub fn ensure_apca_contrast(self, bg: Color, target_lc: ColorFloat) -> Color {
+        self.adjust_lightness(target_lc.abs(), |c| c.apca_contrast(bg).abs())
+    }
+
+    /// Simulate how a color looks to someone with a color vision deficiency.
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`Color`) - The color to simulate.
+    /// - `cvd` (`Cvd`) - The deficiency, at full severity.
+    ///
+    /// # Returns
+    ///
+    /// - `Color` - The color as it would be seen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use codimate::color::{Color, cvd::Cvd};
+    ///
+    /// let orange = Color::new(255, 165, 0, 255);
+    /// let seen = orange.simulate_cvd(Cvd::Protanopia);
+    /// ```
+    #[must_use]
+    pub fn simulate_cvd(self, cvd: Cvd) -> Color {
+        cvd.simulate(self)
+    }
+
     /// Lighten a color in 0.0-1.0 HSL space (by raising its luminance).
     ///
     /// # Arguments
@@ -594,7 +701,7 @@ impl Color {
     /// use codimate::color::Color;
     ///
     /// let navy = Color::new(0, 0, 128, 255);
-    /// let lightened = navy.lighten_linear();
+    /// let lightened = navy.lighten_linear(0.2);
     /// ```
     #[must_use]
     #[inline]
@@ -623,7 +730,7 @@ impl Color {
     /// use codimate::color::Color;
     ///
     /// let lemon_chiffon = Color::new(255, 250, 205, 255);
-    /// let darkened = lemon_chiffon.darken_linear();
+    /// let darkened = lemon_chiffon.darken_linear(0.2);
     /// ```
     #[must_use]
     #[inline]
@@ -791,7 +898,7 @@ impl Color {
     #[inline]
     #[cfg(feature = "alloc")]
     pub fn into_hex6(self) -> alloc::string::String {
-        format!("{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        alloc::format!("{:02x}{:02x}{:02x}", self.r, self.g, self.b)
     }
 
     /// Get an 8 character hex representation of a color (#RRGGBBAA).
@@ -816,7 +923,7 @@ impl Color {
     #[inline]
     #[cfg(feature = "alloc")]
     pub fn into_hex8(self) -> alloc::string::String {
-        format!("{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        alloc::format!("{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
     }
 
     /// Create a color from an HSL array.
@@ -833,7 +940,7 @@ impl Color {
     /// ```
     /// use codimate::color::Color;
     ///
-    /// let light_salmon = Color::from_hsl([17.143, 100.0, 73.922])
+    /// let light_salmon = Color::from_hsl([17.143, 100.0, 73.922]);
     /// ```
     #[must_use]
     #[inline]
@@ -980,7 +1087,7 @@ impl Color {
     /// use codimate::color::Color;
     ///
     /// let translucent_light_goldenrod_yellow = Color::new(250, 250, 210, 128);
-    /// let [h, s, l, a] = into_hsla(translucent_light_goldenrod_yellow);
+    /// let [h, s, l, a] = translucent_light_goldenrod_yellow.into_hsla();
     /// ```
     #[must_use]
     #[inline]
@@ -1033,7 +1140,7 @@ impl Color {
     /// ```
     /// use codimate::color::Color;
     ///
-    /// let light_yellow = Color::from_linear([1.0, 1.0, 0.745404]);
+    /// let light_yellow = Color::from_linear([1.0, 1.0, 0.745404, 1.0]);
     /// ```
     #[must_use]
     #[inline]
@@ -1098,22 +1205,8 @@ impl Color {
     #[must_use]
     #[inline]
     pub fn from_oklab(lab: [ColorFloat; 3]) -> Self {
-        // source: https://bottosson.github.io/posts/oklab/
-
-        let l_ = lab[0] + 0.39633778 * lab[1] + 0.21580376 * lab[2];
-        let m_ = lab[0] - 0.105561346 * lab[1] - 0.06385417 * lab[2];
-        let s_ = lab[0] - 0.08948418 * lab[1] - 1.2914856 * lab[2];
-
-        let l = l_ * l_ * l_;
-        let m = m_ * m_ * m_;
-        let s = s_ * s_ * s_;
-
-        Self::from_linear([
-            4.0767417 * l - 3.3077116 * m + 0.23096993 * s,
-            -1.268438 * l + 2.6097574 * m - 0.3413194 * s,
-            -0.0041960863 * l - 0.7034186 * m + 1.7076147 * s,
-            1.0,
-        ])
+        let [r, g, b] = Self::oklab_to_linear(lab);
+        Self::from_linear([r, g, b, 1.0])
     }
 
     /// Get an OKLAB representation of a color.
@@ -1137,19 +1230,8 @@ impl Color {
     #[must_use]
     #[inline]
     pub fn into_oklab(self) -> [ColorFloat; 3] {
-        // source: https://bottosson.github.io/posts/oklab/
-
-        let lin = self.into_linear();
-
-        let l = (0.41222147 * lin[0] + 0.53633254 * lin[1] + 0.051445993 * lin[2]).cbrt();
-        let m = (0.2119035 * lin[0] + 0.6806996 * lin[1] + 0.10739696 * lin[2]).cbrt();
-        let s = (0.08830246 * lin[0] + 0.28171884 * lin[1] + 0.6299787 * lin[2]).cbrt();
-
-        [
-            0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
-            1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
-            0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
-        ]
+        let [r, g, b, _] = self.into_linear();
+        Self::linear_to_oklab([r, g, b])
     }
 
     /// Create a color from an OKLCH array.
@@ -1172,41 +1254,8 @@ impl Color {
     #[must_use]
     #[inline]
     pub fn from_oklch(lch: [ColorFloat; 3]) -> Self {
-        // Gamut mapping to keep rgb valid when converting
-        // current method: chroma reduction at fixed L and H
-        // switch to Björn Ottosson's "gamut mapping in OKLCH"
-        // in the future if perfect ramping needed
-        let within = |rgb: [ColorFloat; 3]| {
-            rgb[0] >= 0.0
-                && rgb[0] <= 1.0
-                && rgb[1] >= 0.0
-                && rgb[1] <= 1.0
-                && rgb[2] >= 0.0
-                && rgb[2] <= 1.0
-        };
-        let to_srgb = |lch: [ColorFloat; 3]| {
-            let lin = Self::from_oklab(Self::oklch_to_oklab(lch)).into_linear();
-            [lin[0], lin[1], lin[2]]
-        };
-
-        if within(to_srgb(lch)) {
-            return Self::from_oklab(Self::oklch_to_oklab(lch));
-        }
-
-        // shrink c
-        let (mut lo, mut hi) = (0.0f32, lch[1]);
-        for _ in 0..24 {
-            // ~1e-7 precision
-            let mid = 0.5 * (lo + hi);
-            let test = [lch[0], mid, lch[2]];
-            if within(to_srgb(test)) {
-                lo = mid;
-            } else {
-                hi = mid;
-            }
-        }
-
-        Self::from_oklab(Self::oklch_to_oklab([lch[0], lo, lch[2]]))
+        // out of gamut colors lose chroma at fixed L and H (CSS Color 4 gamut mapping)
+        WideColor::from_oklch(ColorSpace::SrgbLinear, lch, 1.0).into()
     }
 
     /// Get an OKLCH representation of a color.
@@ -1239,12 +1288,136 @@ impl Color {
         [l, c, h]
     }
 
+    /// Get the name of the closest CSS named color, by OKLAB distance.
+    /// Meant for debug output, alpha is ignored and fully transparent colors are `"transparent"`.
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`Color`) - The color to name.
+    ///
+    /// # Returns
+    ///
+    /// - `&'static str` - The closest CSS color name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use codimate::color::Color;
+    ///
+    /// let almost_tomato = Color::new(250, 100, 70, 255);
+    /// assert_eq!(almost_tomato.nearest_name(), "tomato");
+    /// ```
+    #[must_use]
+    pub fn nearest_name(self) -> &'static str {
+        if self.a == 0 {
+            return "transparent";
+        }
+        crate::color::named::nearest(self).0
+    }
+
     // --- private methods --- //
 
+    /// Move OKLCH lightness toward black or white until `score` reaches `target`,
+    /// taking the direction that needs the smaller move.
+    fn adjust_lightness(self, target: ColorFloat, score: impl Fn(Color) -> ColorFloat) -> Color {
+        if score(self) >= target {
+            return self;
+        }
+
+        let [l, c, h] = self.into_oklch();
+        let mut fallback = (score(self), self);
+        // near black and white even in-gamut tints lose a little contrast,
+        // so if keeping the chroma can't get there, fall back to greys
+        for c in [c, 0.0] {
+            let at = |l: ColorFloat| Self::from_oklch([l, c, h]).with_alpha(self.a);
+
+            let mut best: Option<(ColorFloat, Color)> = None;
+            for end in [0.0, 1.0] {
+                let extreme = at(end);
+                let s = score(extreme);
+                if s < target {
+                    if s > fallback.0 {
+                        fallback = (s, extreme);
+                    }
+                    continue;
+                }
+
+                // `hi` always passes, so the result does even if the score isn't monotonic
+                let (mut lo, mut hi) = (l, end);
+                for _ in 0..20 {
+                    let mid = 0.5 * (lo + hi);
+                    if score(at(mid)) >= target {
+                        hi = mid;
+                    } else {
+                        lo = mid;
+                    }
+                }
+                let moved = (hi - l).abs();
+                if best.is_none_or(|(d, _)| moved < d) {
+                    best = Some((moved, at(hi)));
+                }
+            }
+            if let Some((_, found)) = best {
+                return found;
+            }
+        }
+        fallback.1
+    }
+
+    /// Convert linear sRGB to OKLAB without quantizing.
+    #[must_use]
+    #[inline]
+    pub(crate) fn linear_to_oklab(lin: [ColorFloat; 3]) -> [ColorFloat; 3] {
+        // source: https://bottosson.github.io/posts/oklab/
+
+        let l = (0.41222147 * lin[0] + 0.53633254 * lin[1] + 0.051445993 * lin[2]).cbrt();
+        let m = (0.2119035 * lin[0] + 0.6806996 * lin[1] + 0.10739696 * lin[2]).cbrt();
+        let s = (0.08830246 * lin[0] + 0.28171884 * lin[1] + 0.6299787 * lin[2]).cbrt();
+
+        [
+            0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+            1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+            0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+        ]
+    }
+
+    /// Convert OKLAB to linear sRGB without quantizing (or clamping).
+    #[must_use]
+    #[inline]
+    pub(crate) fn oklab_to_linear(lab: [ColorFloat; 3]) -> [ColorFloat; 3] {
+        // source: https://bottosson.github.io/posts/oklab/
+
+        let l_ = lab[0] + 0.39633778 * lab[1] + 0.21580376 * lab[2];
+        let m_ = lab[0] - 0.105561346 * lab[1] - 0.06385417 * lab[2];
+        let s_ = lab[0] - 0.08948418 * lab[1] - 1.2914856 * lab[2];
+
+        let l = l_ * l_ * l_;
+        let m = m_ * m_ * m_;
+        let s = s_ * s_ * s_;
+
+        [
+            4.0767417 * l - 3.3077116 * m + 0.23096993 * s,
+            -1.268438 * l + 2.6097574 * m - 0.3413194 * s,
+            -0.0041960863 * l - 0.7034186 * m + 1.7076147 * s,
+        ]
+    }
+
+    /// Encode a linear float into an sRGB float (0.0-1.0) without quantizing to 8 bits.
+    #[must_use]
+    #[inline]
+    pub(crate) fn encode_srgb_unit(lin: ColorFloat) -> ColorFloat {
+        let l = lin.clamp(0.0, 1.0);
+        if l <= 0.0031308 {
+            12.92 * l
+        } else {
+            1.055 * l.powf(1.0 / 2.4) - 0.055
+        }
+    }
+
     /// Convert an OKLCH array to an OKLAB array.
     #[must_use]
     #[inline]
-    fn oklch_to_oklab(lch: [ColorFloat; 3]) -> [ColorFloat; 3] {
+    pub(crate) fn oklch_to_oklab(lch: [ColorFloat; 3]) -> [ColorFloat; 3] {
         let (l, c, h) = (lch[0], lch[1], lch[2]);
         let h = h.to_radians();
         let a = c * h.cos();
@@ -1263,25 +1436,37 @@ impl Color {
     #[cfg(not(feature = "srgb_lut"))]
     #[inline]
     fn decode_srgb(srgb_u8: u8) -> ColorFloat {
-        let srgb = (srgb_u8 as ColorFloat) / 255.0;
-        if srgb <= 0.04045 {
-            srgb / 12.92
-        } else {
-            ((srgb + 0.055) / 1.055).powf(2.4)
-        }
+        Self::decode_srgb_analytic(srgb_u8)
     }
 
     /// Encode an 8 bit sRGB value into a linear float using a lookup table.
     #[cfg(feature = "srgb_lut")]
     #[inline]
     fn encode_srgb(lin: ColorFloat) -> u8 {
-        crate::color::lut::encode_srgb_lut_f32(lin)
+        crate::color::lut::encode_srgb_lut_f32(lin as _)
     }
 
     /// Encode a linear float into an 8 bit sRGB value.
     #[cfg(not(feature = "srgb_lut"))]
     #[inline]
     fn encode_srgb(lin: ColorFloat) -> u8 {
+        Self::encode_srgb_analytic(lin)
+    }
+
+    /// The sRGB decoding curve, computed rather than looked up.
+    #[inline]
+    pub(crate) fn decode_srgb_analytic(srgb_u8: u8) -> ColorFloat {
+        let srgb = (srgb_u8 as ColorFloat) / 255.0;
+        if srgb <= 0.04045 {
+            srgb / 12.92
+        } else {
+            ((srgb + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    /// The sRGB encoding curve, computed rather than looked up.
+    #[inline]
+    pub(crate) fn encode_srgb_analytic(lin: ColorFloat) -> u8 {
         let l = lin.clamp(0.0, 1.0);
         if l <= 0.0031308 {
             ((12.92 * l) * 255.0 + 0.5).floor() as u8
@@ -1306,17 +1491,9 @@ impl Color {
         let n = r.min(g).min(b);
         let x = r.max(g).max(b);
         if n < 0.0 {
-            c.iter()
-                .map(|&v| l + (((v - l) * l) / (l - n)))
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap()
+            c.map(|v| l + (((v - l) * l) / (l - n)))
         } else if x > 1.0 {
-            c.iter()
-                .map(|&v| l + (((v - l) * (1.0 - l)) / (x - l)))
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap()
+            c.map(|v| l + (((v - l) * (1.0 - l)) / (x - l)))
         } else {
             c
         }
@@ -1426,17 +1603,11 @@ impl Color {
     where
         F: FnMut(ColorFloat, ColorFloat) -> ColorFloat,
     {
-        backdrop
-            .iter()
-            .zip(source)
-            .map(|(&b, &s)| f(b, s))
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap()
+        [0, 1, 2].map(|i| f(backdrop[i], source[i]))
     }
 
     #[inline]
-    fn blend_channel(
+    pub(crate) fn blend_channel(
         mode: BlendMode,
         backdrop: [ColorFloat; 3],
         source: [ColorFloat; 3],
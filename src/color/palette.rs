@@ -0,0 +1,311 @@
+#![allow(dead_code)]
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::color::math::Float as _;
+use crate::color::{ColorFloat, model::Color, space::ColorSpace, wide::WideColor};
+
+/// The steps of a Material-style tonal scale, lightest first.
+pub const TONAL_STEPS: [u16; 10] = [50, 100, 200, 300, 400, 500, 600, 700, 800, 900];
+
+// OKLCH lightness of each step. 500 sits in the middle, where most brand colors live
+const TONAL_LIGHTNESS: [ColorFloat; 10] =
+    [0.97, 0.93, 0.86, 0.78, 0.70, 0.62, 0.54, 0.46, 0.38, 0.30];
+
+/// Build a tonal scale (50 to 900) around a color.
+///
+/// Every step keeps the base color's OKLCH hue and chroma at a fixed lightness, so
+/// scales built from different hues line up step for step. Light tints and dark shades
+/// can't hold as much chroma, so there it's capped to what sRGB can show at that hue.
+///
+/// # Arguments
+///
+/// - `base` (`Color`) - The color to build the scale around. Its alpha is kept.
+///
+/// # Returns
+///
+/// - `[(u16, Color); 10]` - The steps from `TONAL_STEPS` with their colors, lightest first.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::color::{Color, palette::tonal_scale};
+///
+/// let blues = tonal_scale(Color::new(30, 102, 245, 255));
+/// let (step, blue_100) = blues[1];
+/// assert_eq!(step, 100);
+/// ```
+#[must_use]
+pub fn tonal_scale(base: Color) -> [(u16, Color); 10] {
+    let [_, c, h] = base.into_oklch();
+    let alpha = base.into_rgba()[3];
+    let mut out = [(0, base); 10];
+    for (slot, (step, l)) in out
+        .iter_mut()
+        .zip(TONAL_STEPS.into_iter().zip(TONAL_LIGHTNESS))
+    {
+        let c = c.min(max_chroma(l, h));
+        *slot = (step, Color::from_oklch([l, c, h]).with_alpha(alpha));
+    }
+    out
+}
+
+/// Rotate a color's OKLCH hue, keeping its lightness and chroma (as far as sRGB allows).
+///
+/// # Arguments
+///
+/// - `color` (`Color`) - The color to rotate. Its alpha is kept.
+/// - `degrees` (`ColorFloat`) - How far to turn the hue.
+///
+/// # Returns
+///
+/// - `Color` - The rotated color.
+#[must_use]
+pub fn rotate_hue(color: Color, degrees: ColorFloat) -> Color {
+    let [l, c, h] = color.into_oklch();
+    Color::from_oklch([l, c, (h + degrees).rem_euclid(360.0)]).with_alpha(color.into_rgba()[3])
+}
+
+/// The color and the one opposite it on the OKLCH hue wheel.
+#[must_use]
+pub fn complementary(base: Color) -> [Color; 2] {
+    [base, rotate_hue(base, 180.0)]
+}
+
+/// The color and the two 150° away from it, a softer complementary.
+#[must_use]
+pub fn split_complementary(base: Color) -> [Color; 3] {
+    [base, rotate_hue(base, 150.0), rotate_hue(base, 210.0)]
+}
+
+/// The color and the two that split the OKLCH hue wheel into thirds with it.
+#[must_use]
+pub fn triadic(base: Color) -> [Color; 3] {
+    [base, rotate_hue(base, 120.0), rotate_hue(base, 240.0)]
+}
+
+/// The color and the two `spread` degrees to either side of it (30° is typical).
+/// The color stays in the middle.
+#[must_use]
+pub fn analogous(base: Color, spread: ColorFloat) -> [Color; 3] {
+    [rotate_hue(base, -spread), base, rotate_hue(base, spread)]
+}
+
+/// The most chroma a lightness and hue can have in sRGB.
+fn max_chroma(l: ColorFloat, h: ColorFloat) -> ColorFloat {
+    let fits =
+        |c: ColorFloat| WideColor::from_oklch(ColorSpace::SrgbLinear, [l, c, h], 1.0).in_gamut();
+    // nothing in sRGB gets near 0.4
+    let (mut lo, mut hi) = (0.0, 0.4);
+    for _ in 0..20 {
+        let mid = 0.5 * (lo + hi);
+        if fits(mid) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Build `n` colors for telling categories apart (bars, graph nodes, array cells).
+///
+/// Hues are spread evenly around the OKLCH wheel starting at `hue`, all at the same
+/// lightness and chroma so no color looks more important than the others. The chroma
+/// is capped at what every one of the hues can show in sRGB, since gamut mapping each
+/// color separately would make some of them duller than the rest.
+///
+/// # Arguments
+///
+/// - `n` (`usize`) - How many colors.
+/// - `lightness` (`ColorFloat`) - OKLCH lightness, 0.0-1.0 (0.7 reads well on dark backgrounds).
+/// - `chroma` (`ColorFloat`) - The chroma to aim for (around 0.15 is vivid).
+/// - `hue` (`ColorFloat`) - The first color's hue in degrees.
+///
+/// # Returns
+///
+/// - `Vec<Color>` - The colors, in hue order.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::color::palette::categorical;
+///
+/// let series = categorical(6, 0.72, 0.15, 25.0);
+/// ```
+#[must_use]
+#[cfg(feature = "alloc")]
+pub fn categorical(
+    n: usize,
+    lightness: ColorFloat,
+    chroma: ColorFloat,
+    hue: ColorFloat,
+) -> Vec<Color> {
+    let l = lightness.clamp(0.0, 1.0);
+    let hues: Vec<ColorFloat> = (0..n)
+        .map(|i| (hue + 360.0 * i as ColorFloat / n as ColorFloat).rem_euclid(360.0))
+        .collect();
+    let c = hues
+        .iter()
+        .map(|&h| max_chroma(l, h))
+        .fold(chroma.max(0.0), ColorFloat::min);
+    hues.into_iter()
+        .map(|h| Color::from_oklch([l, c, h]))
+        .collect()
+}
+
+/// Build an `n` step ramp for ordered data (low to high), interpolated in OKLCH.
+///
+/// Pick endpoints with clearly different lightness (a light and a dark color) so
+/// the order still reads in grayscale and for colorblind viewers.
+///
+/// # Arguments
+///
+/// - `low` (`Color`) - The color of the smallest value.
+/// - `high` (`Color`) - The color of the largest value.
+/// - `n` (`usize`) - How many steps, endpoints included.
+///
+/// # Returns
+///
+/// - `Vec<Color>` - The ramp from `low` to `high`.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::color::{Color, palette::sequential};
+///
+/// // cells of a heatmap, from unvisited to hot
+/// let heat = sequential(Color::new(255, 247, 188, 255), Color::new(153, 52, 4, 255), 9);
+/// ```
+#[must_use]
+#[cfg(feature = "alloc")]
+pub fn sequential(low: Color, high: Color, n: usize) -> Vec<Color> {
+    match n {
+        0 => Vec::new(),
+        1 => vec![low],
+        _ => (0..n)
+            .map(|i| low.lerp_oklch(high, i as ColorFloat / (n - 1) as ColorFloat))
+            .collect(),
+    }
+}
+
+/// Build an `n` step ramp for data that diverges from a midpoint (negative/positive,
+/// smaller/bigger than a pivot), interpolated in OKLCH.
+///
+/// # Arguments
+///
+/// - `low` (`Color`) - The color of the most negative value.
+/// - `mid` (`Color`) - The color of the midpoint, usually a light neutral.
+/// - `high` (`Color`) - The color of the most positive value.
+/// - `n` (`usize`) - How many steps. With an odd `n` the middle step is exactly `mid`.
+///
+/// # Returns
+///
+/// - `Vec<Color>` - The ramp from `low` through `mid` to `high`.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::color::{Color, palette::diverging};
+///
+/// let blue = Color::new(33, 102, 172, 255);
+/// let white = Color::new(247, 247, 247, 255);
+/// let red = Color::new(178, 24, 43, 255);
+/// let diff = diverging(blue, white, red, 11);
+/// ```
+#[must_use]
+#[cfg(feature = "alloc")]
+pub fn diverging(low: Color, mid: Color, high: Color, n: usize) -> Vec<Color> {
+    if n < 2 {
+        return vec![mid; n];
+    }
+    (0..n)
+        .map(|i| {
+            // -1.0 at low, 0.0 at mid, 1.0 at high
+            let t = 2.0 * i as ColorFloat / (n - 1) as ColorFloat - 1.0;
+            if t < 0.0 {
+                mid.lerp_oklch(low, -t)
+            } else {
+                mid.lerp_oklch(high, t)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hue_gap(a: ColorFloat, b: ColorFloat) -> ColorFloat {
+        let d = (a - b).abs() % 360.0;
+        d.min(360.0 - d)
+    }
+
+    #[test]
+    fn tonal_scales_get_darker_and_keep_hue() {
+        let base = Color::new(30, 102, 245, 255);
+        let [_, _, h] = base.into_oklch();
+        let scale = tonal_scale(base);
+        for pair in scale.windows(2) {
+            assert!(pair[0].1.into_oklch()[0] > pair[1].1.into_oklch()[0]);
+        }
+        for (step, c) in scale {
+            let [_, c, ch] = c.into_oklch();
+            if c > 0.03 {
+                assert!(hue_gap(h, ch) < 6.0, "{step}: {ch} vs {h}");
+            }
+        }
+    }
+
+    #[test]
+    fn harmonies_turn_the_hue() {
+        let base = Color::new(203, 166, 247, 255);
+        let h = base.into_oklch()[2];
+        let [_, comp] = complementary(base);
+        assert!(hue_gap(comp.into_oklch()[2], h + 180.0) < 3.0);
+        let [_, b, c] = triadic(base);
+        assert!(hue_gap(b.into_oklch()[2], h + 120.0) < 3.0);
+        assert!(hue_gap(c.into_oklch()[2], h + 240.0) < 3.0);
+        let [left, mid, right] = analogous(base, 30.0);
+        assert_eq!(mid, base);
+        assert!(hue_gap(left.into_oklch()[2], h - 30.0) < 3.0);
+        assert!(hue_gap(right.into_oklch()[2], h + 30.0) < 3.0);
+        assert_eq!(split_complementary(base).len(), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn categorical_colors_are_even() {
+        let colors = categorical(8, 0.72, 0.3, 0.0);
+        assert_eq!(colors.len(), 8);
+
+        let lch: Vec<_> = colors.iter().map(|c| c.into_oklch()).collect();
+        for w in lch.windows(2) {
+            // same lightness and chroma (up to 8 bit rounding), evenly spaced hue
+            assert!((w[0][0] - w[1][0]).abs() < 0.01, "{w:?}");
+            assert!((w[0][1] - w[1][1]).abs() < 0.01, "{w:?}");
+            assert!((hue_gap(w[0][2], w[1][2]) - 45.0).abs() < 3.0, "{w:?}");
+        }
+        assert!(lch[0][1] > 0.05);
+        assert!(categorical(0, 0.5, 0.1, 0.0).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ramps_hit_their_endpoints() {
+        let (lo, hi) = (Color::new(255, 247, 188, 255), Color::new(153, 52, 4, 255));
+        let ramp = sequential(lo, hi, 9);
+        assert_eq!((ramp[0], ramp[8]), (lo, hi));
+        for w in ramp.windows(2) {
+            assert!(w[0].into_oklch()[0] > w[1].into_oklch()[0]);
+        }
+
+        let mid = Color::new(247, 247, 247, 255);
+        let div = diverging(Color::BLUE, mid, Color::RED, 7);
+        assert_eq!((div[0], div[3], div[6]), (Color::BLUE, mid, Color::RED));
+        assert_eq!(diverging(Color::BLUE, mid, Color::RED, 1), [mid]);
+    }
+}
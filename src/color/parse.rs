@@ -1,26 +1,45 @@
 #![allow(dead_code)]
 
-use core::fmt;
+use core::{fmt, ops::RangeInclusive};
 
-use crate::color::model::Color;
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::color::math::Float as _;
+use crate::color::{
+    ColorFloat,
+    model::Color,
+    named,
+    space::{self, ColorSpace},
+    wide::WideColor,
+};
 
 /// An error caused by parsing an invalid color string slice.
 ///
+/// Positions are byte offsets into the string that was passed in.
+///
 /// # Variants
 ///
 /// - `Empty` - The given string slice was empty or all whitespace.
 /// - `InvalidLength` - The given string slice had an invalid length.
 /// - `InvalidHex` - The given string slice was not a valid hex representation.
+/// - `InvalidFunc` - Unknown color function or space, or malformed function syntax
+///   (missing parenthesis, wrong number of arguments, bad separator).
+/// - `OutOfRange` - A number was outside of what its channel allows, like `rgb(300 0 0)`.
+/// - `InvalidNumber` - Something that should have been a number wasn't,
+///   or it had a unit that isn't allowed there.
+/// - `UnknownName` - A bare word that isn't a CSS named color.
+/// - `CurrentColor` - `currentcolor` was used where there's no current color to resolve
+///   it against (use `parse_color_with` to provide one).
 ///
 /// # Examples
 ///
 /// ```
-/// use codimate::color::{ColorParseError, parse_color};
+/// use codimate::color::parse::parse_color;
 ///
 /// let hex = "#fff";
-/// let color = parse_color(hex)
+/// let color = parse_color(hex);
 /// match color {
-///     Ok(v) => println!("Good color value: {}", color.into_hex6()),
+///     Ok(v) => println!("Good color value: {}", v.into_hex6()),
 ///     Err(e) => println!("Error parsing color: {}", e),
 /// }
 /// ```
@@ -29,6 +48,11 @@ pub enum ColorParseError {
     Empty,
     InvalidLength,
     InvalidHex,
+    InvalidFunc { pos: usize },
+    OutOfRange { pos: usize },
+    InvalidNumber { pos: usize },
+    UnknownName { pos: usize },
+    CurrentColor,
 }
 
 impl fmt::Display for ColorParseError {
@@ -38,6 +62,11 @@ impl fmt::Display for ColorParseError {
             Empty => "empty color string",
             InvalidLength => "invalid hex length",
             InvalidHex => "invalid hex digits",
+            InvalidFunc { pos } => return write!(f, "invalid color function at byte {pos}"),
+            OutOfRange { pos } => return write!(f, "color value out of range at byte {pos}"),
+            InvalidNumber { pos } => return write!(f, "invalid number at byte {pos}"),
+            UnknownName { pos } => return write!(f, "unknown color name at byte {pos}"),
+            CurrentColor => "currentcolor has nothing to resolve against",
         };
         f.write_str(msg)
     }
@@ -132,6 +161,13 @@ fn parse_hex(hex: &str) -> Result<Color, ColorParseError> {
 /// Parse a color from a string slice.
 /// This function supports:
 /// * Hex string slices
+/// * CSS Color 4 functions: `rgb()`/`rgba()` and `hsl()`/`hsla()` (comma or space separated),
+///   `hwb()`, `lab()`, `lch()`, `oklab()`, `oklch()` and `color()` with any predefined space,
+///   all with an optional `/ alpha`
+/// * The 148 CSS named colors and `transparent` (case-insensitive)
+///
+/// Colors outside of sRGB (from `lab()`, `color(display-p3 ...)`, etc.) are brought into
+/// gamut by reducing their OKLCH chroma.
 ///
 /// # Arguments
 ///
@@ -144,30 +180,402 @@ fn parse_hex(hex: &str) -> Result<Color, ColorParseError> {
 /// # Examples
 ///
 /// ```
-/// use codimate::color::parse_color;
+/// use codimate::color::parse::parse_color;
 ///
 /// let hex = "#fff";
-/// let color = parse_color(hex)
+/// let color = parse_color(hex);
 /// match color {
-///     Ok(v) => println!("Good color value: {}", color.into_hex6()),
+///     Ok(v) => println!("Good color value: {}", v.into_hex6()),
 ///     Err(e) => println!("Error parsing color: {}", e),
 /// }
 /// ```
-pub fn parse_color(mut s: &str) -> Result<Color, ColorParseError> {
+pub fn parse_color(s: &str) -> Result<Color, ColorParseError> {
+    parse(s, None)
+}
+
+/// Parse a color from a string slice, resolving `currentcolor` to a given color.
+///
+/// Accepts everything `parse_color` does.
+///
+/// # Arguments
+///
+/// - `s` (`&str`) - The string slice to parse.
+/// - `current_color` (`Color`) - What `currentcolor` means here, like a node's inherited color.
+///
+/// # Returns
+///
+/// - `Result<Color, ColorParseError>` - The result of the parse.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::color::{Color, parse::parse_color_with};
+///
+/// let text = Color::new(205, 214, 244, 255);
+/// assert_eq!(parse_color_with("currentColor", text), Ok(text));
+/// ```
+pub fn parse_color_with(s: &str, current_color: Color) -> Result<Color, ColorParseError> {
+    parse(s, Some(current_color))
+}
+
+fn parse(s: &str, current_color: Option<Color>) -> Result<Color, ColorParseError> {
     use ColorParseError::*;
 
-    if s.trim().is_empty() {
+    let trimmed = s.trim();
+    if trimmed.is_empty() {
         return Err(Empty);
     }
-    s = s.trim();
 
     // Hex-like
-    if let Some(rest) = s.strip_prefix('#') {
+    if let Some(rest) = trimmed.strip_prefix('#') {
         let hex = rest.trim();
         return parse_hex(hex);
     }
 
-    Err(InvalidHex)
+    // keep positions relative to what the caller passed in
+    let start = s.len() - s.trim_start().len();
+
+    // keywords are a single identifier, functions have a parenthesis after theirs
+    if !trimmed.contains('(') && trimmed.bytes().all(|c| c.is_ascii_alphabetic()) {
+        if trimmed.eq_ignore_ascii_case("transparent") {
+            return Ok(Color::TRANSPARENT);
+        }
+        if trimmed.eq_ignore_ascii_case("currentcolor") {
+            return current_color.ok_or(CurrentColor);
+        }
+        return named::lookup(trimmed).ok_or(UnknownName { pos: start });
+    }
+
+    parse_func(s, start)
+}
+
+/// One parsed function argument.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Value {
+    None,
+    Number(ColorFloat),
+    Percent(ColorFloat),
+    /// Always in degrees.
+    Angle(ColorFloat),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Arg {
+    value: Value,
+    pos: usize,
+}
+
+impl Arg {
+    /// A plain number, with percentages scaled so 100% is `percent_scale`.
+    /// `none` is zero.
+    fn number(
+        self,
+        percent_scale: ColorFloat,
+        range: RangeInclusive<ColorFloat>,
+    ) -> Result<ColorFloat, ColorParseError> {
+        let v = match self.value {
+            Value::None => 0.0,
+            Value::Number(n) => n,
+            Value::Percent(p) => p / 100.0 * percent_scale,
+            Value::Angle(_) => return Err(ColorParseError::InvalidNumber { pos: self.pos }),
+        };
+        if range.contains(&v) {
+            Ok(v)
+        } else {
+            Err(ColorParseError::OutOfRange { pos: self.pos })
+        }
+    }
+
+    /// A hue in degrees. Bare numbers are degrees too.
+    fn hue(self) -> Result<ColorFloat, ColorParseError> {
+        match self.value {
+            Value::None => Ok(0.0),
+            Value::Number(n) | Value::Angle(n) => Ok(n),
+            Value::Percent(_) => Err(ColorParseError::InvalidNumber { pos: self.pos }),
+        }
+    }
+}
+
+const ANY: RangeInclusive<ColorFloat> = ColorFloat::NEG_INFINITY..=ColorFloat::INFINITY;
+const NON_NEGATIVE: RangeInclusive<ColorFloat> = 0.0..=ColorFloat::INFINITY;
+
+/// A byte cursor over the input. Never allocates.
+struct Cursor<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.src.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), ColorParseError> {
+        self.skip_ws();
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(ColorParseError::InvalidFunc { pos: self.pos })
+        }
+    }
+
+    fn ident(&mut self) -> &'a str {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == b'-')
+        {
+            self.pos += 1;
+        }
+        &self.src[start..self.pos]
+    }
+
+    /// A number with an optional unit, or `none`.
+    fn arg(&mut self) -> Result<Arg, ColorParseError> {
+        use ColorParseError::*;
+
+        self.skip_ws();
+        let pos = self.pos;
+        match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() => {
+                return if self.ident().eq_ignore_ascii_case("none") {
+                    Ok(Arg {
+                        value: Value::None,
+                        pos,
+                    })
+                } else {
+                    Err(InvalidNumber { pos })
+                };
+            }
+            None | Some(b')' | b',' | b'/') => return Err(InvalidFunc { pos }),
+            _ => {}
+        }
+
+        // [+-] digits [. digits] [e [+-] digits]
+        let digits = |cur: &mut Self| {
+            let start = cur.pos;
+            while cur.peek().is_some_and(|c| c.is_ascii_digit()) {
+                cur.pos += 1;
+            }
+            cur.pos > start
+        };
+        if matches!(self.peek(), Some(b'+' | b'-')) {
+            self.pos += 1;
+        }
+        let mut any = digits(self);
+        if self.eat(b'.') {
+            any |= digits(self);
+        }
+        if !any {
+            return Err(InvalidNumber { pos });
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            let mark = self.pos;
+            self.pos += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                self.pos = mark;
+            }
+        }
+        let n: ColorFloat = self.src[pos..self.pos]
+            .parse()
+            .map_err(|_| InvalidNumber { pos })?;
+
+        let unit_pos = self.pos;
+        let value = if self.eat(b'%') {
+            Value::Percent(n)
+        } else {
+            match self.ident() {
+                "" => Value::Number(n),
+                u if u.eq_ignore_ascii_case("deg") => Value::Angle(n),
+                u if u.eq_ignore_ascii_case("rad") => Value::Angle(n.to_degrees()),
+                u if u.eq_ignore_ascii_case("grad") => Value::Angle(n * 0.9),
+                u if u.eq_ignore_ascii_case("turn") => Value::Angle(n * 360.0),
+                _ => return Err(InvalidNumber { pos: unit_pos }),
+            }
+        };
+        Ok(Arg { value, pos })
+    }
+
+    /// Three channels plus an optional alpha, then the closing parenthesis.
+    /// `legacy` allows the old comma separated form (`rgb(1, 2, 3, 0.5)`).
+    fn channels(&mut self, legacy: bool) -> Result<([Arg; 3], Option<Arg>), ColorParseError> {
+        let first = self.arg()?;
+        self.skip_ws();
+
+        let comma_pos = self.pos;
+        let (rest, alpha) = if self.eat(b',') {
+            if !legacy {
+                return Err(ColorParseError::InvalidFunc { pos: comma_pos });
+            }
+            let second = self.arg()?;
+            self.expect(b',')?;
+            let third = self.arg()?;
+            self.skip_ws();
+            let alpha = if self.eat(b',') {
+                Some(self.arg()?)
+            } else {
+                None
+            };
+            ([second, third], alpha)
+        } else {
+            let second = self.arg()?;
+            let third = self.arg()?;
+            self.skip_ws();
+            let alpha = if self.eat(b'/') {
+                Some(self.arg()?)
+            } else {
+                None
+            };
+            ([second, third], alpha)
+        };
+
+        self.expect(b')')?;
+        self.skip_ws();
+        if self.pos != self.src.len() {
+            return Err(ColorParseError::InvalidFunc { pos: self.pos });
+        }
+        Ok(([first, rest[0], rest[1]], alpha))
+    }
+}
+
+/// Parse a CSS color function starting at byte `start` of `src`.
+fn parse_func(src: &str, start: usize) -> Result<Color, ColorParseError> {
+    use ColorParseError::*;
+
+    let mut cur = Cursor { src, pos: start };
+    let name = cur.ident();
+    // no space allowed between the name and the parenthesis
+    if name.is_empty() || !cur.eat(b'(') {
+        return Err(InvalidFunc { pos: cur.pos });
+    }
+    let is = |n: &str| name.eq_ignore_ascii_case(n);
+
+    if is("color") {
+        cur.skip_ws();
+        let space_pos = cur.pos;
+        let space = ColorSpace::from_css_name(cur.ident()).ok_or(InvalidFunc { pos: space_pos })?;
+        let ([c0, c1, c2], alpha) = cur.channels(false)?;
+        let c = [
+            c0.number(1.0, ANY)?,
+            c1.number(1.0, ANY)?,
+            c2.number(1.0, ANY)?,
+        ];
+        let alpha = parse_alpha(alpha)?;
+        return Ok(WideColor::new(space, c, alpha).into());
+    }
+
+    let legacy = is("rgb") || is("rgba") || is("hsl") || is("hsla");
+    let known = legacy
+        || ["hwb", "lab", "lch", "oklab", "oklch"]
+            .iter()
+            .any(|n| is(n));
+    if !known {
+        return Err(InvalidFunc { pos: start });
+    }
+
+    let ([a0, a1, a2], alpha) = cur.channels(legacy)?;
+    let alpha = parse_alpha(alpha)?;
+
+    if is("rgb") || is("rgba") {
+        let ch = |a: Arg| {
+            a.number(255.0, 0.0..=255.0)
+                .map(|v| (v + 0.5).floor() as u8)
+        };
+        return Ok(Color::new(ch(a0)?, ch(a1)?, ch(a2)?, alpha_u8(alpha)));
+    }
+    if is("hsl") || is("hsla") {
+        // bare numbers in the modern syntax mean percentages
+        let (h, s, l) = (
+            a0.hue()?,
+            a1.number(100.0, 0.0..=100.0)?,
+            a2.number(100.0, 0.0..=100.0)?,
+        );
+        return Ok(Color::from_hsl([h, s, l]).with_alpha(alpha_u8(alpha)));
+    }
+    if is("hwb") {
+        let (h, w, b) = (
+            a0.hue()?,
+            a1.number(100.0, 0.0..=100.0)? / 100.0,
+            a2.number(100.0, 0.0..=100.0)? / 100.0,
+        );
+        let rgb = if w + b >= 1.0 {
+            [w / (w + b); 3]
+        } else {
+            hue_rgb(h).map(|c| c * (1.0 - w - b) + w)
+        };
+        return Ok(from_linear_mapped(rgb.map(space::srgb_decode), alpha));
+    }
+
+    if is("lab") || is("lch") {
+        let l = a0.number(100.0, 0.0..=100.0)?;
+        let lab = if is("lab") {
+            [l, a1.number(125.0, ANY)?, a2.number(125.0, ANY)?]
+        } else {
+            space::lch_to_lab([l, a1.number(150.0, NON_NEGATIVE)?, a2.hue()?])
+        };
+        let xyz = space::mul(&space::D50_TO_D65, space::lab_to_xyz_d50(lab));
+        return Ok(from_linear_mapped(
+            space::mul(&space::XYZ_TO_LIN_SRGB, xyz),
+            alpha,
+        ));
+    }
+
+    let l = a0.number(1.0, 0.0..=1.0)?;
+    let lab = if is("oklab") {
+        [l, a1.number(0.4, ANY)?, a2.number(0.4, ANY)?]
+    } else {
+        space::lch_to_lab([l, a1.number(0.4, NON_NEGATIVE)?, a2.hue()?])
+    };
+    Ok(from_linear_mapped(Color::oklab_to_linear(lab), alpha))
+}
+
+fn parse_alpha(alpha: Option<Arg>) -> Result<ColorFloat, ColorParseError> {
+    match alpha {
+        Some(a) => a.number(1.0, 0.0..=1.0),
+        None => Ok(1.0),
+    }
+}
+
+#[inline]
+fn alpha_u8(alpha: ColorFloat) -> u8 {
+    (alpha * 255.0 + 0.5).floor() as u8
+}
+
+/// Fully saturated sRGB (0.0-1.0) at a hue in degrees.
+fn hue_rgb(h: ColorFloat) -> [ColorFloat; 3] {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = 1.0 - (h % 2.0 - 1.0).abs();
+    match h as u8 {
+        0 => [1.0, x, 0.0],
+        1 => [x, 1.0, 0.0],
+        2 => [0.0, 1.0, x],
+        3 => [0.0, x, 1.0],
+        4 => [x, 0.0, 1.0],
+        _ => [1.0, 0.0, x],
+    }
+}
+
+/// Linear sRGB that may be out of gamut to a `Color`, gamut mapped the CSS Color 4 way.
+fn from_linear_mapped(lin: [ColorFloat; 3], alpha: ColorFloat) -> Color {
+    WideColor::new(ColorSpace::SrgbLinear, lin, alpha).into()
 }
 
 impl core::str::FromStr for Color {
@@ -182,3 +590,101 @@ impl TryFrom<&str> for Color {
         parse_color(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ColorParseError::*;
+
+    fn rgba(s: &str) -> [u8; 4] {
+        parse_color(s)
+            .unwrap_or_else(|e| panic!("{s}: {e}"))
+            .into_rgba()
+    }
+
+    /// 50% sRGB is 127.5, a rounding tie. the exact math rounds it up, the lookup tables
+    /// are only good to a code value and can land on either side
+    fn assert_tie(s: &str, expect: [u8; 4]) {
+        let got = rgba(s);
+        if cfg!(feature = "srgb_lut") {
+            assert!(
+                got.iter().zip(expect).all(|(g, e)| g.abs_diff(e) <= 1),
+                "{s}: {got:?}"
+            );
+        } else {
+            assert_eq!(got, expect, "{s}");
+        }
+    }
+
+    #[test]
+    fn rgb_comma_space_percent_and_alpha() {
+        assert_eq!(rgba("rgb(255, 0, 128)"), [255, 0, 128, 255]);
+        assert_eq!(rgba("rgba(255,0,128,0.5)"), [255, 0, 128, 128]);
+        assert_eq!(rgba("rgb(255 0 128 / 50%)"), [255, 0, 128, 128]);
+        assert_eq!(rgba("  RGB(100% 0% 50%)  "), [255, 0, 128, 255]);
+        assert_eq!(rgba("rgb(none 1e2 .5e1)"), [0, 100, 5, 255]);
+    }
+
+    #[test]
+    fn hsl_and_hwb() {
+        assert_eq!(rgba("hsl(120, 100%, 50%)"), [0, 255, 0, 255]);
+        assert_eq!(rgba("hsla(0.5turn 100% 50% / 0.25)"), [0, 255, 255, 64]);
+        assert_eq!(rgba("hsl(240deg 100 50)"), [0, 0, 255, 255]);
+        assert_eq!(rgba("hwb(0 0% 0%)"), [255, 0, 0, 255]);
+        assert_eq!(rgba("hwb(120 100% 0%)"), [255, 255, 255, 255]);
+        // whiteness and blackness past 100% normalize to gray
+        assert_tie("hwb(200 60% 60%)", [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn lab_lch_oklab_oklch() {
+        assert_eq!(rgba("lab(100 0 0)"), [255, 255, 255, 255]);
+        assert_eq!(rgba("lch(0% 0 0)"), [0, 0, 0, 255]);
+        // css color 4 example: rebeccapurple
+        assert_eq!(rgba("lab(32.4% 38.4 -47.7)"), [102, 51, 153, 255]);
+        assert_eq!(rgba("lch(32.39 61.25 308.86)"), [102, 51, 153, 255]);
+        assert_eq!(rgba("oklab(0.628 0.225 0.126)"), [255, 0, 0, 255]);
+        assert_eq!(rgba("oklch(62.8% 0.2577 29.23 / 1)"), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn color_function_spaces() {
+        assert_tie("color(srgb 1 0.5 0)", [255, 128, 0, 255]);
+        assert_eq!(rgba("color(srgb-linear 1 0 0 / 0.5)"), [255, 0, 0, 128]);
+        assert_eq!(rgba("color(xyz-d65 0.9505 1 1.089)"), [255, 255, 255, 255]);
+        assert_eq!(rgba("color(display-p3 1 1 1)"), [255, 255, 255, 255]);
+        // p3 red doesn't fit in srgb, it's mapped instead of clipped into orange or pink
+        let [r, g, b, _] = rgba("color(display-p3 1 0 0)");
+        assert!(r == 255 && g < 40 && b < 40, "{r} {g} {b}");
+    }
+
+    #[test]
+    fn named_colors_and_keywords() {
+        assert_eq!(rgba("rebeccapurple"), [102, 51, 153, 255]);
+        assert_eq!(rgba(" DodgerBlue "), [30, 144, 255, 255]);
+        assert_eq!(rgba("transparent"), [0, 0, 0, 0]);
+        assert_eq!(parse_color("  blu"), Err(UnknownName { pos: 2 }));
+        assert_eq!(parse_color("currentcolor"), Err(CurrentColor));
+        assert_eq!(parse_color_with("CurrentColor", Color::RED), Ok(Color::RED));
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        assert_eq!(parse_color("rgb(300 0 0)"), Err(OutOfRange { pos: 4 }));
+        assert_eq!(parse_color("rgb(0 0 0 / 2)"), Err(OutOfRange { pos: 12 }));
+        assert_eq!(parse_color("  rgb(0 0x 0)"), Err(InvalidNumber { pos: 9 }));
+        assert_eq!(parse_color("rgb(0 abc 0)"), Err(InvalidNumber { pos: 6 }));
+        assert_eq!(parse_color("hsl(10% 0 0)"), Err(InvalidNumber { pos: 4 }));
+        assert_eq!(parse_color("rgb(0 0 0"), Err(InvalidFunc { pos: 9 }));
+        assert_eq!(parse_color("rgb(0 0)"), Err(InvalidFunc { pos: 7 }));
+        assert_eq!(parse_color("rgb(0 0 0) x"), Err(InvalidFunc { pos: 11 }));
+        assert_eq!(parse_color("lab(50, 0, 0)"), Err(InvalidFunc { pos: 6 }));
+        assert_eq!(parse_color("nope(1 2 3)"), Err(InvalidFunc { pos: 0 }));
+        assert_eq!(
+            parse_color("color(cmyk 1 2 3)"),
+            Err(InvalidFunc { pos: 6 })
+        );
+        assert_eq!(parse_color("#ggg"), Err(InvalidHex));
+        assert_eq!(parse_color("   "), Err(Empty));
+    }
+}
@@ -0,0 +1,297 @@
+use core::fmt;
+
+use ::serde::{
+    Deserializer, Serialize, Serializer,
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::{SerializeStruct, SerializeTuple},
+};
+
+use crate::color::model::Color;
+
+// `Color` on its own is a hex string in human readable formats, and reads back from
+// whatever a person might write. binary formats (bincode, postcard) can't be asked what's
+// next, so they get the `{ r, g, b, a }` struct, the same as the old derive wrote.
+// the modules below pick a different output for a field with `#[serde(with = "...")]`
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            as_hex::serialize(self, serializer)
+        } else {
+            as_struct::serialize(self, serializer)
+        }
+    }
+}
+
+impl<'de> ::serde::Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(ColorVisitor)
+        } else {
+            as_struct::deserialize(deserializer)
+        }
+    }
+}
+
+/// A color as a hex string, `"#1E1E2EFF"`, the same as `Color`'s `Display`.
+///
+/// Reading accepts any CSS color: hex, `rgb()`, `hsl()`, `oklch()`, `color()`, named
+/// colors... as well as the tuple and struct forms, so files written by hand or by an
+/// older version keep loading. Formats that aren't self describing only read the string.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::color::{model::Color, serde::as_hex};
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Theme {
+///     #[serde(with = "as_hex")]
+///     background: Color,
+/// }
+/// ```
+pub mod as_hex {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(color)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(ColorVisitor)
+        } else {
+            deserializer.deserialize_str(ColorVisitor)
+        }
+    }
+}
+
+/// A color as an `[r, g, b, a]` tuple of 0-255 values.
+///
+/// Reading also takes `[r, g, b]` (opaque) and the struct form. Unlike `as_hex` it asks
+/// the format for a tuple, so it works with formats that aren't self describing.
+pub mod as_tuple {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut t = serializer.serialize_tuple(4)?;
+        for c in color.into_rgba() {
+            t.serialize_element(&c)?;
+        }
+        t.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        deserializer.deserialize_tuple(4, ColorVisitor)
+    }
+}
+
+/// A color as an `{ r, g, b, a }` struct of 0-255 values.
+///
+/// Reading also takes the tuple form, and `a` can be left out for an opaque color.
+pub mod as_struct {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        let [r, g, b, a] = color.into_rgba();
+        let mut s = serializer.serialize_struct("Color", 4)?;
+        s.serialize_field("r", &r)?;
+        s.serialize_field("g", &g)?;
+        s.serialize_field("b", &b)?;
+        s.serialize_field("a", &a)?;
+        s.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        deserializer.deserialize_struct("Color", FIELDS, ColorVisitor)
+    }
+}
+
+const FIELDS: &[&str] = &["r", "g", "b", "a"];
+
+struct ColorVisitor;
+
+impl<'de> Visitor<'de> for ColorVisitor {
+    type Value = Color;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a CSS color string, an [r, g, b, a] tuple or an { r, g, b, a } struct")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Color, E> {
+        v.parse()
+            .map_err(|e| E::custom(format_args!("invalid color {v:?}: {e}")))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Color, A::Error> {
+        let mut rgba = [0, 0, 0, 255];
+        for (i, c) in rgba.iter_mut().enumerate() {
+            match seq.next_element()? {
+                Some(v) => *c = v,
+                // alpha is optional
+                None if i == 3 => break,
+                None => return Err(de::Error::invalid_length(i, &self)),
+            }
+        }
+        if seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(5, &self));
+        }
+        Ok(Color::from_rgba(rgba))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Color, A::Error> {
+        let mut rgba: [Option<u8>; 4] = [None; 4];
+        while let Some(Channel(i)) = map.next_key()? {
+            if rgba[i].is_some() {
+                return Err(de::Error::duplicate_field(FIELDS[i]));
+            }
+            rgba[i] = Some(map.next_value()?);
+        }
+        let [r, g, b, a] = rgba;
+        let need = |c: Option<u8>, name| c.ok_or_else(|| de::Error::missing_field(name));
+        Ok(Color::new(
+            need(r, "r")?,
+            need(g, "g")?,
+            need(b, "b")?,
+            a.unwrap_or(255),
+        ))
+    }
+}
+
+/// A struct key, as an index into `FIELDS`.
+struct Channel(usize);
+
+impl<'de> ::serde::Deserialize<'de> for Channel {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ChannelVisitor;
+
+        impl Visitor<'_> for ChannelVisitor {
+            type Value = Channel;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("`r`, `g`, `b` or `a`")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Channel, E> {
+                FIELDS
+                    .iter()
+                    .position(|&f| f == v)
+                    .map(Channel)
+                    .ok_or_else(|| E::unknown_field(v, FIELDS))
+            }
+        }
+
+        deserializer.deserialize_identifier(ChannelVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Theme {
+        background: Color,
+        #[serde(with = "as_hex")]
+        text: Color,
+        #[serde(with = "as_tuple")]
+        selection: Color,
+        #[serde(with = "as_struct")]
+        cursor: Color,
+    }
+
+    fn theme() -> Theme {
+        Theme {
+            background: Color::new(30, 30, 46, 255),
+            text: Color::new(205, 214, 244, 255),
+            selection: Color::new(88, 91, 112, 128),
+            cursor: Color::new(245, 224, 220, 255),
+        }
+    }
+
+    #[test]
+    fn round_trips_through_every_format() {
+        let json = serde_json::to_string(&theme()).unwrap();
+        assert_eq!(
+            json,
+            r##"{"background":"#1E1E2EFF","text":"#CDD6F4FF","selection":[88,91,112,128],"cursor":{"r":245,"g":224,"b":220,"a":255}}"##
+        );
+        assert_eq!(serde_json::from_str::<Theme>(&json).unwrap(), theme());
+
+        let toml = toml::to_string(&theme()).unwrap();
+        assert!(toml.contains("background = \"#1E1E2EFF\""), "{toml}");
+        assert_eq!(toml::from_str::<Theme>(&toml).unwrap(), theme());
+
+        let ron = ron::to_string(&theme()).unwrap();
+        assert!(ron.contains("selection:(88,91,112,128)"), "{ron}");
+        assert_eq!(ron::from_str::<Theme>(&ron).unwrap(), theme());
+    }
+
+    #[test]
+    fn binary_formats_keep_the_struct_form() {
+        // what `#[derive(Serialize)]` on `Color` used to write
+        #[derive(Serialize)]
+        struct Old {
+            r: u8,
+            g: u8,
+            b: u8,
+            a: u8,
+        }
+        let old = bincode::serialize(&Old {
+            r: 30,
+            g: 30,
+            b: 46,
+            a: 255,
+        })
+        .unwrap();
+        assert_eq!(bincode::serialize(&theme().background).unwrap(), old);
+        assert_eq!(
+            bincode::deserialize::<Color>(&old).unwrap(),
+            theme().background
+        );
+
+        let bytes = bincode::serialize(&theme()).unwrap();
+        assert_eq!(bincode::deserialize::<Theme>(&bytes).unwrap(), theme());
+    }
+
+    #[test]
+    fn reads_what_people_write() {
+        let theme: Theme = toml::from_str(
+            r#"
+            background = "oklch(0.24 0.03 284)"
+            text = "rebeccapurple"
+            selection = [88, 91, 112]
+            cursor = { r = 245, g = 224, b = 220 }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(theme.text, Color::new(102, 51, 153, 255));
+        assert_eq!(theme.selection, Color::new(88, 91, 112, 255));
+        assert_eq!(theme.cursor, Color::new(245, 224, 220, 255));
+
+        // the plain impl takes every form
+        let any = |s: &str| serde_json::from_str::<Color>(s).unwrap();
+        assert_eq!(any(r#""hsl(0 100% 50%)""#), Color::RED);
+        assert_eq!(any("[0, 0, 255, 255]"), Color::BLUE);
+        assert_eq!(
+            any(r#"{"r": 0, "g": 0, "b": 0, "a": 0}"#),
+            Color::TRANSPARENT
+        );
+    }
+
+    #[test]
+    fn errors_say_what_was_wrong() {
+        let err = |s: &str| serde_json::from_str::<Color>(s).unwrap_err().to_string();
+        assert!(
+            err(r##""#12345""##).starts_with(r##"invalid color "#12345": invalid hex length"##)
+        );
+        assert!(err("[1, 2]").contains("invalid length 2"));
+        assert!(err("[1, 2, 3, 4, 5]").contains("invalid length"));
+        assert!(err("[1, 2, 300]").contains("300"));
+        assert!(err(r#"{"r": 1, "g": 2}"#).contains("missing field `b`"));
+        assert!(err(r#"{"r": 1, "g": 2, "b": 3, "x": 4}"#).contains("unknown field `x`"));
+        assert!(err("true").contains("a CSS color string"));
+    }
+}
@@ -0,0 +1,371 @@
+// Batch versions of the per-pixel conversions, for whole rows and frames at a time.
+// `wide` picks the widest registers the build targets (SSE2 by default on x86_64,
+// AVX with `-C target-cpu=native`, NEON on aarch64, and plain arrays elsewhere),
+// so there's no unsafe here and every platform gets the same results.
+
+#![allow(dead_code)]
+
+use core::array;
+
+use wide::{CmpEq, CmpLe};
+
+use crate::color::{
+    ColorFloat,
+    model::{BlendMode, Color},
+};
+
+#[cfg(not(feature = "color_double_precision"))]
+type Lanes = wide::f32x8;
+#[cfg(feature = "color_double_precision")]
+type Lanes = wide::f64x4;
+
+/// How many values one register holds.
+const LANES: usize = size_of::<Lanes>() / size_of::<ColorFloat>();
+
+#[inline]
+fn splat(v: ColorFloat) -> Lanes {
+    Lanes::splat(v)
+}
+
+/// sRGB (0.0-1.0) to linear, the same curve as `Color::into_linear`.
+#[inline]
+fn decode(x: Lanes) -> Lanes {
+    let low = x / splat(12.92);
+    let high = ((x + splat(0.055)) / splat(1.055)).powf(2.4);
+    x.cmp_le(splat(0.04045)).blend(low, high)
+}
+
+/// Linear to sRGB (0.0-1.0), clamping first.
+#[inline]
+fn encode(lin: Lanes) -> Lanes {
+    let l = lin.max(splat(0.0)).min(splat(1.0));
+    let low = l * splat(12.92);
+    let high = splat(1.055) * l.powf(1.0 / 2.4) - splat(0.055);
+    l.cmp_le(splat(0.0031308)).blend(low, high)
+}
+
+/// 0.0-1.0 to 0-255, rounding half up like `Color::from_linear`.
+#[inline]
+fn to_u8(v: Lanes) -> [u8; LANES] {
+    let v = (v * splat(255.0) + splat(0.5)).floor();
+    v.to_array().map(|v| v as u8)
+}
+
+/// Decode 8 bit sRGB values into linear floats.
+///
+/// # Arguments
+///
+/// - `src` (`&[u8]`) - sRGB values, any channel layout (alpha isn't special cased here).
+/// - `dst` (`&mut [ColorFloat]`) - Where the linear values go.
+///
+/// # Panics
+///
+/// If `src` and `dst` aren't the same length.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::color::simd::decode_srgb_slice;
+///
+/// let row = [0u8, 64, 128, 255];
+/// let mut lin = [0.0; 4];
+/// decode_srgb_slice(&row, &mut lin);
+/// ```
+pub fn decode_srgb_slice(src: &[u8], dst: &mut [ColorFloat]) {
+    assert_eq!(src.len(), dst.len(), "decode_srgb_slice: length mismatch");
+    let mut s = src.chunks_exact(LANES);
+    let mut d = dst.chunks_exact_mut(LANES);
+    for (s, d) in (&mut s).zip(&mut d) {
+        let x = Lanes::from(array::from_fn::<ColorFloat, LANES, _>(|i| {
+            s[i] as ColorFloat / 255.0
+        }));
+        d.copy_from_slice(&decode(x).to_array());
+    }
+
+    // the tail goes through the same math, so results don't depend on the length
+    let (s, d) = (s.remainder(), d.into_remainder());
+    if !s.is_empty() {
+        let x = Lanes::from(array::from_fn::<ColorFloat, LANES, _>(|i| {
+            s.get(i).map_or(0.0, |&v| v as ColorFloat / 255.0)
+        }));
+        d.copy_from_slice(&decode(x).to_array()[..d.len()]);
+    }
+}
+
+/// Encode linear floats into 8 bit sRGB values, clamping to 0.0-1.0.
+///
+/// # Arguments
+///
+/// - `src` (`&[ColorFloat]`) - Linear values.
+/// - `dst` (`&mut [u8]`) - Where the sRGB values go.
+///
+/// # Panics
+///
+/// If `src` and `dst` aren't the same length.
+pub fn encode_srgb_slice(src: &[ColorFloat], dst: &mut [u8]) {
+    assert_eq!(src.len(), dst.len(), "encode_srgb_slice: length mismatch");
+    let mut s = src.chunks_exact(LANES);
+    let mut d = dst.chunks_exact_mut(LANES);
+    for (s, d) in (&mut s).zip(&mut d) {
+        let x = Lanes::from(array::from_fn::<ColorFloat, LANES, _>(|i| s[i]));
+        d.copy_from_slice(&to_u8(encode(x)));
+    }
+
+    let (s, d) = (s.remainder(), d.into_remainder());
+    if !s.is_empty() {
+        let x = Lanes::from(array::from_fn::<ColorFloat, LANES, _>(|i| {
+            s.get(i).copied().unwrap_or(0.0)
+        }));
+        d.copy_from_slice(&to_u8(encode(x))[..d.len()]);
+    }
+}
+
+/// Blend a row of RGBA8 pixels over another with a blend mode, like calling
+/// `Color::blend_over` on every pair (up to a step of rounding).
+///
+/// Separable modes run fully in SIMD. The non-separable ones (`Hue`, `Saturation`,
+/// `Color`, `Luminosity`) mix channels in ways that don't vectorize, so only their
+/// decoding, compositing and encoding is batched.
+///
+/// # Arguments
+///
+/// - `mode` (`BlendMode`) - The blend mode to use.
+/// - `src` (`&[u8]`) - The source pixels, RGBA8 with straight alpha.
+/// - `dst` (`&mut [u8]`) - The backdrop pixels, overwritten with the result.
+///
+/// # Panics
+///
+/// If `src` and `dst` aren't the same length, or it isn't a multiple of 4.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::color::{model::BlendMode, simd::blend_row};
+///
+/// // multiply a shadow layer onto a 64x64 frame, one row at a time
+/// let stride = 64 * 4;
+/// let shadow = vec![128; stride * 64];
+/// let mut frame = vec![255; stride * 64];
+/// for (src, dst) in shadow.chunks_exact(stride).zip(frame.chunks_exact_mut(stride)) {
+///     blend_row(BlendMode::Multiply, src, dst);
+/// }
+/// ```
+pub fn blend_row(mode: BlendMode, src: &[u8], dst: &mut [u8]) {
+    assert_eq!(src.len(), dst.len(), "blend_row: length mismatch");
+    assert_eq!(src.len() % 4, 0, "blend_row: not RGBA8");
+
+    for (s, d) in src.chunks(LANES * 4).zip(dst.chunks_mut(LANES * 4)) {
+        // transpose to one register per channel, padding with transparent pixels
+        let channel = |px: &[u8], c: usize| {
+            Lanes::from(array::from_fn::<ColorFloat, LANES, _>(|i| {
+                px.get(i * 4 + c).map_or(0.0, |&v| v as ColorFloat / 255.0)
+            }))
+        };
+        let sc = [0, 1, 2].map(|c| decode(channel(s, c)));
+        let dc = [0, 1, 2].map(|c| decode(channel(d, c)));
+        let (sa, da) = (channel(s, 3), channel(d, 3));
+
+        let out = if mode == BlendMode::Normal {
+            over(sc, sa, dc, da)
+        } else {
+            // like the scalar path, a backdrop with no alpha is a plain over
+            let blended = blend_over(mode, sc, sa, dc, da);
+            let plain = over(sc, sa, dc, da);
+            let empty = da.cmp_eq(splat(0.0));
+            array::from_fn(|c| empty.blend(plain[c], blended[c]))
+        };
+
+        let rgb = [0, 1, 2].map(|c| to_u8(encode(out[c])));
+        let a = to_u8(out[3]);
+        let sa = sa.to_array();
+        for (i, px) in d.chunks_exact_mut(4).enumerate() {
+            // a transparent source leaves the backdrop exactly as it was
+            if sa[i] > 0.0 {
+                px.copy_from_slice(&[rgb[0][i], rgb[1][i], rgb[2][i], a[i]]);
+            }
+        }
+    }
+}
+
+/// Porter-Duff over with straight alpha, `LinearColor::over`.
+#[inline]
+fn over(s: [Lanes; 3], sa: Lanes, d: [Lanes; 3], da: Lanes) -> [Lanes; 4] {
+    let one = splat(1.0);
+    let out_a = sa + da * (one - sa);
+    let empty = out_a.cmp_le(splat(0.0));
+    let inv = empty.blend(splat(0.0), one / out_a);
+    let mix = |s: Lanes, d: Lanes| (s * sa + d * da * (one - sa)) * inv;
+    [mix(s[0], d[0]), mix(s[1], d[1]), mix(s[2], d[2]), out_a]
+}
+
+/// A blend mode composited over a backdrop, `LinearColor::blend_over`.
+#[inline]
+fn blend_over(mode: BlendMode, s: [Lanes; 3], sa: Lanes, d: [Lanes; 3], da: Lanes) -> [Lanes; 4] {
+    // the scalar path hands the source in as the blend's backdrop, keep it identical
+    let blended = blend(mode, s, d);
+
+    let one = splat(1.0);
+    let a_out = sa + da - sa * da;
+    let empty = a_out.cmp_le(splat(0.0));
+    let inv = empty.blend(splat(0.0), one / a_out);
+    let mix = |s: Lanes, d: Lanes, b: Lanes| {
+        (d * da * (one - sa) + s * sa * (one - da) + sa * da * b) * inv
+    };
+    [
+        mix(s[0], d[0], blended[0]),
+        mix(s[1], d[1], blended[1]),
+        mix(s[2], d[2], blended[2]),
+        a_out,
+    ]
+}
+
+/// `Color::blend_channel` for a register of pixels at a time.
+#[inline]
+fn blend(mode: BlendMode, backdrop: [Lanes; 3], source: [Lanes; 3]) -> [Lanes; 3] {
+    let f: fn(Lanes, Lanes) -> Lanes = match mode {
+        BlendMode::Normal => return source,
+        BlendMode::Multiply => |b, s| b * s,
+        BlendMode::Screen => |b, s| b + s - b * s,
+        BlendMode::Overlay | BlendMode::HardLight => |b, s| {
+            let (one, two) = (splat(1.0), splat(2.0));
+            let low = two * b * s;
+            let high = one - two * (one - b) * (one - s);
+            s.cmp_le(splat(0.5)).blend(low, high)
+        },
+        BlendMode::Darken => |b, s| b.min(s),
+        BlendMode::Lighten => |b, s| b.max(s),
+        BlendMode::ColorDodge => |b, s| {
+            let one = splat(1.0);
+            let v = one.min(b / (one - s));
+            let v = s.cmp_eq(one).blend(one, v);
+            b.cmp_eq(splat(0.0)).blend(splat(0.0), v)
+        },
+        BlendMode::ColorBurn => |b, s| {
+            let (zero, one) = (splat(0.0), splat(1.0));
+            let v = one - one.min((one - b) / s);
+            let v = s.cmp_eq(zero).blend(zero, v);
+            b.cmp_eq(one).blend(one, v)
+        },
+        BlendMode::SoftLight => |b, s| {
+            let (one, two) = (splat(1.0), splat(2.0));
+            let low = b - (one - two * s) * b * (one - b);
+            let d = b.cmp_le(splat(0.25)).blend(
+                ((splat(16.0) * b - splat(12.0)) * b + splat(4.0)) * b,
+                b.sqrt(),
+            );
+            let high = b + (two * s - one) * (d - b);
+            s.cmp_le(splat(0.5)).blend(low, high)
+        },
+        BlendMode::Difference => |b, s| (b - s).abs(),
+        BlendMode::Exclusion => |b, s| b + s - splat(2.0) * b * s,
+        // these mix the channels of a pixel, so do them one pixel at a time
+        BlendMode::Hue | BlendMode::Saturation | BlendMode::Color | BlendMode::Luminosity => {
+            let (b, s) = (backdrop.map(|v| v.to_array()), source.map(|v| v.to_array()));
+            let mut out = [[0.0; LANES]; 3];
+            for i in 0..LANES {
+                let px = Color::blend_channel(
+                    mode,
+                    [b[0][i], b[1][i], b[2][i]],
+                    [s[0][i], s[1][i], s[2][i]],
+                );
+                for (o, v) in out.iter_mut().zip(px) {
+                    o[i] = v;
+                }
+            }
+            return out.map(Lanes::from);
+        }
+    };
+    [0, 1, 2].map(|c| f(backdrop[c], source[c]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::model;
+
+    fn next(state: &mut u32) -> u32 {
+        *state ^= *state << 13;
+        *state ^= *state >> 17;
+        *state ^= *state << 5;
+        *state
+    }
+
+    #[test]
+    fn conversions_match_the_scalar_path() {
+        // every 8 bit value, at a length that leaves a tail
+        let bytes: Vec<u8> = (0..=255).chain(0..3).collect();
+        let mut lin = vec![0.0; bytes.len()];
+        decode_srgb_slice(&bytes, &mut lin);
+        for (&b, &l) in bytes.iter().zip(&lin) {
+            let scalar = Color::new(b, 0, 0, 255).into_linear()[0];
+            assert!((l - scalar).abs() < 1e-5, "{b}: {l} vs {scalar}");
+        }
+
+        let mut back = vec![0; lin.len()];
+        encode_srgb_slice(&lin, &mut back);
+        assert_eq!(back, bytes);
+
+        // out of range values clamp
+        let mut out = [0; 3];
+        encode_srgb_slice(&[-1.0, 0.5, 7.0], &mut out);
+        assert_eq!(
+            out,
+            [
+                0,
+                Color::from_linear([0.5, 0.0, 0.0, 1.0]).into_rgb()[0],
+                255
+            ]
+        );
+    }
+
+    #[test]
+    fn blend_row_matches_blend_over() {
+        let mut state = 0x1234_5678;
+        let mut row = |n: usize| -> Vec<u8> {
+            (0..n * 4)
+                .map(|i| match (i % 4, next(&mut state) % 5) {
+                    // plenty of opaque and transparent pixels
+                    (3, 0) => 0,
+                    (3, 1) => 255,
+                    _ => next(&mut state) as u8,
+                })
+                .collect()
+        };
+        let (src, dst) = (row(37), row(37));
+
+        use BlendMode::*;
+        let modes = [
+            Normal,
+            Multiply,
+            Screen,
+            Overlay,
+            Darken,
+            Lighten,
+            ColorDodge,
+            ColorBurn,
+            HardLight,
+            SoftLight,
+            Difference,
+            Exclusion,
+            Hue,
+            Saturation,
+            BlendMode::Color,
+            Luminosity,
+        ];
+        for mode in modes {
+            let mut out = dst.clone();
+            blend_row(mode, &src, &mut out);
+            for ((s, d), o) in src.chunks(4).zip(dst.chunks(4)).zip(out.chunks(4)) {
+                let s = model::Color::from_rgba([s[0], s[1], s[2], s[3]]);
+                let d = model::Color::from_rgba([d[0], d[1], d[2], d[3]]);
+                let expect = s.blend_over(d, mode).into_rgba();
+                for (a, b) in o.iter().zip(expect) {
+                    assert!(
+                        a.abs_diff(b) <= 1,
+                        "{mode:?} {s:?} over {d:?}: {o:?} vs {expect:?}"
+                    );
+                }
+            }
+        }
+    }
+}
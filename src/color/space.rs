@@ -0,0 +1,401 @@
+#![allow(dead_code)]
+// the matrices are the CSS Color 4 reference values, which carry f64 precision
+#![allow(clippy::excessive_precision)]
+
+use crate::color::ColorFloat;
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::color::math::Float as _;
+
+/// A 3x3 matrix, row major.
+pub(crate) type Mat3 = [[ColorFloat; 3]; 3];
+
+/// The RGB and XYZ color spaces CSS Color 4 can name in `color()`.
+///
+/// # Variants
+///
+/// - `Srgb` - sRGB, gamma encoded. What `Color` stores.
+/// - `SrgbLinear` - sRGB primaries without the transfer function.
+/// - `DisplayP3` - Display P3 (DCI-P3 primaries, D65, sRGB transfer function).
+/// - `A98Rgb` - Adobe RGB (1998).
+/// - `ProphotoRgb` - ProPhoto RGB (D50).
+/// - `Rec2020` - ITU-R BT.2020.
+/// - `XyzD50` - CIE XYZ relative to D50.
+/// - `XyzD65` - CIE XYZ relative to D65.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    Srgb,
+    SrgbLinear,
+    DisplayP3,
+    A98Rgb,
+    ProphotoRgb,
+    Rec2020,
+    XyzD50,
+    XyzD65,
+}
+
+impl ColorSpace {
+    /// Look up a space by its CSS `color()` name (ASCII case-insensitive).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use codimate::color::space::ColorSpace;
+    ///
+    /// assert_eq!(ColorSpace::from_css_name("display-p3"), Some(ColorSpace::DisplayP3));
+    /// ```
+    #[must_use]
+    pub fn from_css_name(name: &str) -> Option<Self> {
+        use ColorSpace::*;
+        const NAMES: [(&str, ColorSpace); 9] = [
+            ("srgb", Srgb),
+            ("srgb-linear", SrgbLinear),
+            ("display-p3", DisplayP3),
+            ("a98-rgb", A98Rgb),
+            ("prophoto-rgb", ProphotoRgb),
+            ("rec2020", Rec2020),
+            ("xyz", XyzD65),
+            ("xyz-d50", XyzD50),
+            ("xyz-d65", XyzD65),
+        ];
+        NAMES
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|&(_, space)| space)
+    }
+
+    /// The space's CSS `color()` name.
+    #[must_use]
+    pub fn css_name(self) -> &'static str {
+        match self {
+            ColorSpace::Srgb => "srgb",
+            ColorSpace::SrgbLinear => "srgb-linear",
+            ColorSpace::DisplayP3 => "display-p3",
+            ColorSpace::A98Rgb => "a98-rgb",
+            ColorSpace::ProphotoRgb => "prophoto-rgb",
+            ColorSpace::Rec2020 => "rec2020",
+            ColorSpace::XyzD50 => "xyz-d50",
+            ColorSpace::XyzD65 => "xyz-d65",
+        }
+    }
+
+    /// Convert channels in this space to CIE XYZ (D65).
+    #[must_use]
+    pub fn to_xyz_d65(self, c: [ColorFloat; 3]) -> [ColorFloat; 3] {
+        use ColorSpace::*;
+        match self {
+            Srgb => mul(&LIN_SRGB_TO_XYZ, c.map(srgb_decode)),
+            SrgbLinear => mul(&LIN_SRGB_TO_XYZ, c),
+            DisplayP3 => mul(&LIN_P3_TO_XYZ, c.map(srgb_decode)),
+            A98Rgb => mul(&LIN_A98_TO_XYZ, c.map(a98_decode)),
+            ProphotoRgb => mul(
+                &D50_TO_D65,
+                mul(&LIN_PROPHOTO_TO_XYZ_D50, c.map(prophoto_decode)),
+            ),
+            Rec2020 => mul(&LIN_REC2020_TO_XYZ, c.map(rec2020_decode)),
+            XyzD50 => mul(&D50_TO_D65, c),
+            XyzD65 => c,
+        }
+    }
+
+    /// Convert CIE XYZ (D65) to channels in this space. Nothing is clamped.
+    #[must_use]
+    pub fn channels_from_xyz_d65(self, xyz: [ColorFloat; 3]) -> [ColorFloat; 3] {
+        use ColorSpace::*;
+        match self {
+            Srgb => mul(&XYZ_TO_LIN_SRGB, xyz).map(srgb_encode),
+            SrgbLinear => mul(&XYZ_TO_LIN_SRGB, xyz),
+            DisplayP3 => mul(&XYZ_TO_LIN_P3, xyz).map(srgb_encode),
+            A98Rgb => mul(&XYZ_TO_LIN_A98, xyz).map(a98_encode),
+            ProphotoRgb => {
+                mul(&XYZ_D50_TO_LIN_PROPHOTO, mul(&D65_TO_D50, xyz)).map(prophoto_encode)
+            }
+            Rec2020 => mul(&XYZ_TO_LIN_REC2020, xyz).map(rec2020_encode),
+            XyzD50 => mul(&D65_TO_D50, xyz),
+            XyzD65 => xyz,
+        }
+    }
+}
+
+#[inline]
+pub(crate) fn mul(m: &Mat3, v: [ColorFloat; 3]) -> [ColorFloat; 3] {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+// source for every matrix below: https://www.w3.org/TR/css-color-4/#color-conversion-code
+
+#[rustfmt::skip]
+pub(crate) const LIN_SRGB_TO_XYZ: Mat3 = [
+    [0.41239079926595934, 0.357584339383878, 0.1804807884018343],
+    [0.21263900587151027, 0.715168678767756, 0.07219231536073371],
+    [0.01933081871559182, 0.11919477979462598, 0.9505321522496607],
+];
+
+#[rustfmt::skip]
+pub(crate) const XYZ_TO_LIN_SRGB: Mat3 = [
+    [3.2409699419045226, -1.537383177570094, -0.4986107602930034],
+    [-0.9692436362808796, 1.8759675015077202, 0.04155505740717559],
+    [0.05563007969699366, -0.20397695888897652, 1.0569715142428786],
+];
+
+#[rustfmt::skip]
+pub(crate) const LIN_P3_TO_XYZ: Mat3 = [
+    [0.4865709486482162, 0.26566769316909306, 0.1982172852343625],
+    [0.2289745640697488, 0.6917385218365064, 0.079286914093745],
+    [0.0, 0.04511338185890264, 1.043944368900976],
+];
+
+#[rustfmt::skip]
+pub(crate) const XYZ_TO_LIN_P3: Mat3 = [
+    [2.493496911941425, -0.9313836179191239, -0.40271078445071684],
+    [-0.8294889695615747, 1.7626640603183463, 0.023624685841943577],
+    [0.03584583024378447, -0.07617238926804182, 0.9568845240076872],
+];
+
+#[rustfmt::skip]
+pub(crate) const LIN_A98_TO_XYZ: Mat3 = [
+    [0.5766690429101305, 0.1855582379065463, 0.1882286462349947],
+    [0.29734497525053605, 0.6273635662554661, 0.0752914584939978],
+    [0.02703136138641234, 0.07068885253582723, 0.9913375368376388],
+];
+
+#[rustfmt::skip]
+pub(crate) const XYZ_TO_LIN_A98: Mat3 = [
+    [2.0415879038107465, -0.5650069742788596, -0.34473135077832956],
+    [-0.9692436362808795, 1.8759675015077202, 0.04155505740717557],
+    [0.013444280632031142, -0.11836239223101838, 1.0151749943912054],
+];
+
+#[rustfmt::skip]
+pub(crate) const LIN_PROPHOTO_TO_XYZ_D50: Mat3 = [
+    [0.7977666449006423, 0.13518129740053308, 0.0313477341283922],
+    [0.2880748288194013, 0.711835234241873, 0.00008993693872564],
+    [0.0, 0.0, 0.8251046025104602],
+];
+
+#[rustfmt::skip]
+pub(crate) const XYZ_D50_TO_LIN_PROPHOTO: Mat3 = [
+    [1.3457868816471583, -0.25557208737979464, -0.05110186497554526],
+    [-0.5446307051249019, 1.5082477428451468, 0.02052744743642139],
+    [0.0, 0.0, 1.2119675456389452],
+];
+
+#[rustfmt::skip]
+pub(crate) const LIN_REC2020_TO_XYZ: Mat3 = [
+    [0.6369580483012914, 0.14461690358620832, 0.1688809751641721],
+    [0.2627002120112671, 0.6779980715188708, 0.05930171646986196],
+    [0.0, 0.028072693049087428, 1.060985057710791],
+];
+
+#[rustfmt::skip]
+pub(crate) const XYZ_TO_LIN_REC2020: Mat3 = [
+    [1.716651187971268, -0.355670783776392, -0.253366281373660],
+    [-0.666684351832489, 1.616481236634939, 0.0157685458139111],
+    [0.017639857445311, -0.042770613257809, 0.942103121235474],
+];
+
+/// Bradford chromatic adaptation from D50 to D65.
+#[rustfmt::skip]
+pub(crate) const D50_TO_D65: Mat3 = [
+    [0.955473421488075, -0.02309845494876471, 0.06325924320057072],
+    [-0.0283697093338637, 1.0099953980813041, 0.021041441191917323],
+    [0.012314014864481998, -0.020507649298898964, 1.330365926242124],
+];
+
+/// Bradford chromatic adaptation from D65 to D50.
+#[rustfmt::skip]
+pub(crate) const D65_TO_D50: Mat3 = [
+    [1.0479297925449969, 0.022946870601609652, -0.05019226628920524],
+    [0.02962780877005599, 0.9904344267538799, -0.017073799063418826],
+    [-0.009243040646204504, 0.015055191490298152, 0.7518742814281371],
+];
+
+/// The D50 white point in XYZ, which CIE Lab is relative to.
+pub(crate) const D50_WHITE: [ColorFloat; 3] =
+    [0.3457 / 0.3585, 1.0, (1.0 - 0.3457 - 0.3585) / 0.3585];
+
+// transfer functions. they mirror around zero so out of gamut values survive the round trip
+
+/// sRGB (and Display P3) gamma encoded to linear.
+#[inline]
+pub(crate) fn srgb_decode(v: ColorFloat) -> ColorFloat {
+    let a = v.abs();
+    if a <= 0.04045 {
+        v / 12.92
+    } else {
+        ((a + 0.055) / 1.055).powf(2.4).copysign(v)
+    }
+}
+
+/// Linear to sRGB (and Display P3) gamma encoded.
+#[inline]
+pub(crate) fn srgb_encode(v: ColorFloat) -> ColorFloat {
+    let a = v.abs();
+    if a <= 0.0031308 {
+        v * 12.92
+    } else {
+        (1.055 * a.powf(1.0 / 2.4) - 0.055).copysign(v)
+    }
+}
+
+#[inline]
+fn a98_decode(v: ColorFloat) -> ColorFloat {
+    v.abs().powf(563.0 / 256.0).copysign(v)
+}
+
+#[inline]
+fn a98_encode(v: ColorFloat) -> ColorFloat {
+    v.abs().powf(256.0 / 563.0).copysign(v)
+}
+
+#[inline]
+fn prophoto_decode(v: ColorFloat) -> ColorFloat {
+    let a = v.abs();
+    if a <= 16.0 / 512.0 {
+        v / 16.0
+    } else {
+        a.powf(1.8).copysign(v)
+    }
+}
+
+#[inline]
+fn prophoto_encode(v: ColorFloat) -> ColorFloat {
+    let a = v.abs();
+    if a >= 1.0 / 512.0 {
+        a.powf(1.0 / 1.8).copysign(v)
+    } else {
+        v * 16.0
+    }
+}
+
+const REC2020_ALPHA: ColorFloat = 1.09929682680944;
+const REC2020_BETA: ColorFloat = 0.018053968510807;
+
+#[inline]
+fn rec2020_decode(v: ColorFloat) -> ColorFloat {
+    let a = v.abs();
+    if a < REC2020_BETA * 4.5 {
+        v / 4.5
+    } else {
+        ((a + REC2020_ALPHA - 1.0) / REC2020_ALPHA)
+            .powf(1.0 / 0.45)
+            .copysign(v)
+    }
+}
+
+#[inline]
+fn rec2020_encode(v: ColorFloat) -> ColorFloat {
+    let a = v.abs();
+    if a > REC2020_BETA {
+        (REC2020_ALPHA * a.powf(0.45) - (REC2020_ALPHA - 1.0)).copysign(v)
+    } else {
+        v * 4.5
+    }
+}
+
+const LAB_KAPPA: ColorFloat = 24389.0 / 27.0;
+const LAB_EPSILON: ColorFloat = 216.0 / 24389.0;
+
+/// CIE Lab (D50) to CIE XYZ (D50).
+#[must_use]
+pub(crate) fn lab_to_xyz_d50(lab: [ColorFloat; 3]) -> [ColorFloat; 3] {
+    let [l, a, b] = lab;
+    let f1 = (l + 16.0) / 116.0;
+    let f0 = a / 500.0 + f1;
+    let f2 = f1 - b / 200.0;
+
+    let x = if f0.powi(3) > LAB_EPSILON {
+        f0.powi(3)
+    } else {
+        (116.0 * f0 - 16.0) / LAB_KAPPA
+    };
+    let y = if l > LAB_KAPPA * LAB_EPSILON {
+        f1.powi(3)
+    } else {
+        l / LAB_KAPPA
+    };
+    let z = if f2.powi(3) > LAB_EPSILON {
+        f2.powi(3)
+    } else {
+        (116.0 * f2 - 16.0) / LAB_KAPPA
+    };
+
+    [x * D50_WHITE[0], y * D50_WHITE[1], z * D50_WHITE[2]]
+}
+
+/// CIE XYZ (D50) to CIE Lab (D50).
+#[must_use]
+pub(crate) fn xyz_d50_to_lab(xyz: [ColorFloat; 3]) -> [ColorFloat; 3] {
+    let f = |v: ColorFloat| {
+        if v > LAB_EPSILON {
+            v.cbrt()
+        } else {
+            (LAB_KAPPA * v + 16.0) / 116.0
+        }
+    };
+    let [fx, fy, fz] = [
+        f(xyz[0] / D50_WHITE[0]),
+        f(xyz[1] / D50_WHITE[1]),
+        f(xyz[2] / D50_WHITE[2]),
+    ];
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// Polar (lightness, chroma, hue in degrees) to rectangular (lightness, a, b).
+/// Works for both CIE LCH and OKLCH.
+#[must_use]
+#[inline]
+pub(crate) fn lch_to_lab(lch: [ColorFloat; 3]) -> [ColorFloat; 3] {
+    let h = lch[2].to_radians();
+    [lch[0], lch[1] * h.cos(), lch[1] * h.sin()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: [ColorFloat; 3], b: [ColorFloat; 3], tol: ColorFloat) -> bool {
+        a.iter().zip(b).all(|(a, b)| (a - b).abs() < tol)
+    }
+
+    #[test]
+    fn every_space_round_trips_through_xyz() {
+        use ColorSpace::*;
+        let c = [0.25, 0.5, 0.75];
+        for space in [
+            Srgb,
+            SrgbLinear,
+            DisplayP3,
+            A98Rgb,
+            ProphotoRgb,
+            Rec2020,
+            XyzD50,
+            XyzD65,
+        ] {
+            let back = space.channels_from_xyz_d65(space.to_xyz_d65(c));
+            assert!(close(back, c, 1e-4), "{space:?}: {back:?}");
+            assert_eq!(ColorSpace::from_css_name(space.css_name()), Some(space));
+        }
+    }
+
+    #[test]
+    fn white_is_white_everywhere() {
+        let white = ColorSpace::Srgb.to_xyz_d65([1.0; 3]);
+        for space in [
+            ColorSpace::DisplayP3,
+            ColorSpace::Rec2020,
+            ColorSpace::A98Rgb,
+        ] {
+            assert!(
+                close(space.channels_from_xyz_d65(white), [1.0; 3], 1e-4),
+                "{space:?}"
+            );
+        }
+        let lab = xyz_d50_to_lab(ColorSpace::XyzD50.channels_from_xyz_d65(white));
+        assert!(close(lab, [100.0, 0.0, 0.0], 1e-2), "{lab:?}");
+    }
+}
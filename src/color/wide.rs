@@ -0,0 +1,349 @@
+#![allow(dead_code)]
+
+use core::fmt;
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::color::math::Float as _;
+use crate::color::{
+    ColorFloat,
+    model::Color,
+    space::{self, ColorSpace},
+};
+
+/// A float color tagged with the space its channels are in.
+///
+/// `Color` can only hold 8 bit sRGB. `WideColor` keeps Display P3, Rec.2020 and friends
+/// exact until the very end, where converting to `Color` gamut maps anything sRGB can't show.
+///
+/// # Fields
+///
+/// - `space` (`ColorSpace`) - What the channels mean.
+/// - `channels` (`[ColorFloat; 3]`) - The channels, 0.0-1.0 inside the gamut of RGB spaces.
+///   Values outside that range are kept as they are.
+/// - `alpha` (`ColorFloat`) - Straight alpha, 0.0-1.0.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::color::{Color, space::ColorSpace, wide::WideColor};
+///
+/// let p3_red = WideColor::new(ColorSpace::DisplayP3, [1.0, 0.0, 0.0], 1.0);
+/// assert!(!p3_red.convert(ColorSpace::Srgb).in_gamut());
+///
+/// // the most saturated sRGB red that keeps the P3 red's hue and lightness
+/// let red: Color = p3_red.into();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WideColor {
+    pub space: ColorSpace,
+    pub channels: [ColorFloat; 3],
+    pub alpha: ColorFloat,
+}
+
+impl WideColor {
+    /// Create a color from channels in a space.
+    ///
+    /// # Arguments
+    ///
+    /// - `space` (`ColorSpace`) - What the channels mean.
+    /// - `channels` (`[ColorFloat; 3]`) - The channels.
+    /// - `alpha` (`ColorFloat`) - Straight alpha, 0.0-1.0.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The new color.
+    #[must_use]
+    pub const fn new(space: ColorSpace, channels: [ColorFloat; 3], alpha: ColorFloat) -> Self {
+        Self {
+            space,
+            channels,
+            alpha,
+        }
+    }
+
+    /// Create a color in a space from OKLAB. Nothing is clamped or gamut mapped.
+    ///
+    /// # Arguments
+    ///
+    /// - `space` (`ColorSpace`) - The space to put the color in.
+    /// - `lab` (`[ColorFloat; 3]`) - The OKLAB array.
+    /// - `alpha` (`ColorFloat`) - Straight alpha, 0.0-1.0.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The new color.
+    #[must_use]
+    pub fn from_oklab(space: ColorSpace, lab: [ColorFloat; 3], alpha: ColorFloat) -> Self {
+        let xyz = space::mul(&space::LIN_SRGB_TO_XYZ, Color::oklab_to_linear(lab));
+        Self::new(space, space.channels_from_xyz_d65(xyz), alpha)
+    }
+
+    /// Create a color in a space from OKLCH (hue in degrees). Nothing is clamped or gamut mapped.
+    ///
+    /// # Arguments
+    ///
+    /// - `space` (`ColorSpace`) - The space to put the color in.
+    /// - `lch` (`[ColorFloat; 3]`) - The OKLCH array.
+    /// - `alpha` (`ColorFloat`) - Straight alpha, 0.0-1.0.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The new color.
+    #[must_use]
+    pub fn from_oklch(space: ColorSpace, lch: [ColorFloat; 3], alpha: ColorFloat) -> Self {
+        Self::from_oklab(space, space::lch_to_lab(lch), alpha)
+    }
+
+    /// Get the OKLAB representation of a color.
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`WideColor`) - The color to get the OKLAB representation of.
+    ///
+    /// # Returns
+    ///
+    /// - `[ColorFloat; 3]` - The OKLAB representation.
+    #[must_use]
+    pub fn to_oklab(self) -> [ColorFloat; 3] {
+        let xyz = self.space.to_xyz_d65(self.channels);
+        Color::linear_to_oklab(space::mul(&space::XYZ_TO_LIN_SRGB, xyz))
+    }
+
+    /// Get the OKLCH representation of a color, hue in degrees (0-360).
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`WideColor`) - The color to get the OKLCH representation of.
+    ///
+    /// # Returns
+    ///
+    /// - `[ColorFloat; 3]` - The OKLCH representation.
+    #[must_use]
+    pub fn to_oklch(self) -> [ColorFloat; 3] {
+        let [l, a, b] = self.to_oklab();
+        let mut h = b.atan2(a).to_degrees();
+        if h < 0.0 {
+            h += 360.0;
+        }
+        [l, (a * a + b * b).sqrt(), h]
+    }
+
+    /// Convert a color to another space through CIE XYZ. Nothing is clamped, so colors
+    /// outside the new space's gamut come out with channels below 0.0 or above 1.0.
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`WideColor`) - The color to convert.
+    /// - `to` (`ColorSpace`) - The space to convert to.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The same color in the new space.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use codimate::color::{space::ColorSpace, wide::WideColor};
+    ///
+    /// let srgb_red = WideColor::new(ColorSpace::Srgb, [1.0, 0.0, 0.0], 1.0);
+    /// let in_p3 = srgb_red.convert(ColorSpace::DisplayP3);
+    /// ```
+    #[must_use]
+    pub fn convert(self, to: ColorSpace) -> Self {
+        match (self.space, to) {
+            _ if self.space == to => return self,
+            // skip the matrices when only the transfer function changes, so sRGB stays exact
+            (ColorSpace::Srgb, ColorSpace::SrgbLinear) => {
+                return Self::new(to, self.channels.map(space::srgb_decode), self.alpha);
+            }
+            (ColorSpace::SrgbLinear, ColorSpace::Srgb) => {
+                return Self::new(to, self.channels.map(space::srgb_encode), self.alpha);
+            }
+            _ => {}
+        }
+        let xyz = self.space.to_xyz_d65(self.channels);
+        Self::new(to, to.channels_from_xyz_d65(xyz), self.alpha)
+    }
+
+    /// Whether a color fits inside its space's gamut. XYZ has no gamut, so it always does.
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`WideColor`) - The color to check.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - `true` if every channel is within 0.0-1.0.
+    #[must_use]
+    pub fn in_gamut(self) -> bool {
+        // round trips through the matrices leave a little float noise on the gamut edge
+        const EPS: ColorFloat = 1e-5;
+        match self.space {
+            ColorSpace::XyzD50 | ColorSpace::XyzD65 => true,
+            _ => self.channels.iter().all(|c| (-EPS..=1.0 + EPS).contains(c)),
+        }
+    }
+
+    /// Convert a color into another space, gamut mapping it if it doesn't fit.
+    ///
+    /// This is the CSS Color 4 algorithm: chroma is reduced in OKLCH at a fixed
+    /// lightness and hue, until clipping what is left changes the color by less than
+    /// a just noticeable difference (0.02 deltaEOK).
+    ///
+    /// # Arguments
+    ///
+    /// - `self` (`WideColor`) - The color to map.
+    /// - `to` (`ColorSpace`) - The space to map into.
+    ///
+    /// # Returns
+    ///
+    /// - `Self` - The color in `to`, with every channel within 0.0-1.0 for RGB spaces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use codimate::color::{space::ColorSpace, wide::WideColor};
+    ///
+    /// let rec2020_green = WideColor::new(ColorSpace::Rec2020, [0.0, 1.0, 0.0], 1.0);
+    /// let mapped = rec2020_green.gamut_map(ColorSpace::DisplayP3);
+    /// assert!(mapped.in_gamut());
+    /// ```
+    #[must_use]
+    pub fn gamut_map(self, to: ColorSpace) -> Self {
+        // source: https://www.w3.org/TR/css-color-4/#binsearch
+        const JND: ColorFloat = 0.02;
+        const EPS: ColorFloat = 0.0001;
+
+        let target = self.convert(to);
+        if target.in_gamut() {
+            return target;
+        }
+
+        let [l, c, h] = self.to_oklch();
+        if l >= 1.0 {
+            return Self::new(to, [1.0; 3], self.alpha);
+        }
+        if l <= 0.0 {
+            return Self::new(to, [0.0; 3], self.alpha);
+        }
+
+        let clip = |c: Self| Self::new(c.space, c.channels.map(|v| v.clamp(0.0, 1.0)), c.alpha);
+        let mut current = target;
+        let mut clipped = clip(current);
+        if delta_eok(clipped, current) < JND {
+            return clipped;
+        }
+
+        let (mut min, mut max, mut min_in_gamut) = (0.0, c, true);
+        while max - min > EPS {
+            let chroma = 0.5 * (min + max);
+            current = Self::from_oklch(to, [l, chroma, h], self.alpha);
+            if min_in_gamut && current.in_gamut() {
+                min = chroma;
+                continue;
+            }
+
+            clipped = clip(current);
+            let e = delta_eok(clipped, current);
+            if e < JND {
+                if JND - e < EPS {
+                    break;
+                }
+                min_in_gamut = false;
+                min = chroma;
+            } else {
+                max = chroma;
+            }
+        }
+        clip(current)
+    }
+}
+
+/// Euclidean distance in OKLAB.
+#[inline]
+fn delta_eok(a: WideColor, b: WideColor) -> ColorFloat {
+    let (a, b) = (a.to_oklab(), b.to_oklab());
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+impl From<Color> for WideColor {
+    fn from(color: Color) -> Self {
+        let [r, g, b, a] = color.into_rgba();
+        let unit = |v: u8| v as ColorFloat / 255.0;
+        Self::new(ColorSpace::Srgb, [unit(r), unit(g), unit(b)], unit(a))
+    }
+}
+
+impl From<WideColor> for Color {
+    /// Gamut maps into sRGB, then quantizes to 8 bits.
+    fn from(color: WideColor) -> Self {
+        let [r, g, b] = color.gamut_map(ColorSpace::SrgbLinear).channels;
+        Color::from_linear([r, g, b, color.alpha])
+    }
+}
+
+impl fmt::Display for WideColor {
+    /// Formats as a CSS `color()` function, which `parse_color` reads back.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [r, g, b] = self.channels;
+        write!(f, "color({} {r} {g} {b}", self.space.css_name())?;
+        if self.alpha < 1.0 {
+            write!(f, " / {}", self.alpha)?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::parse::parse_color;
+
+    #[test]
+    fn colors_round_trip_through_wide_spaces() {
+        for rgba in [[255, 0, 0, 255], [12, 200, 99, 128], [255, 255, 255, 0]] {
+            let c = Color::from_rgba(rgba);
+            for space in [
+                ColorSpace::DisplayP3,
+                ColorSpace::Rec2020,
+                ColorSpace::XyzD50,
+            ] {
+                let wide = WideColor::from(c).convert(space);
+                assert!(wide.in_gamut(), "{space:?}");
+                assert_eq!(Color::from(wide).into_rgba(), rgba, "{space:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn out_of_gamut_keeps_lightness_and_hue() {
+        let p3_green = WideColor::new(ColorSpace::DisplayP3, [0.0, 1.0, 0.0], 1.0);
+        assert!(!p3_green.convert(ColorSpace::Srgb).in_gamut());
+
+        let mapped = p3_green.gamut_map(ColorSpace::Srgb);
+        assert!(mapped.in_gamut());
+        let ([l0, c0, h0], [l1, c1, h1]) = (p3_green.to_oklch(), mapped.to_oklch());
+        assert!((l0 - l1).abs() < 0.02, "{l0} vs {l1}");
+        assert!((h0 - h1).abs() < 3.0, "{h0} vs {h1}");
+        assert!(c1 < c0);
+
+        // plain clipping would land on pure #00ff00, a noticeably different hue
+        assert_ne!(Color::from(mapped).into_rgba(), [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn extremes_map_to_white_and_black() {
+        let bright = WideColor::from_oklch(ColorSpace::Rec2020, [1.2, 0.3, 40.0], 1.0);
+        assert_eq!(Color::from(bright).into_rgba(), [255, 255, 255, 255]);
+        let dark = WideColor::from_oklch(ColorSpace::Rec2020, [-0.1, 0.3, 40.0], 0.5);
+        assert_eq!(Color::from(dark).into_rgba(), [0, 0, 0, 128]);
+    }
+
+    #[test]
+    fn display_is_css() {
+        let c = WideColor::new(ColorSpace::DisplayP3, [1.0, 0.5, 0.0], 0.5);
+        assert_eq!(c.to_string(), "color(display-p3 1 0.5 0 / 0.5)");
+        assert_eq!(parse_color(&c.to_string()), Ok(Color::from(c)));
+    }
+}
@@ -1,5 +1,11 @@
-use pyo3::prelude::*;
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
-mod color;
-mod renderer;
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod color;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "std")]
+pub mod renderer;
 mod traits;
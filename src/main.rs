@@ -0,0 +1,359 @@
+// the `codimate` command line renderer. everything here is argument handling,
+// the rendering itself is `renderer::export` on a scene from `renderer::scene_file`.
+//
+// exit codes: 0 ok, 1 the scene file is missing or invalid, 2 bad arguments,
+// 3 rendering or writing the output failed. with --watch the scene has to load once to
+// start, after that errors are printed and it keeps watching
+
+use std::{
+    io::{IsTerminal, Write},
+    ops::{ControlFlow, Range},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
+use codimate::renderer::{
+    export::{self, ExportError, ExportOptions},
+    scene::Scene,
+    scene_file::{self, SceneFileError},
+    watch::{self, Target, WatchEvent, WatchOptions},
+};
+
+const EXIT_SCENE: u8 = 1;
+const EXIT_RENDER: u8 = 3;
+
+enum Failure {
+    Scene(SceneFileError),
+    Render(ExportError),
+}
+
+impl From<SceneFileError> for Failure {
+    fn from(value: SceneFileError) -> Self {
+        Failure::Scene(value)
+    }
+}
+
+impl From<ExportError> for Failure {
+    fn from(value: ExportError) -> Self {
+        Failure::Render(value)
+    }
+}
+
+fn cli() -> Command {
+    let scene = Arg::new("scene")
+        .help("Scene file (.toml, .json or .ron)")
+        .required(true)
+        .value_parser(value_parser!(PathBuf));
+    let overrides = [
+        Arg::new("width")
+            .long("width")
+            .help("Output width in px (the scene is scaled to fit)")
+            .value_parser(value_parser!(u16).range(1..)),
+        Arg::new("height")
+            .long("height")
+            .help("Output height in px (the scene is scaled to fit)")
+            .value_parser(value_parser!(u16).range(1..)),
+        Arg::new("quality")
+            .long("quality")
+            .short('q')
+            .help("0-100, video CRF / GIF colors / PNG compression")
+            .default_value("80")
+            .value_parser(value_parser!(u8).range(0..=100)),
+        Arg::new("watch")
+            .long("watch")
+            .short('w')
+            .help("Keep running and re-render when the scene, its code files or fonts change")
+            .action(ArgAction::SetTrue),
+    ];
+
+    Command::new("codimate")
+        .about("Render codimate scene files")
+        .version(env!("CARGO_PKG_VERSION"))
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("render")
+                .about("Render a scene to a video, GIF or PNG sequence")
+                .arg(scene.clone())
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .help("Output file: .mp4/.mov/.mkv/.webm (needs ffmpeg), .gif, .png or a directory [default: <scene>.mp4]")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .args(overrides.clone())
+                .arg(
+                    Arg::new("fps")
+                        .long("fps")
+                        .help("Frames per second")
+                        .value_parser(parse_fps),
+                )
+                .arg(
+                    Arg::new("range")
+                        .long("range")
+                        .help("Only render part of the scene, in seconds: 1.5..3, 2.. or ..4")
+                        .value_parser(parse_range),
+                ),
+        )
+        .subcommand(
+            Command::new("frame")
+                .about("Render a single frame to a PNG, for previews")
+                .arg(scene.clone())
+                .arg(
+                    Arg::new("time")
+                        .long("time")
+                        .short('t')
+                        .help("When, in seconds")
+                        .default_value("0")
+                        .value_parser(parse_time),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .help("Output PNG [default: <scene>.png]")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .args(overrides),
+        )
+        .subcommand(
+            Command::new("info")
+                .about("Print what's in a scene")
+                .arg(scene),
+        )
+}
+
+fn parse_time(s: &str) -> Result<f32, String> {
+    match s.trim().parse::<f32>() {
+        Ok(t) if t.is_finite() && t >= 0.0 => Ok(t),
+        _ => Err(format!("expected a time in seconds, got `{s}`")),
+    }
+}
+
+fn parse_fps(s: &str) -> Result<f32, String> {
+    match s.trim().parse::<f32>() {
+        Ok(f) if f.is_finite() && f > 0.0 => Ok(f),
+        _ => Err(format!("expected a positive frame rate, got `{s}`")),
+    }
+}
+
+/// `a..b` in seconds, either end optional.
+fn parse_range(s: &str) -> Result<(Option<f32>, Option<f32>), String> {
+    let (a, b) = s
+        .split_once("..")
+        .ok_or_else(|| format!("expected START..END in seconds, got `{s}`"))?;
+    let end = |t: &str| (!t.trim().is_empty()).then(|| parse_time(t)).transpose();
+    let (a, b) = (end(a)?, end(b)?);
+    if let (Some(a), Some(b)) = (a, b)
+        && b <= a
+    {
+        return Err(format!("the range `{s}` is empty"));
+    }
+    Ok((a, b))
+}
+
+/// The output size, keeping the scene's aspect ratio when only one side is given.
+fn output_size(args: &ArgMatches, scene: &Scene) -> Option<[u16; 2]> {
+    let (w, h) = (args.get_one::<u16>("width"), args.get_one::<u16>("height"));
+    let aspect = scene.width as f32 / scene.height as f32;
+    let side = |v: f32| v.round().clamp(1.0, u16::MAX as f32) as u16;
+    match (w, h) {
+        (None, None) => None,
+        (Some(&w), Some(&h)) => Some([w, h]),
+        (Some(&w), None) => Some([w, side(w as f32 / aspect)]),
+        (None, Some(&h)) => Some([side(h as f32 * aspect), h]),
+    }
+}
+
+fn frame_range(scene: &Scene, (a, b): (Option<f32>, Option<f32>)) -> Range<u32> {
+    let frame = |t: f32| (t * scene.fps).ceil() as u32;
+    a.map_or(0, frame)..b.map_or(scene.frame_count(), frame)
+}
+
+fn default_output(scene: &Path, ext: &str) -> PathBuf {
+    let stem = scene.file_stem().unwrap_or("out".as_ref());
+    Path::new(stem).with_extension(ext)
+}
+
+fn render(args: &ArgMatches) -> Result<(), Failure> {
+    let path = args.get_one::<PathBuf>("scene").expect("required");
+    let mut scene = scene_file::load(path)?;
+    if let Some(&fps) = args.get_one::<f32>("fps") {
+        scene.fps = fps;
+    }
+    let output = args
+        .get_one::<PathBuf>("output")
+        .cloned()
+        .unwrap_or_else(|| default_output(path, "mp4"));
+    let options = ExportOptions {
+        size: output_size(args, &scene),
+        frames: args.get_one("range").map(|&r| frame_range(&scene, r)),
+        quality: *args.get_one::<u8>("quality").expect("has a default"),
+    };
+    if args.get_flag("watch") {
+        let target = Target::Export { output, options };
+        run_watch(path, &target, args.get_one::<f32>("fps").copied());
+        return Ok(());
+    }
+
+    let interactive = std::io::stderr().is_terminal();
+    let written = export::export(&scene, &output, &options, |done, total| {
+        if interactive {
+            eprint!("\rrendering frame {done}/{total}");
+            let _ = std::io::stderr().flush();
+        }
+    });
+    if interactive {
+        eprintln!();
+    }
+
+    let [w, h] = options.size.unwrap_or([scene.width, scene.height]);
+    println!(
+        "wrote {} ({} frames, {w}x{h} @ {} fps)",
+        output.display(),
+        written?,
+        scene.fps
+    );
+    Ok(())
+}
+
+fn frame(args: &ArgMatches) -> Result<(), Failure> {
+    let path = args.get_one::<PathBuf>("scene").expect("required");
+    let scene = scene_file::load(path)?;
+    let time = *args.get_one::<f32>("time").expect("has a default");
+    let output = args
+        .get_one::<PathBuf>("output")
+        .cloned()
+        .unwrap_or_else(|| default_output(path, "png"));
+    let size = output_size(args, &scene);
+    let quality = *args.get_one::<u8>("quality").expect("has a default");
+    if args.get_flag("watch") {
+        let target = Target::Frame {
+            output,
+            time,
+            size,
+            quality,
+        };
+        run_watch(path, &target, None);
+        return Ok(());
+    }
+
+    let fb = export::render_frame(&scene, time, size);
+    export::save_png(&fb, &output, quality)?;
+    println!(
+        "wrote {} ({}x{} at {time}s)",
+        output.display(),
+        fb.width(),
+        fb.height()
+    );
+    Ok(())
+}
+
+/// Watch until killed. Errors are printed and watching goes on, so a broken save doesn't
+/// end the session.
+fn run_watch(path: &Path, target: &Target, fps: Option<f32>) {
+    let output = match target {
+        Target::Export { output, .. } | Target::Frame { output, .. } => output.display(),
+    };
+    eprintln!(
+        "watching {} and the files it uses (ctrl-c to stop)",
+        path.display()
+    );
+    let options = WatchOptions {
+        fps,
+        ..Default::default()
+    };
+    watch::watch(path, target, &options, |event| {
+        match event {
+            WatchEvent::Rendered(frames) if frames.len() == 1 => {
+                println!("wrote {output} (frame {})", frames.start);
+            }
+            WatchEvent::Rendered(frames) => {
+                println!("wrote {output} (frames {}..{})", frames.start, frames.end);
+            }
+            WatchEvent::Unchanged => println!("no visible changes"),
+            WatchEvent::SceneError(e) => eprintln!("error: {e}"),
+            WatchEvent::RenderError(e) => eprintln!("error: {e}"),
+        }
+        ControlFlow::Continue(())
+    });
+}
+
+fn info(args: &ArgMatches) -> Result<(), Failure> {
+    let path = args.get_one::<PathBuf>("scene").expect("required");
+    let scene = scene_file::load(path)?;
+
+    println!("{}", path.display());
+    println!(
+        "  canvas      {}x{} @ {} fps",
+        scene.width, scene.height, scene.fps
+    );
+    println!(
+        "  duration    {}s ({} frames)",
+        scene.duration,
+        scene.frame_count()
+    );
+    println!("  background  {}", scene.background);
+    println!("  objects     {}", scene.nodes().count());
+    for (_, node) in scene.nodes() {
+        let kind = node.shape.kind();
+        let [x, y] = node.position;
+        println!(
+            "    {:<16}{kind:<9}at ({x}, {y})",
+            node.name.as_deref().unwrap_or("-")
+        );
+    }
+    let tweens = scene.tweens();
+    let end = tweens
+        .iter()
+        .map(|t| t.start + t.duration)
+        .fold(0.0, f32::max);
+    println!("  timeline    {} tweens, last ends at {end}s", tweens.len());
+    if end > scene.duration {
+        println!("  warning: the timeline runs past the end of the scene");
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let matches = cli().get_matches();
+    let result = match matches.subcommand() {
+        Some(("render", args)) => render(args),
+        Some(("frame", args)) => frame(args),
+        Some(("info", args)) => info(args),
+        _ => unreachable!("a subcommand is required"),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Scene(e)) => {
+            eprintln!("error: {e}");
+            ExitCode::from(EXIT_SCENE)
+        }
+        Err(Failure::Render(e)) => {
+            eprintln!("error: {e}");
+            ExitCode::from(EXIT_RENDER)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_is_well_formed() {
+        cli().debug_assert();
+    }
+
+    #[test]
+    fn ranges_parse() {
+        assert_eq!(parse_range("1.5..3"), Ok((Some(1.5), Some(3.0))));
+        assert_eq!(parse_range("2.."), Ok((Some(2.0), None)));
+        assert_eq!(parse_range("..4"), Ok((None, Some(4.0))));
+        assert!(parse_range("3..1").is_err());
+        assert!(parse_range("3").is_err());
+    }
+}
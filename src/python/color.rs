@@ -0,0 +1,166 @@
+// python's `Color`. immutable like the rust one, so it's frozen and hashable and can be a
+// dict key. everything that takes a color also takes a CSS string (see `ColorLike`)
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::color::{
+    ColorFloat,
+    model::{BlendMode, Color},
+    parse::parse_color,
+};
+
+/// How `Color.blend_over` mixes a color into its backdrop, as in CSS `mix-blend-mode`.
+#[pyclass(
+    name = "BlendMode",
+    module = "codimate",
+    eq,
+    eq_int,
+    frozen,
+    hash,
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PyBlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+impl From<PyBlendMode> for BlendMode {
+    fn from(value: PyBlendMode) -> Self {
+        match value {
+            PyBlendMode::Normal => BlendMode::Normal,
+            PyBlendMode::Multiply => BlendMode::Multiply,
+            PyBlendMode::Screen => BlendMode::Screen,
+            PyBlendMode::Overlay => BlendMode::Overlay,
+            PyBlendMode::Darken => BlendMode::Darken,
+            PyBlendMode::Lighten => BlendMode::Lighten,
+            PyBlendMode::ColorDodge => BlendMode::ColorDodge,
+            PyBlendMode::ColorBurn => BlendMode::ColorBurn,
+            PyBlendMode::HardLight => BlendMode::HardLight,
+            PyBlendMode::SoftLight => BlendMode::SoftLight,
+            PyBlendMode::Difference => BlendMode::Difference,
+            PyBlendMode::Exclusion => BlendMode::Exclusion,
+            PyBlendMode::Hue => BlendMode::Hue,
+            PyBlendMode::Saturation => BlendMode::Saturation,
+            PyBlendMode::Color => BlendMode::Color,
+            PyBlendMode::Luminosity => BlendMode::Luminosity,
+        }
+    }
+}
+
+/// An sRGB color with 8 bits per channel.
+#[pyclass(name = "Color", module = "codimate", frozen, eq, hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PyColor(pub Color);
+
+#[pymethods]
+impl PyColor {
+    #[classattr]
+    const TRANSPARENT: Self = Self(Color::TRANSPARENT);
+    #[classattr]
+    const BLACK: Self = Self(Color::BLACK);
+    #[classattr]
+    const WHITE: Self = Self(Color::WHITE);
+    #[classattr]
+    const RED: Self = Self(Color::RED);
+    #[classattr]
+    const GREEN: Self = Self(Color::GREEN);
+    #[classattr]
+    const BLUE: Self = Self(Color::BLUE);
+
+    #[new]
+    #[pyo3(signature = (r, g, b, a = 255))]
+    fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self(Color::new(r, g, b, a))
+    }
+
+    /// Parse any CSS color: hex, `rgb()`, `hsl()`, `oklch()`, names...
+    #[staticmethod]
+    fn parse(css: &str) -> PyResult<Self> {
+        parse(css).map(Self)
+    }
+
+    #[getter]
+    fn r(&self) -> u8 {
+        self.0.into_rgba()[0]
+    }
+
+    #[getter]
+    fn g(&self) -> u8 {
+        self.0.into_rgba()[1]
+    }
+
+    #[getter]
+    fn b(&self) -> u8 {
+        self.0.into_rgba()[2]
+    }
+
+    #[getter]
+    fn a(&self) -> u8 {
+        self.0.into_rgba()[3]
+    }
+
+    fn with_alpha(&self, a: u8) -> Self {
+        Self(self.0.with_alpha(a))
+    }
+
+    /// Blend towards `other` in OKLCH, `t` from 0.0 (this color) to 1.0 (`other`).
+    fn mix(&self, other: ColorLike, t: f64) -> PyResult<Self> {
+        Ok(Self(self.0.lerp_oklch(other.resolve()?, t as ColorFloat)))
+    }
+
+    /// Composite this color over `backdrop` (source-over, in linear light).
+    fn over(&self, backdrop: ColorLike) -> PyResult<Self> {
+        Ok(Self(self.0.over(backdrop.resolve()?)))
+    }
+
+    /// Blend this color over `backdrop` with a blend mode (in linear light).
+    #[pyo3(signature = (backdrop, mode = PyBlendMode::Normal))]
+    fn blend_over(&self, backdrop: ColorLike, mode: PyBlendMode) -> PyResult<Self> {
+        Ok(Self(self.0.blend_over(backdrop.resolve()?, mode.into())))
+    }
+
+    /// `#RRGGBBAA`
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        let [r, g, b, a] = self.0.into_rgba();
+        format!("Color({r}, {g}, {b}, {a})")
+    }
+}
+
+fn parse(css: &str) -> PyResult<Color> {
+    parse_color(css).map_err(|e| PyValueError::new_err(format!("invalid color {css:?}: {e}")))
+}
+
+/// A `Color` or a CSS color string, for arguments.
+#[derive(FromPyObject)]
+pub enum ColorLike {
+    Color(PyColor),
+    Css(String),
+}
+
+impl ColorLike {
+    pub fn resolve(self) -> PyResult<Color> {
+        match self {
+            ColorLike::Color(PyColor(color)) => Ok(color),
+            ColorLike::Css(css) => parse(&css),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+// the `codimate._codimate` extension module. the public `codimate` package
+// (python/codimate) re-exports it and adds the pure python bits on top.
+
+use pyo3::prelude::*;
+
+pub mod color;
+pub mod render;
+pub mod scene;
+
+#[pymodule]
+fn _codimate(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    use scene::*;
+
+    m.add_class::<color::PyColor>()?;
+    m.add_class::<color::PyBlendMode>()?;
+    m.add_class::<render::PyFrame>()?;
+    m.add_class::<render::PyRenderer>()?;
+
+    m.add_class::<Object>()?;
+    m.add_class::<Text>()?;
+    m.add_class::<Code>()?;
+    m.add_class::<Rect>()?;
+    m.add_class::<Circle>()?;
+    m.add_class::<Line>()?;
+    m.add_class::<Polygon>()?;
+
+    m.add_class::<Animation>()?;
+    m.add_class::<FadeIn>()?;
+    m.add_class::<FadeOut>()?;
+    m.add_class::<MoveTo>()?;
+    m.add_class::<Shift>()?;
+    m.add_class::<Rotate>()?;
+    m.add_class::<ScaleInPlace>()?;
+    m.add_class::<FadeToColor>()?;
+
+    m.add_class::<Timeline>()?;
+    m.add("RenderError", m.py().get_type::<RenderError>())?;
+    Ok(())
+}
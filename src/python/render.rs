@@ -0,0 +1,143 @@
+// frames and the immediate mode half of the renderer, for drawing by hand and for pulling
+// single frames out of a timeline (previews, notebooks, tests)
+
+use std::path::PathBuf;
+
+use pyo3::{prelude::*, types::PyBytes};
+
+use crate::{
+    python::{
+        color::{ColorLike, PyColor},
+        scene::{RenderError, Timeline},
+    },
+    renderer::{export, frame::Frame, pool::FramePool, render::Renderer},
+};
+
+/// An RGBA8 image.
+#[pyclass(name = "Frame", module = "codimate")]
+pub struct PyFrame(pub Frame);
+
+#[pymethods]
+impl PyFrame {
+    /// A transparent `width` x `height` frame.
+    #[new]
+    fn new(width: u16, height: u16) -> Self {
+        Self(Frame::new(width, height))
+    }
+
+    #[getter]
+    fn width(&self) -> u16 {
+        self.0.width()
+    }
+
+    #[getter]
+    fn height(&self) -> u16 {
+        self.0.height()
+    }
+
+    /// The color at (`x`, `y`), `None` outside the frame.
+    fn get_pixel(&self, x: u16, y: u16) -> Option<PyColor> {
+        self.0.get_pixel(x, y).map(PyColor)
+    }
+
+    /// The pixels, row by row, 4 bytes (RGBA) each.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, self.0.as_slice())
+    }
+
+    /// Save as a PNG. `quality` (0-100) only trades file size for encoding time.
+    #[pyo3(signature = (path, *, quality = 80))]
+    fn save(&self, path: PathBuf, quality: u8) -> PyResult<()> {
+        export::save_png(&self.0, &path, quality).map_err(|e| RenderError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<Frame {}x{}>", self.0.width(), self.0.height())
+    }
+}
+
+/// Draws into frames of one size.
+#[pyclass(name = "Renderer", module = "codimate")]
+pub struct PyRenderer(Renderer);
+
+#[pymethods]
+impl PyRenderer {
+    #[new]
+    fn new(width: u16, height: u16) -> Self {
+        Self(Renderer::new(FramePool::new(width, height, 1)))
+    }
+
+    #[getter]
+    fn width(&self) -> u16 {
+        self.0.pool().width()
+    }
+
+    #[getter]
+    fn height(&self) -> u16 {
+        self.0.pool().height()
+    }
+
+    /// A transparent frame this renderer's size.
+    fn new_frame(&self) -> PyFrame {
+        PyFrame::new(self.width(), self.height())
+    }
+
+    /// Fill the whole frame.
+    fn clear(&mut self, mut frame: PyRefMut<'_, PyFrame>, color: ColorLike) -> PyResult<()> {
+        self.0.clear(&mut frame.0, color.resolve()?);
+        Ok(())
+    }
+
+    fn set_pixel(
+        &mut self,
+        mut frame: PyRefMut<'_, PyFrame>,
+        x: u16,
+        y: u16,
+        color: ColorLike,
+    ) -> PyResult<()> {
+        self.0.set_pixel(&mut frame.0, x, y, color.resolve()?);
+        Ok(())
+    }
+
+    /// Fill a rectangle (no blending), clipped to the frame.
+    #[allow(clippy::too_many_arguments)]
+    fn rect(
+        &mut self,
+        mut frame: PyRefMut<'_, PyFrame>,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+        color: ColorLike,
+    ) -> PyResult<()> {
+        self.0
+            .rect(&mut frame.0, x, y, width, height, color.resolve()?);
+        Ok(())
+    }
+
+    /// Draw `source` over `frame` with its top left corner at (`x`, `y`).
+    fn blit_over(
+        &mut self,
+        mut frame: PyRefMut<'_, PyFrame>,
+        source: PyRef<'_, PyFrame>,
+        x: i32,
+        y: i32,
+    ) {
+        self.0.blit_over(&mut frame.0, &source.0, x, y);
+    }
+
+    /// The frame `timeline` shows at `time` seconds, scaled to fit this renderer's size.
+    fn render(&mut self, py: Python<'_>, timeline: PyRef<'_, Timeline>, time: f32) -> PyFrame {
+        let mut fb = self.new_frame().0;
+        let scene = timeline.scene();
+        // the immediate mode calls above ignore the camera, so it can stay set
+        let r = &mut self.0;
+        r.set_camera(export::fit(scene, Some([fb.width(), fb.height()])));
+        py.detach(|| scene.render(r, &mut fb, time));
+        PyFrame(fb)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<Renderer {}x{}>", self.width(), self.height())
+    }
+}
@@ -0,0 +1,802 @@
+// the manim style API: objects, animations, and a `Timeline` that turns `add`, `play`,
+// `wait` and `remove` calls into tweens on a `renderer::scene::Scene`. python's
+// `codimate.Scene` (python/codimate/scene.py) subclasses `Timeline` and adds `construct()`
+// and `render()`.
+//
+// objects are templates: adding one to a timeline copies it, so changing an object after
+// it's been added only matters to timelines it's added to later (animate it instead).
+// every tween written here continues from the current value (`from: None`), because
+// the timeline's "a first tween with a `from` holds it until it starts" rule is for scene
+// files and would make a python `from_` reach back to before the `play()` call.
+
+use std::path::PathBuf;
+
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyValueError},
+    prelude::*,
+    types::PyTuple,
+};
+
+use crate::{
+    python::color::{ColorLike, PyColor},
+    renderer::{
+        easing::Easing,
+        export::{self, ExportOptions},
+        scene::{Node, NodeId, Property, Scene, Shape, Tween, TweenError, Value},
+    },
+};
+
+create_exception!(
+    codimate,
+    RenderError,
+    PyException,
+    "Rendering a scene or writing the output failed."
+);
+
+fn easing(name: &str) -> PyResult<Easing> {
+    match name {
+        "linear" => Ok(Easing::Linear),
+        "ease_in" => Ok(Easing::EaseIn),
+        "ease_out" => Ok(Easing::EaseOut),
+        "ease_in_out" => Ok(Easing::EaseInOut),
+        "hold" => Ok(Easing::Hold),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown easing {name:?} (expected linear, ease_in, ease_out, ease_in_out or hold)"
+        ))),
+    }
+}
+
+fn positive(what: &str, v: f32) -> PyResult<f32> {
+    if v.is_finite() && v > 0.0 {
+        Ok(v)
+    } else {
+        Err(PyValueError::new_err(format!(
+            "`{what}` has to be a positive number, got {v}"
+        )))
+    }
+}
+
+// objects
+
+/// Something that can be put in a scene. Subclasses pick the shape.
+#[pyclass(name = "Object", module = "codimate", subclass)]
+pub struct Object {
+    node: Node,
+}
+
+impl Object {
+    fn new(
+        shape: Shape,
+        position: (f32, f32),
+        color: Option<ColorLike>,
+        opacity: f32,
+        name: Option<String>,
+    ) -> PyResult<Self> {
+        shape
+            .check()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let color = color.map_or(Ok(crate::color::model::Color::WHITE), ColorLike::resolve)?;
+        Ok(Self {
+            node: Node {
+                name,
+                opacity,
+                ..Node::new(shape, [position.0, position.1], color)
+            },
+        })
+    }
+}
+
+#[pymethods]
+impl Object {
+    /// What kind of shape this is: text, code, rect, circle, line or polygon.
+    #[getter]
+    fn kind(&self) -> &'static str {
+        self.node.shape.kind()
+    }
+
+    #[getter]
+    fn name(&self) -> Option<&str> {
+        self.node.name.as_deref()
+    }
+
+    #[setter]
+    fn set_name(&mut self, name: Option<String>) {
+        self.node.name = name;
+    }
+
+    #[getter]
+    fn position(&self) -> (f32, f32) {
+        let [x, y] = self.node.position;
+        (x, y)
+    }
+
+    #[setter]
+    fn set_position(&mut self, position: (f32, f32)) {
+        self.node.position = [position.0, position.1];
+    }
+
+    #[getter]
+    fn color(&self) -> PyColor {
+        PyColor(self.node.color)
+    }
+
+    #[setter]
+    fn set_color(&mut self, color: ColorLike) -> PyResult<()> {
+        self.node.color = color.resolve()?;
+        Ok(())
+    }
+
+    #[getter]
+    fn opacity(&self) -> f32 {
+        self.node.opacity
+    }
+
+    #[setter]
+    fn set_opacity(&mut self, opacity: f32) {
+        self.node.opacity = opacity;
+    }
+
+    #[getter]
+    fn scale(&self) -> f32 {
+        self.node.scale
+    }
+
+    #[setter]
+    fn set_scale(&mut self, scale: f32) {
+        self.node.scale = scale;
+    }
+
+    /// Clockwise, in degrees.
+    #[getter]
+    fn rotation(&self) -> f32 {
+        self.node.rotation
+    }
+
+    #[setter]
+    fn set_rotation(&mut self, rotation: f32) {
+        self.node.rotation = rotation;
+    }
+
+    fn __repr__(&self) -> String {
+        let [x, y] = self.node.position;
+        match &self.node.name {
+            Some(name) => format!("<{} {name:?} at ({x}, {y})>", self.node.shape.kind()),
+            None => format!("<{} at ({x}, {y})>", self.node.shape.kind()),
+        }
+    }
+}
+
+/// Text with its top left corner at `position`. `size` is the line height.
+#[pyclass(name = "Text", module = "codimate", extends = Object)]
+pub struct Text;
+
+#[pymethods]
+impl Text {
+    #[new]
+    #[pyo3(signature = (text, size = 32.0, *, position = (0.0, 0.0), color = None, opacity = 1.0, name = None))]
+    fn new(
+        text: String,
+        size: f32,
+        position: (f32, f32),
+        color: Option<ColorLike>,
+        opacity: f32,
+        name: Option<String>,
+    ) -> PyResult<(Self, Object)> {
+        let shape = Shape::Text {
+            text,
+            size,
+            font: None,
+        };
+        Ok((Text, Object::new(shape, position, color, opacity, name)?))
+    }
+}
+
+/// A code block, optionally on a `background` panel with `padding` around the code.
+#[pyclass(name = "Code", module = "codimate", extends = Object)]
+pub struct Code;
+
+#[pymethods]
+impl Code {
+    #[new]
+    #[pyo3(signature = (source, size = 24.0, *, background = None, padding = None, position = (0.0, 0.0), color = None, opacity = 1.0, name = None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        source: String,
+        size: f32,
+        background: Option<ColorLike>,
+        padding: Option<f32>,
+        position: (f32, f32),
+        color: Option<ColorLike>,
+        opacity: f32,
+        name: Option<String>,
+    ) -> PyResult<(Self, Object)> {
+        let background = background.map(ColorLike::resolve).transpose()?;
+        let padding = padding.unwrap_or(if background.is_some() { 24.0 } else { 0.0 });
+        let shape = Shape::Code {
+            source,
+            size,
+            font: None,
+            background,
+            padding,
+        };
+        Ok((Code, Object::new(shape, position, color, opacity, name)?))
+    }
+}
+
+/// A `width` x `height` rectangle with its top left corner at `position`.
+#[pyclass(name = "Rect", module = "codimate", extends = Object)]
+pub struct Rect;
+
+#[pymethods]
+impl Rect {
+    #[new]
+    #[pyo3(signature = (width, height, *, radius = 0.0, position = (0.0, 0.0), color = None, opacity = 1.0, name = None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        width: f32,
+        height: f32,
+        radius: f32,
+        position: (f32, f32),
+        color: Option<ColorLike>,
+        opacity: f32,
+        name: Option<String>,
+    ) -> PyResult<(Self, Object)> {
+        let shape = Shape::Rect {
+            size: [width, height],
+            radius,
+        };
+        Ok((Rect, Object::new(shape, position, color, opacity, name)?))
+    }
+}
+
+/// A circle centered on `position`.
+#[pyclass(name = "Circle", module = "codimate", extends = Object)]
+pub struct Circle;
+
+#[pymethods]
+impl Circle {
+    #[new]
+    #[pyo3(signature = (radius, *, position = (0.0, 0.0), color = None, opacity = 1.0, name = None))]
+    fn new(
+        radius: f32,
+        position: (f32, f32),
+        color: Option<ColorLike>,
+        opacity: f32,
+        name: Option<String>,
+    ) -> PyResult<(Self, Object)> {
+        let shape = Shape::Circle { radius };
+        Ok((Circle, Object::new(shape, position, color, opacity, name)?))
+    }
+}
+
+/// A line from `position` to `position + end`.
+#[pyclass(name = "Line", module = "codimate", extends = Object)]
+pub struct Line;
+
+#[pymethods]
+impl Line {
+    #[new]
+    #[pyo3(signature = (end, width = 2.0, *, position = (0.0, 0.0), color = None, opacity = 1.0, name = None))]
+    fn new(
+        end: (f32, f32),
+        width: f32,
+        position: (f32, f32),
+        color: Option<ColorLike>,
+        opacity: f32,
+        name: Option<String>,
+    ) -> PyResult<(Self, Object)> {
+        let shape = Shape::Line {
+            end: [end.0, end.1],
+            width,
+        };
+        Ok((Line, Object::new(shape, position, color, opacity, name)?))
+    }
+}
+
+/// A closed polygon through `points`, relative to `position`.
+#[pyclass(name = "Polygon", module = "codimate", extends = Object)]
+pub struct Polygon;
+
+#[pymethods]
+impl Polygon {
+    #[new]
+    #[pyo3(signature = (points, *, position = (0.0, 0.0), color = None, opacity = 1.0, name = None))]
+    fn new(
+        points: Vec<(f32, f32)>,
+        position: (f32, f32),
+        color: Option<ColorLike>,
+        opacity: f32,
+        name: Option<String>,
+    ) -> PyResult<(Self, Object)> {
+        let shape = Shape::Polygon {
+            points: points.into_iter().map(|(x, y)| [x, y]).collect(),
+        };
+        Ok((Polygon, Object::new(shape, position, color, opacity, name)?))
+    }
+}
+
+// animations
+
+/// A tween value as python writes it.
+#[derive(FromPyObject)]
+enum AnyValue {
+    Number(f32),
+    Point((f32, f32)),
+    Color(ColorLike),
+}
+
+impl AnyValue {
+    fn resolve(self) -> PyResult<Value> {
+        Ok(match self {
+            AnyValue::Number(n) => Value::Number(n),
+            AnyValue::Point((x, y)) => Value::Point([x, y]),
+            AnyValue::Color(c) => Value::Color(c.resolve()?),
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Change {
+    FadeIn,
+    FadeOut,
+    To {
+        property: Property,
+        from: Option<Value>,
+        to: Value,
+    },
+    Shift([f32; 2]),
+    Rotate(f32),
+    Scale(f32),
+}
+
+/// A change to one object, played by `Timeline.play`.
+#[pyclass(name = "Animation", module = "codimate", subclass)]
+pub struct Animation {
+    target: Py<Object>,
+    change: Change,
+    easing: Easing,
+}
+
+impl Animation {
+    fn new(target: Py<Object>, change: Change, easing_name: &str) -> PyResult<Self> {
+        Ok(Self {
+            target,
+            change,
+            easing: easing(easing_name)?,
+        })
+    }
+}
+
+#[pymethods]
+impl Animation {
+    /// Animate any property (position, x, y, opacity, scale, rotation or color) to `to`,
+    /// jumping to `from_` first if it's given.
+    #[new]
+    #[pyo3(signature = (target, property, to, *, from_ = None, easing = "ease_in_out"))]
+    fn animate(
+        target: Py<Object>,
+        property: &str,
+        to: AnyValue,
+        from_: Option<AnyValue>,
+        easing: &str,
+    ) -> PyResult<Self> {
+        let property = Property::from_name(property).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "unknown property {property:?} (expected position, x, y, opacity, scale, rotation or color)"
+            ))
+        })?;
+        let (to, from) = (to.resolve()?, from_.map(AnyValue::resolve).transpose()?);
+        for value in from.iter().chain([&to]) {
+            if !property.accepts(value) {
+                let error = TweenError::WrongValue {
+                    property,
+                    value: *value,
+                };
+                return Err(PyValueError::new_err(error.to_string()));
+            }
+        }
+        Self::new(target, Change::To { property, from, to }, easing)
+    }
+
+    /// The object being animated.
+    #[getter]
+    fn target(&self, py: Python<'_>) -> Py<Object> {
+        self.target.clone_ref(py)
+    }
+}
+
+/// Fade an object in from transparent, adding it to the scene if it isn't yet.
+#[pyclass(name = "FadeIn", module = "codimate", extends = Animation)]
+pub struct FadeIn;
+
+#[pymethods]
+impl FadeIn {
+    #[new]
+    #[pyo3(signature = (target, *, easing = "ease_in_out"))]
+    fn new(target: Py<Object>, easing: &str) -> PyResult<(Self, Animation)> {
+        Ok((FadeIn, Animation::new(target, Change::FadeIn, easing)?))
+    }
+}
+
+/// Fade an object out. Adding it again brings it back.
+#[pyclass(name = "FadeOut", module = "codimate", extends = Animation)]
+pub struct FadeOut;
+
+#[pymethods]
+impl FadeOut {
+    #[new]
+    #[pyo3(signature = (target, *, easing = "ease_in_out"))]
+    fn new(target: Py<Object>, easing: &str) -> PyResult<(Self, Animation)> {
+        Ok((FadeOut, Animation::new(target, Change::FadeOut, easing)?))
+    }
+}
+
+/// Move an object to `position`.
+#[pyclass(name = "MoveTo", module = "codimate", extends = Animation)]
+pub struct MoveTo;
+
+#[pymethods]
+impl MoveTo {
+    #[new]
+    #[pyo3(signature = (target, position, *, easing = "ease_in_out"))]
+    fn new(target: Py<Object>, position: (f32, f32), easing: &str) -> PyResult<(Self, Animation)> {
+        let change = Change::To {
+            property: Property::Position,
+            from: None,
+            to: Value::Point([position.0, position.1]),
+        };
+        Ok((MoveTo, Animation::new(target, change, easing)?))
+    }
+}
+
+/// Move an object by `offset` from wherever it is.
+#[pyclass(name = "Shift", module = "codimate", extends = Animation)]
+pub struct Shift;
+
+#[pymethods]
+impl Shift {
+    #[new]
+    #[pyo3(signature = (target, offset, *, easing = "ease_in_out"))]
+    fn new(target: Py<Object>, offset: (f32, f32), easing: &str) -> PyResult<(Self, Animation)> {
+        let change = Change::Shift([offset.0, offset.1]);
+        Ok((Shift, Animation::new(target, change, easing)?))
+    }
+}
+
+/// Rotate an object by `degrees` (clockwise) around its center.
+#[pyclass(name = "Rotate", module = "codimate", extends = Animation)]
+pub struct Rotate;
+
+#[pymethods]
+impl Rotate {
+    #[new]
+    #[pyo3(signature = (target, degrees, *, easing = "ease_in_out"))]
+    fn new(target: Py<Object>, degrees: f32, easing: &str) -> PyResult<(Self, Animation)> {
+        Ok((
+            Rotate,
+            Animation::new(target, Change::Rotate(degrees), easing)?,
+        ))
+    }
+}
+
+/// Scale an object by `factor` around its center.
+#[pyclass(name = "ScaleInPlace", module = "codimate", extends = Animation)]
+pub struct ScaleInPlace;
+
+#[pymethods]
+impl ScaleInPlace {
+    #[new]
+    #[pyo3(signature = (target, factor, *, easing = "ease_in_out"))]
+    fn new(target: Py<Object>, factor: f32, easing: &str) -> PyResult<(Self, Animation)> {
+        Ok((
+            ScaleInPlace,
+            Animation::new(target, Change::Scale(factor), easing)?,
+        ))
+    }
+}
+
+/// Blend an object's color to `color` (in OKLCH).
+#[pyclass(name = "FadeToColor", module = "codimate", extends = Animation)]
+pub struct FadeToColor;
+
+#[pymethods]
+impl FadeToColor {
+    #[new]
+    #[pyo3(signature = (target, color, *, easing = "ease_in_out"))]
+    fn new(target: Py<Object>, color: ColorLike, easing: &str) -> PyResult<(Self, Animation)> {
+        let change = Change::To {
+            property: Property::Color,
+            from: None,
+            to: Value::Color(color.resolve()?),
+        };
+        Ok((FadeToColor, Animation::new(target, change, easing)?))
+    }
+}
+
+// the timeline
+
+struct Entry {
+    object: Py<Object>,
+    id: NodeId,
+    // the opacity it was added with, which `FadeIn` and `add` go back to
+    opacity: f32,
+    shown: bool,
+}
+
+/// A scene being built up call by call, with a playhead (`time`) that `play` and `wait`
+/// move forward. `codimate.Scene` is the class to subclass.
+#[pyclass(name = "Timeline", module = "codimate", subclass)]
+pub struct Timeline {
+    scene: Scene,
+    time: f32,
+    entries: Vec<Entry>,
+}
+
+impl Timeline {
+    pub fn scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    fn entry(&self, object: &Py<Object>) -> Option<usize> {
+        self.entries.iter().position(|e| e.object.is(object))
+    }
+
+    /// Tween `property` of `id` to `to` from the playhead.
+    fn tween(&mut self, id: NodeId, property: Property, to: Value, duration: f32, easing: Easing) {
+        self.scene
+            .animate(Tween {
+                node: id,
+                property,
+                from: None,
+                to,
+                start: self.time,
+                duration,
+                easing,
+            })
+            .expect("values are checked against their property when the animation is made");
+    }
+
+    fn set(&mut self, id: NodeId, property: Property, to: Value) {
+        self.tween(id, property, to, 0.0, Easing::Linear);
+    }
+
+    /// Make an object visible from the playhead on, adding it if it's new.
+    fn show(&mut self, py: Python<'_>, object: &Py<Object>) -> NodeId {
+        match self.entry(object) {
+            Some(i) if self.entries[i].shown => self.entries[i].id,
+            Some(i) => {
+                let (id, opacity) = (self.entries[i].id, self.entries[i].opacity);
+                self.entries[i].shown = true;
+                self.set(id, Property::Opacity, Value::Number(opacity));
+                id
+            }
+            None => {
+                let mut node = object.borrow(py).node.clone();
+                let opacity = node.opacity;
+                // nodes exist for the whole scene, so hide it until now
+                if self.time > 0.0 {
+                    node.opacity = 0.0;
+                }
+                let id = self.scene.add(node);
+                if self.time > 0.0 {
+                    self.set(id, Property::Opacity, Value::Number(opacity));
+                }
+                self.entries.push(Entry {
+                    object: object.clone_ref(py),
+                    id,
+                    opacity,
+                    shown: true,
+                });
+                id
+            }
+        }
+    }
+
+    fn apply(&mut self, py: Python<'_>, animation: &Animation, run_time: f32) {
+        let id = self.show(py, &animation.target);
+        let now = self.scene.state_at(id, self.time);
+        let (easing, i) = (
+            animation.easing,
+            self.entry(&animation.target).expect("just shown"),
+        );
+        match animation.change {
+            Change::FadeIn => {
+                let opacity = self.entries[i].opacity;
+                self.set(id, Property::Opacity, Value::Number(0.0));
+                self.tween(
+                    id,
+                    Property::Opacity,
+                    Value::Number(opacity),
+                    run_time,
+                    easing,
+                );
+            }
+            Change::FadeOut => {
+                self.entries[i].shown = false;
+                self.tween(id, Property::Opacity, Value::Number(0.0), run_time, easing);
+            }
+            Change::To { property, from, to } => {
+                if let Some(from) = from {
+                    self.set(id, property, from);
+                }
+                self.tween(id, property, to, run_time, easing);
+            }
+            Change::Shift([dx, dy]) => {
+                let [x, y] = now.position;
+                let to = Value::Point([x + dx, y + dy]);
+                self.tween(id, Property::Position, to, run_time, easing);
+            }
+            Change::Rotate(degrees) => {
+                let to = Value::Number(now.rotation + degrees);
+                self.tween(id, Property::Rotation, to, run_time, easing);
+            }
+            Change::Scale(factor) => {
+                let to = Value::Number(now.scale * factor);
+                self.tween(id, Property::Scale, to, run_time, easing);
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl Timeline {
+    #[new]
+    #[pyo3(signature = (width = 1280, height = 720, fps = 30.0, background = None))]
+    fn new(width: u16, height: u16, fps: f32, background: Option<ColorLike>) -> PyResult<Self> {
+        let mut scene = Scene::new(width, height);
+        scene.fps = positive("fps", fps)?;
+        if let Some(background) = background {
+            scene.background = background.resolve()?;
+        }
+        Ok(Self {
+            scene,
+            time: 0.0,
+            entries: Vec::new(),
+        })
+    }
+
+    #[getter]
+    fn width(&self) -> u16 {
+        self.scene.width
+    }
+
+    #[getter]
+    fn height(&self) -> u16 {
+        self.scene.height
+    }
+
+    #[getter]
+    fn fps(&self) -> f32 {
+        self.scene.fps
+    }
+
+    #[setter]
+    fn set_fps(&mut self, fps: f32) -> PyResult<()> {
+        self.scene.fps = positive("fps", fps)?;
+        Ok(())
+    }
+
+    #[getter]
+    fn background(&self) -> PyColor {
+        PyColor(self.scene.background)
+    }
+
+    #[setter]
+    fn set_background(&mut self, background: ColorLike) -> PyResult<()> {
+        self.scene.background = background.resolve()?;
+        Ok(())
+    }
+
+    /// Where the playhead is, in seconds. It's also how long the scene is so far.
+    #[getter]
+    fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Show objects from now on. Adding one that's already showing does nothing.
+    #[pyo3(signature = (*objects))]
+    fn add(&mut self, py: Python<'_>, objects: Vec<Py<Object>>) {
+        for object in &objects {
+            self.show(py, object);
+        }
+    }
+
+    /// Hide objects from now on.
+    #[pyo3(signature = (*objects))]
+    fn remove(&mut self, objects: Vec<Py<Object>>) {
+        for object in &objects {
+            if let Some(i) = self.entry(object)
+                && self.entries[i].shown
+            {
+                self.entries[i].shown = false;
+                self.set(self.entries[i].id, Property::Opacity, Value::Number(0.0));
+            }
+        }
+    }
+
+    /// Play animations together over `run_time` seconds, then move the playhead past them.
+    #[pyo3(signature = (*animations, run_time = 1.0))]
+    fn play(
+        &mut self,
+        py: Python<'_>,
+        animations: &Bound<'_, PyTuple>,
+        run_time: f32,
+    ) -> PyResult<()> {
+        if animations.is_empty() {
+            return Err(PyValueError::new_err("play() needs at least one animation"));
+        }
+        if !(run_time.is_finite() && run_time >= 0.0) {
+            return Err(PyValueError::new_err(format!(
+                "`run_time` has to be a non-negative number, got {run_time}"
+            )));
+        }
+        // check them all before changing anything
+        let animations = animations
+            .iter()
+            .map(|a| a.cast_into::<Animation>().map_err(PyErr::from))
+            .collect::<PyResult<Vec<_>>>()?;
+        for animation in &animations {
+            self.apply(py, &animation.borrow(), run_time);
+        }
+        self.time += run_time;
+        Ok(())
+    }
+
+    /// Hold still for `duration` seconds.
+    #[pyo3(signature = (duration = 1.0))]
+    fn wait(&mut self, duration: f32) -> PyResult<()> {
+        if !(duration.is_finite() && duration >= 0.0) {
+            return Err(PyValueError::new_err(format!(
+                "`duration` has to be a non-negative number, got {duration}"
+            )));
+        }
+        self.time += duration;
+        Ok(())
+    }
+
+    /// Start over with an empty timeline, keeping the canvas settings.
+    fn clear(&mut self) {
+        let old = &self.scene;
+        let mut scene = Scene::new(old.width, old.height);
+        scene.fps = old.fps;
+        scene.background = old.background;
+        self.scene = scene;
+        self.time = 0.0;
+        self.entries.clear();
+    }
+
+    /// Render what's on the timeline to a video, GIF or PNG sequence (picked from `output`,
+    /// like the `codimate render` command). An empty timeline renders a single frame.
+    ///
+    /// Returns how many frames were written.
+    #[pyo3(signature = (output, *, quality = 80, size = None))]
+    fn export(
+        &mut self,
+        py: Python<'_>,
+        output: PathBuf,
+        quality: u8,
+        size: Option<(u16, u16)>,
+    ) -> PyResult<u32> {
+        self.scene.duration = self.time.max(1.0 / self.scene.fps);
+        let options = ExportOptions {
+            size: size.map(|(w, h)| [w, h]),
+            frames: None,
+            quality: quality.min(100),
+        };
+        let scene = &self.scene;
+        py.detach(|| export::export(scene, &output, &options, |_, _| {}))
+            .map_err(|e| RenderError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<Timeline {}x{} @ {} fps, {} objects, {}s>",
+            self.scene.width,
+            self.scene.height,
+            self.scene.fps,
+            self.entries.len(),
+            self.time
+        )
+    }
+}
@@ -0,0 +1,305 @@
+#![allow(dead_code)]
+
+use crate::renderer::{easing::Easing, transform::Transform2D};
+
+/// A view into the scene's world space.
+///
+/// The camera looks at `position`, which lands in the middle of the viewport.
+///
+/// # Fields
+///
+/// - `position` (`[f32; 2]`) - The world point at the center of the frame.
+/// - `zoom` (`f32`) - Screen pixels per world unit (2.0 shows everything twice as big).
+/// - `rotation` (`f32`) - Clockwise camera roll in degrees. The scene turns the other way.
+/// - `viewport` (`[f32; 2]`) - The output size in pixels.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::renderer::camera::Camera;
+///
+/// // start on the whole 1080p canvas, end zoomed onto a code block
+/// let wide = Camera::new(1920.0, 1080.0);
+/// let close = wide.framing([400.0, 300.0, 880.0, 560.0], 32.0);
+/// let halfway = wide.lerp(&close, 0.5);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub position: [f32; 2],
+    pub zoom: f32,
+    pub rotation: f32,
+    pub viewport: [f32; 2],
+}
+
+impl Camera {
+    /// A camera that shows world space 1:1, so the view is the identity.
+    #[must_use]
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            position: [width / 2.0, height / 2.0],
+            zoom: 1.0,
+            rotation: 0.0,
+            viewport: [width, height],
+        }
+    }
+
+    /// The world to screen transform.
+    #[must_use]
+    pub fn view(&self) -> Transform2D {
+        Transform2D::translate(-self.position[0], -self.position[1])
+            .then(Transform2D::rotate_deg(-self.rotation))
+            .then(Transform2D::scale(self.zoom, self.zoom))
+            .then(Transform2D::translate(
+                self.viewport[0] / 2.0,
+                self.viewport[1] / 2.0,
+            ))
+    }
+
+    #[must_use]
+    pub fn world_to_screen(&self, p: [f32; 2]) -> [f32; 2] {
+        self.view().apply(p)
+    }
+
+    /// Where a screen pixel lands in the world (`None` at zero zoom).
+    #[must_use]
+    pub fn screen_to_world(&self, p: [f32; 2]) -> Option<[f32; 2]> {
+        Some(self.view().invert()?.apply(p))
+    }
+
+    /// The same camera moved and zoomed so a world rect `[x0, y0, x1, y1]` fills the
+    /// viewport with `padding` screen pixels to spare. Rotation is kept.
+    #[must_use]
+    pub fn framing(&self, rect: [f32; 4], padding: f32) -> Self {
+        let (w, h) = ((rect[2] - rect[0]).abs(), (rect[3] - rect[1]).abs());
+        let room = [
+            (self.viewport[0] - 2.0 * padding).max(1.0),
+            (self.viewport[1] - 2.0 * padding).max(1.0),
+        ];
+        let zoom = if w > 0.0 && h > 0.0 {
+            (room[0] / w).min(room[1] / h)
+        } else {
+            self.zoom
+        };
+
+        Self {
+            position: [(rect[0] + rect[2]) / 2.0, (rect[1] + rect[3]) / 2.0],
+            zoom,
+            ..*self
+        }
+    }
+
+    /// Blend toward another camera.
+    ///
+    /// Zoom is interpolated geometrically so zooming 1x to 4x passes 2x halfway and
+    /// the motion feels even.
+    #[must_use]
+    pub fn lerp(&self, other: &Camera, t: f32) -> Self {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        let zoom = if self.zoom > 0.0 && other.zoom > 0.0 {
+            self.zoom * (other.zoom / self.zoom).powf(t)
+        } else {
+            mix(self.zoom, other.zoom)
+        };
+
+        Self {
+            position: [
+                mix(self.position[0], other.position[0]),
+                mix(self.position[1], other.position[1]),
+            ],
+            zoom,
+            rotation: mix(self.rotation, other.rotation),
+            viewport: [
+                mix(self.viewport[0], other.viewport[0]),
+                mix(self.viewport[1], other.viewport[1]),
+            ],
+        }
+    }
+}
+
+/// One stop on a camera track.
+///
+/// # Fields
+///
+/// - `time` (`f32`) - When the camera gets here, in seconds.
+/// - `camera` (`Camera`) - The camera at that time.
+/// - `easing` (`Easing`) - How the camera moves from the previous key to this one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraKey {
+    pub time: f32,
+    pub camera: Camera,
+    pub easing: Easing,
+}
+
+/// Camera keyframes sampled over time.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CameraTrack {
+    keys: Vec<CameraKey>,
+}
+
+impl CameraTrack {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a keyframe, keeping the track sorted by time.
+    pub fn key(&mut self, time: f32, camera: Camera, easing: Easing) -> &mut Self {
+        let at = self.keys.partition_point(|k| k.time <= time);
+        self.keys.insert(
+            at,
+            CameraKey {
+                time,
+                camera,
+                easing,
+            },
+        );
+        self
+    }
+
+    #[must_use]
+    pub fn keys(&self) -> &[CameraKey] {
+        &self.keys
+    }
+
+    /// The camera at a point in time. Holds the first/last key outside of the track.
+    #[must_use]
+    pub fn sample(&self, time: f32) -> Option<Camera> {
+        let first = self.keys.first()?;
+        if time <= first.time {
+            return Some(first.camera);
+        }
+
+        let next = self.keys.partition_point(|k| k.time <= time);
+        let Some(to) = self.keys.get(next) else {
+            return self.keys.last().map(|k| k.camera);
+        };
+        let from = &self.keys[next - 1];
+
+        let span = to.time - from.time;
+        let t = if span > 0.0 {
+            (time - from.time) / span
+        } else {
+            1.0
+        };
+        Some(from.camera.lerp(&to.camera, to.easing.apply(t)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        color::model::Color,
+        renderer::{
+            frame::Frame,
+            path::Path,
+            pool::FramePool,
+            render::Renderer,
+            sample::{Filter, Texture},
+            tile::Tiling,
+        },
+    };
+
+    fn close(a: [f32; 2], b: [f32; 2]) -> bool {
+        (a[0] - b[0]).abs() < 1e-3 && (a[1] - b[1]).abs() < 1e-3
+    }
+
+    #[test]
+    fn default_camera_is_identity() {
+        let cam = Camera::new(1920.0, 1080.0);
+        assert!(close(cam.world_to_screen([10.0, 20.0]), [10.0, 20.0]));
+    }
+
+    #[test]
+    fn framing_centers_and_fits() {
+        // the 200x50 rect is too wide to be limited by height
+        let cam = Camera::new(1000.0, 500.0).framing([100.0, 100.0, 300.0, 150.0], 50.0);
+        assert!((cam.zoom - 4.5).abs() < 1e-5);
+        assert!(close(cam.world_to_screen([200.0, 125.0]), [500.0, 250.0]));
+        assert!(close(cam.world_to_screen([100.0, 150.0]), [50.0, 362.5]));
+
+        let back = cam.screen_to_world([50.0, 362.5]).unwrap();
+        assert!(close(back, [100.0, 150.0]));
+    }
+
+    #[test]
+    fn zoom_lerps_geometrically() {
+        let a = Camera::new(100.0, 100.0);
+        let b = Camera { zoom: 4.0, ..a };
+        assert!((a.lerp(&b, 0.5).zoom - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn track_samples_between_keys() {
+        let a = Camera::new(100.0, 100.0);
+        let b = Camera {
+            position: [100.0, 50.0],
+            ..a
+        };
+        let mut track = CameraTrack::new();
+        track
+            .key(2.0, b, Easing::Linear)
+            .key(0.0, a, Easing::Linear);
+
+        assert_eq!(track.sample(-1.0), Some(a));
+        assert_eq!(track.sample(5.0), Some(b));
+        let mid = track.sample(1.0).unwrap();
+        assert!(close(mid.position, [75.0, 50.0]));
+        assert!(CameraTrack::new().sample(0.0).is_none());
+    }
+
+    fn render(build: impl FnOnce(&mut Renderer)) -> Frame {
+        let mut r = Renderer::new(FramePool::new(64, 64, 1));
+        r.set_tiling(Tiling::Serial);
+        let mut fb = r.acquire().unwrap();
+        r.begin_frame();
+        r.queue_clear(Color::BLACK);
+        build(&mut r);
+        r.end_frame(&mut fb);
+        fb
+    }
+
+    #[test]
+    fn zoomed_paths_are_rerasterized() {
+        // a 4px square zoomed 8x covers half the frame with hard interior pixels
+        let cam = Camera::new(64.0, 64.0).framing([0.0, 0.0, 8.0, 8.0], 0.0);
+        let fb = render(|r| {
+            r.set_camera(Some(cam));
+            r.queue_fill_path(Path::rect(0.0, 0.0, 4.0, 4.0), Color::WHITE);
+        });
+        assert_eq!(fb.get_pixel(31, 31).unwrap().into_rgba(), [255; 4]);
+        assert_eq!(fb.get_pixel(32, 32).unwrap().into_rgba(), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn images_follow_transform_and_camera() {
+        let mut img = Frame::new(4, 4);
+        for px in img.as_bytes_mut().chunks_exact_mut(4) {
+            px.copy_from_slice(&[255, 0, 0, 255]);
+        }
+        let img = Arc::new(Texture::new(&img));
+
+        let fb = render(|r| {
+            r.set_camera(Some(Camera {
+                zoom: 2.0,
+                ..Camera::new(64.0, 64.0)
+            }));
+            r.push_transform(Transform2D::translate(32.0, 32.0));
+            r.queue_image(
+                Arc::clone(&img),
+                Transform2D::scale(2.0, 2.0),
+                Filter::Bilinear,
+            );
+            r.pop_transform();
+        });
+        // world (32, 32) is the frame center, the 8 world px image is 16 screen px
+        assert_eq!(fb.get_pixel(40, 40).unwrap().into_rgba(), [255, 0, 0, 255]);
+        assert_eq!(fb.get_pixel(29, 40).unwrap().into_rgba(), [0, 0, 0, 255]);
+        assert_eq!(fb.get_pixel(50, 40).unwrap().into_rgba(), [0, 0, 0, 255]);
+        // the edge is filtered, not blocky
+        let edge = fb.get_pixel(32, 40).unwrap().into_rgba();
+        assert!(edge[0] > 0 && edge[0] < 255, "{edge:?}");
+    }
+}
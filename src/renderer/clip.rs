@@ -0,0 +1,330 @@
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use crate::{
+    color::model::Color,
+    renderer::{command::PixelRect, frame::Frame, path::Path},
+};
+
+/// Which part of a mask layer's pixels controls visibility.
+///
+/// # Variants
+///
+/// - `Alpha` - The mask's alpha channel.
+/// - `Luminance` - The mask's relative luminance times its alpha
+///   (white shows, black hides), like CSS `mask-type: luminance`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MaskMode {
+    #[default]
+    Alpha,
+    Luminance,
+}
+
+/// A rendered layer whose pixels modulate whatever is drawn under it.
+///
+/// Everything outside of the mask layer is hidden.
+///
+/// # Fields
+///
+/// - `layer` (`Arc<Frame>`) - The mask pixels.
+/// - `x` (`i32`) - Where the mask's left edge sits in device space.
+/// - `y` (`i32`) - Where the mask's top edge sits in device space.
+/// - `mode` (`MaskMode`) - How pixels turn into visibility.
+#[derive(Clone, Debug)]
+pub struct Mask {
+    pub layer: Arc<Frame>,
+    pub x: i32,
+    pub y: i32,
+    pub mode: MaskMode,
+}
+
+impl Mask {
+    #[must_use]
+    pub fn new(layer: impl Into<Arc<Frame>>, x: i32, y: i32, mode: MaskMode) -> Self {
+        Self {
+            layer: layer.into(),
+            x,
+            y,
+            mode,
+        }
+    }
+
+    /// The device pixels the mask covers.
+    #[must_use]
+    pub fn pixel_bounds(&self) -> PixelRect {
+        let clamp = |v: i32| v.clamp(0, u16::MAX as i32) as u16;
+        PixelRect::new(
+            clamp(self.x),
+            clamp(self.y),
+            clamp(self.x + self.layer.width() as i32),
+            clamp(self.y + self.layer.height() as i32),
+        )
+    }
+
+    /// How visible a device pixel is, from 0.0 to 1.0.
+    #[must_use]
+    #[inline]
+    pub fn value(&self, x: u16, y: u16) -> f32 {
+        let (mx, my) = (x as i32 - self.x, y as i32 - self.y);
+        if mx < 0 || my < 0 {
+            return 0.0;
+        }
+        let Some(c) = self.layer.get_pixel(mx as u16, my as u16) else {
+            return 0.0;
+        };
+
+        let a = c.into_rgba()[3] as f32 / 255.0;
+        match self.mode {
+            MaskMode::Alpha => a,
+            MaskMode::Luminance => {
+                // ColorFloat may be f64
+                let lum: f32 = c.with_alpha(255).relative_luminance() as _;
+                lum * a
+            }
+        }
+    }
+}
+
+// frames are big, compare masks by identity
+impl PartialEq for Mask {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.layer, &other.layer)
+            && (self.x, self.y, self.mode) == (other.x, other.y, other.mode)
+    }
+}
+
+/// One entry on the renderer's clip stack.
+///
+/// # Variants
+///
+/// - `Rect` - Only draw inside a pixel rectangle. This is the fast path,
+///   it only shrinks the area commands rasterize.
+/// - `Path` - Only draw inside an anti-aliased path.
+/// - `Mask` - Modulate drawing by a mask layer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Clip {
+    Rect(PixelRect),
+    Path(Arc<Path>),
+    Mask(Mask),
+}
+
+impl Clip {
+    /// The device pixels that can stay visible under this clip.
+    #[must_use]
+    pub fn pixel_bounds(&self) -> PixelRect {
+        match self {
+            Clip::Rect(r) => *r,
+            Clip::Path(p) => p.pixel_bounds().unwrap_or(PixelRect::EMPTY),
+            Clip::Mask(m) => m.pixel_bounds(),
+        }
+    }
+}
+
+impl From<PixelRect> for Clip {
+    fn from(value: PixelRect) -> Self {
+        Clip::Rect(value)
+    }
+}
+
+impl From<Path> for Clip {
+    fn from(value: Path) -> Self {
+        Clip::Path(Arc::new(value))
+    }
+}
+
+impl From<Mask> for Clip {
+    fn from(value: Mask) -> Self {
+        Clip::Mask(value)
+    }
+}
+
+/// A snapshot of nested clips, all of which apply at once.
+///
+/// Recorded commands hold on to the snapshot that was active when they were queued.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClipChain {
+    clips: Vec<Clip>,
+    bounds: PixelRect,
+    soft: bool,
+}
+
+impl ClipChain {
+    #[must_use]
+    pub fn new(clips: Vec<Clip>) -> Self {
+        let bounds = clips
+            .iter()
+            .fold(PixelRect::new(0, 0, u16::MAX, u16::MAX), |b, c| {
+                b.intersect(c.pixel_bounds())
+            });
+        let soft = clips.iter().any(|c| !matches!(c, Clip::Rect(_)));
+        Self {
+            clips,
+            bounds,
+            soft,
+        }
+    }
+
+    #[must_use]
+    pub fn clips(&self) -> &[Clip] {
+        &self.clips
+    }
+
+    /// The area anything drawn under the chain can end up in.
+    #[must_use]
+    pub fn bounds(&self) -> PixelRect {
+        self.bounds
+    }
+
+    /// Per-pixel visibility over an area, row by row.
+    /// `None` means only rect clips are involved, so everything inside `bounds` is fully visible.
+    #[must_use]
+    pub fn coverage(&self, area: PixelRect) -> Option<Vec<f32>> {
+        if !self.soft {
+            return None;
+        }
+
+        let w = area.width() as usize;
+        let mut cov = vec![1.0; w * area.height() as usize];
+        for clip in &self.clips {
+            match clip {
+                // already folded into bounds
+                Clip::Rect(_) => {}
+                Clip::Path(p) => {
+                    for (c, pc) in cov.iter_mut().zip(p.coverage(area)) {
+                        *c *= pc;
+                    }
+                }
+                Clip::Mask(m) => {
+                    for y in area.y0..area.y1 {
+                        let row = (y - area.y0) as usize * w;
+                        for x in area.x0..area.x1 {
+                            cov[row + (x - area.x0) as usize] *= m.value(x, y);
+                        }
+                    }
+                }
+            }
+        }
+        Some(cov)
+    }
+}
+
+/// Mix freshly drawn pixels over the old ones by per-pixel coverage, in linear space.
+///
+/// `old` and `new` are RGBA pixel rows of the same area as `coverage`.
+pub(crate) fn apply_coverage(old: &[u8], new: &mut [u8], coverage: &[f32]) {
+    for ((o, n), &c) in old
+        .chunks_exact(4)
+        .zip(new.chunks_exact_mut(4))
+        .zip(coverage)
+    {
+        if c >= 1.0 || o == n {
+            continue;
+        }
+        if c <= 0.0 {
+            n.copy_from_slice(o);
+            continue;
+        }
+
+        let before = Color::from_rgba([o[0], o[1], o[2], o[3]]);
+        let after = Color::from_rgba([n[0], n[1], n[2], n[3]]);
+        n.copy_from_slice(&before.lerp_linear(after, c as _).into_rgba());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{frame::Frame, pool::FramePool, render::Renderer, tile::Tiling};
+
+    fn render(build: impl FnOnce(&mut Renderer)) -> Frame {
+        let mut r = Renderer::new(FramePool::new(64, 64, 1));
+        r.set_tiling(Tiling::Serial);
+        let mut fb = r.acquire().unwrap();
+        r.begin_frame();
+        r.queue_clear(Color::BLACK);
+        build(&mut r);
+        r.end_frame(&mut fb);
+        fb
+    }
+
+    fn px(fb: &Frame, x: u16, y: u16) -> [u8; 4] {
+        fb.get_pixel(x, y).unwrap().into_rgba()
+    }
+
+    #[test]
+    fn nested_clips_intersect_and_pop() {
+        let fb = render(|r| {
+            r.push_clip(PixelRect::new(0, 0, 32, 64));
+            r.push_clip(Path::circle(32.0, 32.0, 16.0));
+            r.queue_clear(Color::WHITE);
+            r.pop_clip();
+            r.queue_rect(0, 60, 64, 4, Color::RED);
+            r.pop_clip();
+            r.queue_rect(60, 0, 4, 4, Color::BLUE);
+        });
+
+        // inside both
+        assert_eq!(px(&fb, 24, 32), [255, 255, 255, 255]);
+        // inside the circle but outside the rect
+        assert_eq!(px(&fb, 40, 32), [0, 0, 0, 255]);
+        // only the rect clip was left for the red strip
+        assert_eq!(px(&fb, 10, 62), [255, 0, 0, 255]);
+        assert_eq!(px(&fb, 40, 62), [0, 0, 0, 255]);
+        // nothing left on the stack
+        assert_eq!(px(&fb, 62, 2), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn path_clip_edges_are_anti_aliased() {
+        let fb = render(|r| {
+            r.push_clip(Path::rect(10.5, 0.0, 20.0, 64.0));
+            r.queue_clear(Color::WHITE);
+        });
+        let edge = px(&fb, 10, 5);
+        assert!(edge[0] > 0 && edge[0] < 255, "{edge:?}");
+        assert_eq!(px(&fb, 11, 5), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn translucent_path_fills_blend_with_the_backdrop() {
+        let half_red = Color::RED.with_alpha(128);
+        let fb = render(|r| {
+            r.queue_clear(Color::BLUE);
+            r.queue_fill_path(Path::rect(8.0, 8.0, 16.5, 16.0), half_red);
+        });
+
+        let blended = half_red.over(Color::BLUE).into_rgba();
+        assert_eq!(blended[3], 255);
+        assert_eq!(px(&fb, 16, 16), blended);
+        // the half covered edge lands between the blend and the backdrop
+        let edge = px(&fb, 24, 16);
+        assert_eq!(edge[3], 255);
+        assert!(edge[0] > 0 && edge[0] < blended[0], "{edge:?}");
+        assert_eq!(px(&fb, 40, 16), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn luminance_and_alpha_masks() {
+        let mut layer = Frame::new(2, 1);
+        layer
+            .as_bytes_mut()
+            .copy_from_slice(&[255, 255, 255, 255, 0, 0, 0, 255]);
+        let layer = Arc::new(layer);
+
+        let lum = render(|r| {
+            r.push_mask(Mask::new(Arc::clone(&layer), 0, 0, MaskMode::Luminance));
+            r.queue_clear(Color::WHITE);
+        });
+        assert_eq!(px(&lum, 0, 0), [255, 255, 255, 255]);
+        assert_eq!(px(&lum, 1, 0), [0, 0, 0, 255]);
+        // outside of the mask layer is hidden
+        assert_eq!(px(&lum, 5, 5), [0, 0, 0, 255]);
+
+        let alpha = render(|r| {
+            r.push_mask(Mask::new(Arc::clone(&layer), 0, 0, MaskMode::Alpha));
+            r.queue_clear(Color::WHITE);
+        });
+        assert_eq!(px(&alpha, 1, 0), [255, 255, 255, 255]);
+    }
+}
@@ -0,0 +1,309 @@
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use crate::{
+    color::model::Color,
+    renderer::{
+        clip::{self, ClipChain},
+        paint::Paint,
+        path::Path,
+        sample::{self, Filter, Texture},
+        transform::Transform2D,
+    },
+};
+
+/// A half-open pixel rectangle (`x0..x1` by `y0..y1`) in device space.
+///
+/// # Fields
+///
+/// - `x0` (`u16`) - The left edge (inclusive).
+/// - `y0` (`u16`) - The top edge (inclusive).
+/// - `x1` (`u16`) - The right edge (exclusive).
+/// - `y1` (`u16`) - The bottom edge (exclusive).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PixelRect {
+    pub x0: u16,
+    pub y0: u16,
+    pub x1: u16,
+    pub y1: u16,
+}
+
+impl PixelRect {
+    pub const EMPTY: Self = Self::new(0, 0, 0, 0);
+
+    /// Create a rect from its edges.
+    #[must_use]
+    #[inline]
+    pub const fn new(x0: u16, y0: u16, x1: u16, y1: u16) -> Self {
+        Self { x0, y0, x1, y1 }
+    }
+
+    /// Create a rect from a position and a size, saturating at the edge of device space.
+    #[must_use]
+    #[inline]
+    pub const fn from_xywh(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Self::new(x, y, x.saturating_add(width), y.saturating_add(height))
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn width(&self) -> u16 {
+        self.x1.saturating_sub(self.x0)
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn height(&self) -> u16 {
+        self.y1.saturating_sub(self.y0)
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.x1 <= self.x0 || self.y1 <= self.y0
+    }
+
+    /// Get the overlapping area of two rects (empty if they don't touch).
+    #[must_use]
+    #[inline]
+    pub fn intersect(&self, other: PixelRect) -> PixelRect {
+        let r = PixelRect::new(
+            self.x0.max(other.x0),
+            self.y0.max(other.y0),
+            self.x1.min(other.x1),
+            self.y1.min(other.y1),
+        );
+        if r.is_empty() { PixelRect::EMPTY } else { r }
+    }
+
+    /// Check whether two rects share at least one pixel.
+    #[must_use]
+    #[inline]
+    pub fn overlaps(&self, other: PixelRect) -> bool {
+        !self.intersect(other).is_empty()
+    }
+}
+
+/// A single recorded draw operation.
+///
+/// Commands are recorded between `Renderer::begin_frame` and `Renderer::end_frame`
+/// and rasterized afterwards, which lets the renderer bin them into tiles. `Clear` replaces
+/// what's in the target, everything else composites source-over it.
+///
+/// # Variants
+///
+/// - `Clear` - Fill the whole target with a paint.
+/// - `Pixel` - Write one pixel.
+/// - `HSpan` - Fill one row from `x0` to `x1` (exclusive, in either order).
+/// - `Rect` - Fill a rectangle.
+/// - `FillPath` - Fill an anti-aliased path.
+/// - `Image` - Composite a texture through a transform (image pixels to device pixels),
+///   resampled with a filter.
+/// - `Clipped` - Draw another command through the clips that were active when it was queued.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCmd {
+    Clear(Paint),
+    Pixel {
+        x: u16,
+        y: u16,
+        paint: Paint,
+    },
+    HSpan {
+        y: u16,
+        x0: u16,
+        x1: u16,
+        paint: Paint,
+    },
+    Rect {
+        rect: PixelRect,
+        paint: Paint,
+    },
+    FillPath {
+        path: Arc<Path>,
+        paint: Paint,
+    },
+    Image {
+        image: Arc<Texture>,
+        transform: Transform2D,
+        filter: Filter,
+    },
+    Clipped {
+        cmd: Box<DrawCmd>,
+        clip: Arc<ClipChain>,
+    },
+}
+
+impl DrawCmd {
+    /// The device space area a command can touch. `None` means the whole target.
+    ///
+    /// Used to bin commands into tiles, so it must never be smaller than what
+    /// `DrawCmd::rasterize` actually writes.
+    #[must_use]
+    #[inline]
+    pub fn bounds(&self) -> Option<PixelRect> {
+        use DrawCmd::*;
+        match *self {
+            Clear(_) => None,
+            Pixel { x, y, .. } => Some(PixelRect::from_xywh(x, y, 1, 1)),
+            HSpan { y, x0, x1, .. } => Some(PixelRect::new(
+                x0.min(x1),
+                y,
+                x0.max(x1),
+                y.saturating_add(1),
+            )),
+            Rect { rect, .. } => Some(rect),
+            FillPath { ref path, .. } => Some(path.pixel_bounds().unwrap_or(PixelRect::EMPTY)),
+            Image {
+                ref image,
+                ref transform,
+                filter,
+            } => Some(sample::transformed_bounds(image, transform, filter)),
+            Clipped { ref cmd, ref clip } => Some(match cmd.bounds() {
+                Some(b) => b.intersect(clip.bounds()),
+                None => clip.bounds(),
+            }),
+        }
+    }
+
+    /// Rasterize the command into a region of a frame.
+    ///
+    /// `data` holds the RGBA pixels of `area` only (row stride is `area.width() * 4`),
+    /// so the same code renders both whole frames and single tiles.
+    pub fn rasterize(&self, area: PixelRect, data: &mut [u8]) {
+        let covered = match self.bounds() {
+            Some(b) => b.intersect(area),
+            None => area,
+        };
+        if covered.is_empty() {
+            return;
+        }
+
+        let paint = match self {
+            DrawCmd::Clear(p) => p,
+            DrawCmd::Pixel { paint, .. }
+            | DrawCmd::HSpan { paint, .. }
+            | DrawCmd::Rect { paint, .. } => paint,
+            DrawCmd::FillPath { path, paint } => {
+                let before = copy_out(area, data, covered);
+                let mut after = before.clone();
+                shade_rect_over(covered, &mut after, covered, paint);
+                clip::apply_coverage(&before, &mut after, &path.coverage(covered));
+                copy_in(area, data, covered, &after);
+                return;
+            }
+            DrawCmd::Image {
+                image,
+                transform,
+                filter,
+            } => {
+                sample::draw_transformed(area, data, covered, image, transform, *filter);
+                return;
+            }
+            DrawCmd::Clipped { cmd, clip } => {
+                let before = copy_out(area, data, covered);
+                let mut after = before.clone();
+                cmd.rasterize(covered, &mut after);
+                if let Some(cov) = clip.coverage(covered) {
+                    clip::apply_coverage(&before, &mut after, &cov);
+                }
+                copy_in(area, data, covered, &after);
+                return;
+            }
+        };
+        // the rest are fills of their clipped bounds. a clear replaces what's there, the
+        // others go source-over it (opaque solid fills can still just copy)
+        match (self, paint.as_solid()) {
+            (DrawCmd::Clear(_), Some(color)) => fill_rect(area, data, covered, color.into_rgba()),
+            (DrawCmd::Clear(_), None) => shade_rect(area, data, covered, paint),
+            (_, Some(color)) if color.into_rgba()[3] == 255 => {
+                fill_rect(area, data, covered, color.into_rgba())
+            }
+            _ => shade_rect_over(area, data, covered, paint),
+        }
+    }
+}
+
+/// Copy the pixels of `sub` out of `data` (which holds `area`) into a tightly packed buffer.
+pub(crate) fn copy_out(area: PixelRect, data: &[u8], sub: PixelRect) -> Vec<u8> {
+    let stride = area.width() as usize * 4;
+    let row_len = sub.width() as usize * 4;
+
+    let mut out = Vec::with_capacity(row_len * sub.height() as usize);
+    for y in sub.y0..sub.y1 {
+        let start = (y - area.y0) as usize * stride + (sub.x0 - area.x0) as usize * 4;
+        out.extend_from_slice(&data[start..start + row_len]);
+    }
+    out
+}
+
+/// Copy a tightly packed buffer of `sub` back into `data` (which holds `area`).
+pub(crate) fn copy_in(area: PixelRect, data: &mut [u8], sub: PixelRect, src: &[u8]) {
+    let stride = area.width() as usize * 4;
+    let row_len = sub.width() as usize * 4;
+    if row_len == 0 {
+        return;
+    }
+
+    for (row, px) in src.chunks_exact(row_len).enumerate() {
+        let start =
+            (sub.y0 as usize - area.y0 as usize + row) * stride + (sub.x0 - area.x0) as usize * 4;
+        data[start..start + row_len].copy_from_slice(px);
+    }
+}
+
+/// Fill `rect` (already clipped to `area`) with a packed color.
+#[inline]
+fn fill_rect(area: PixelRect, data: &mut [u8], rect: PixelRect, packed: [u8; 4]) {
+    let stride = area.width() as usize * 4;
+    let x0 = (rect.x0 - area.x0) as usize * 4;
+    let x1 = (rect.x1 - area.x0) as usize * 4;
+
+    for y in rect.y0..rect.y1 {
+        let row = (y - area.y0) as usize * stride;
+        for chunk in data[row + x0..row + x1].chunks_exact_mut(4) {
+            chunk.copy_from_slice(&packed);
+        }
+    }
+}
+
+/// Fill `rect` (already clipped to `area`) pixel by pixel from a paint.
+fn shade_rect(area: PixelRect, data: &mut [u8], rect: PixelRect, paint: &Paint) {
+    let stride = area.width() as usize * 4;
+
+    for y in rect.y0..rect.y1 {
+        let row = (y - area.y0) as usize * stride;
+        for x in rect.x0..rect.x1 {
+            if let Some(px) = paint.shade(x, y) {
+                let i = row + (x - area.x0) as usize * 4;
+                data[i..i + 4].copy_from_slice(&px);
+            }
+        }
+    }
+}
+
+/// Composite a paint source-over `rect` (already clipped to `area`), in linear space.
+fn shade_rect_over(area: PixelRect, data: &mut [u8], rect: PixelRect, paint: &Paint) {
+    let stride = area.width() as usize * 4;
+
+    for y in rect.y0..rect.y1 {
+        let row = (y - area.y0) as usize * stride;
+        for x in rect.x0..rect.x1 {
+            let Some(src) = paint.shade(x, y) else {
+                continue;
+            };
+            let i = row + (x - area.x0) as usize * 4;
+            let px = &mut data[i..i + 4];
+
+            // a == 0 skip, a == 255 copy, everything else blends
+            match src[3] {
+                0 => {}
+                255 => px.copy_from_slice(&src),
+                _ => {
+                    let bg = Color::from_rgba([px[0], px[1], px[2], px[3]]);
+                    px.copy_from_slice(&Color::from_rgba(src).over(bg).into_rgba());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,579 @@
+#![allow(dead_code)]
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::{
+    color::{ColorFloat, model::Color},
+    renderer::{frame::Frame, gradient},
+};
+
+/// How `quantize` hides the steps between palette colors.
+///
+/// # Variants
+///
+/// - `None` - Every pixel snaps to its nearest palette color. Smooth areas band.
+/// - `Bayer` - Ordered 8x8 Bayer pattern. Stable from frame to frame, so it doesn't
+///   shimmer in video, but the cross-hatch is visible.
+/// - `BlueNoise` - Ordered 64x64 blue noise. Also stable, and reads as fine grain
+///   instead of a pattern.
+/// - `FloydSteinberg` - Error diffusion to four neighbors. The most accurate, but the
+///   pattern can change completely when a single pixel does.
+/// - `Atkinson` - Error diffusion that only passes on 3/4 of the error. Crisper, with
+///   blown out highlights and shadows, like early Macs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Dither {
+    None,
+    Bayer,
+    #[default]
+    BlueNoise,
+    FloydSteinberg,
+    Atkinson,
+}
+
+/// A fixed set of colors to quantize frames to, searched in OKLab.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::renderer::{
+///     dither::{Dither, Palette, quantize},
+///     frame::Frame,
+/// };
+///
+/// // a 256 color GIF frame
+/// let mut frame = Frame::new(320, 180);
+/// let palette = Palette::from_frame(&frame, 256);
+/// quantize(&mut frame, &palette, Dither::FloydSteinberg);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    colors: Vec<Color>,
+    lab: Vec<[ColorFloat; 3]>,
+}
+
+impl Palette {
+    /// A palette of the given colors (alpha is ignored).
+    #[must_use]
+    pub fn new(colors: &[Color]) -> Self {
+        let colors: Vec<Color> = colors.iter().map(|c| c.with_alpha(255)).collect();
+        let lab = colors.iter().map(|c| c.into_oklab()).collect();
+        Self { colors, lab }
+    }
+
+    /// Pick up to `n` colors that represent a frame well.
+    ///
+    /// The frame's colors are split with median cut in OKLab, then refined with a few
+    /// rounds of k-means so the palette sits where the pixels actually are.
+    /// Transparent pixels don't count.
+    #[must_use]
+    pub fn from_frame(fb: &Frame, n: usize) -> Self {
+        let mut counts: HashMap<[u8; 3], u32> = HashMap::new();
+        for p in fb.as_slice().chunks_exact(4).filter(|p| p[3] > 0) {
+            *counts.entry([p[0], p[1], p[2]]).or_default() += 1;
+        }
+        let mut entries: Vec<([ColorFloat; 3], ColorFloat)> = counts
+            .into_iter()
+            .map(|(rgb, n)| (Color::from_rgb(rgb).into_oklab(), n as ColorFloat))
+            .collect();
+        // hash map order isn't stable, the palette should be
+        entries.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        let centers = median_cut(&mut entries, n);
+        let centers = kmeans(&entries, centers, 4);
+        let colors: Vec<Color> = centers.into_iter().map(Color::from_oklab).collect();
+        Self::new(&colors)
+    }
+
+    #[must_use]
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// The index of the palette color closest to `color` in OKLab (alpha is ignored).
+    /// `None` for an empty palette.
+    #[must_use]
+    pub fn nearest(&self, color: Color) -> Option<usize> {
+        self.nearest_lab(color.into_oklab())
+    }
+
+    /// The average OKLab distance from each color to its closest neighbor, how far apart
+    /// the palette's colors are. An ordered dither has to reach about this far.
+    fn spacing(&self) -> ColorFloat {
+        let n = self.lab.len();
+        if n < 2 {
+            return 0.0;
+        }
+        let total: ColorFloat = self
+            .lab
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                self.lab
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .map(|(_, q)| dist(p, q))
+                    .fold(ColorFloat::MAX, ColorFloat::min)
+                    .sqrt()
+            })
+            .sum();
+        total / n as ColorFloat
+    }
+
+    fn nearest_lab(&self, lab: [ColorFloat; 3]) -> Option<usize> {
+        nearest(&self.lab, lab)
+    }
+}
+
+/// Snap every pixel of a frame to a palette color, dithering the difference.
+///
+/// Ordered dithers nudge OKLab lightness by up to the spacing between palette colors,
+/// so an area keeps its perceived lightness.
+/// Error diffusion works in linear light, so the average brightness of an area is kept.
+/// Alpha isn't touched and fully transparent pixels are skipped.
+///
+/// # Arguments
+///
+/// - `fb` (`&mut Frame`) - The frame to quantize in place.
+/// - `palette` (`&Palette`) - The colors to use. Nothing happens if it's empty.
+/// - `dither` (`Dither`) - How to dither.
+pub fn quantize(fb: &mut Frame, palette: &Palette, dither: Dither) {
+    if palette.is_empty() {
+        return;
+    }
+    match dither {
+        Dither::None => quantize_ordered(fb, palette, |_, _| 0.0),
+        Dither::Bayer => quantize_ordered(fb, palette, gradient::bayer8),
+        Dither::BlueNoise => quantize_ordered(fb, palette, blue_noise),
+        Dither::FloydSteinberg => diffuse(fb, palette, FLOYD_STEINBERG, 16.0),
+        Dither::Atkinson => diffuse(fb, palette, ATKINSON, 8.0),
+    }
+}
+
+fn quantize_ordered(fb: &mut Frame, palette: &Palette, bias: impl Fn(u16, u16) -> ColorFloat) {
+    let spread = palette.spacing();
+    let w = fb.width() as usize;
+    let mut labs: HashMap<[u8; 3], [ColorFloat; 3]> = HashMap::new();
+
+    for (i, px) in fb.as_bytes_mut().chunks_exact_mut(4).enumerate() {
+        if px[3] == 0 {
+            continue;
+        }
+        let [l, a, b] = *labs
+            .entry([px[0], px[1], px[2]])
+            .or_insert_with(|| Color::from_rgb([px[0], px[1], px[2]]).into_oklab());
+        // only lightness is pushed around, it's what the eye resolves best
+        let l = l + bias((i % w) as u16, (i / w) as u16) * spread;
+
+        let k = palette.nearest_lab([l, a, b]).unwrap();
+        px[..3].copy_from_slice(&palette.colors[k].into_rgb());
+    }
+}
+
+// (dx, dy, weight), dx is flipped on right to left rows
+const FLOYD_STEINBERG: &[(i32, i32, ColorFloat)] =
+    &[(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)];
+const ATKINSON: &[(i32, i32, ColorFloat)] = &[
+    (1, 0, 1.0),
+    (2, 0, 1.0),
+    (-1, 1, 1.0),
+    (0, 1, 1.0),
+    (1, 1, 1.0),
+    (0, 2, 1.0),
+];
+
+fn diffuse(
+    fb: &mut Frame,
+    palette: &Palette,
+    kernel: &[(i32, i32, ColorFloat)],
+    divisor: ColorFloat,
+) {
+    let (w, h) = (fb.width() as i32, fb.height() as i32);
+    let pal_lin: Vec<[ColorFloat; 3]> = palette
+        .colors
+        .iter()
+        .map(|c| {
+            let [r, g, b, _] = c.into_linear();
+            [r, g, b]
+        })
+        .collect();
+
+    let data = fb.as_bytes_mut();
+    let mut err = vec![[0.0; 3]; (w * h) as usize];
+    for y in 0..h {
+        // serpentine, so the error doesn't pile up toward one side
+        let flip = y % 2 == 1;
+        for i in 0..w {
+            let x = if flip { w - 1 - i } else { i };
+            let at = (y * w + x) as usize;
+            let px = &mut data[at * 4..at * 4 + 4];
+            if px[3] == 0 {
+                continue;
+            }
+
+            let [r, g, b, _] = Color::from_rgba([px[0], px[1], px[2], px[3]]).into_linear();
+            let e = err[at];
+            // carried error may overshoot the gamut and has to be kept to average out,
+            // but a palette that can't reach a color shouldn't pile it up forever
+            let want = [r + e[0], g + e[1], b + e[2]].map(|c| c.clamp(-0.5, 1.5));
+            let k = palette
+                .nearest_lab(Color::linear_to_oklab(want.map(|c| c.clamp(0.0, 1.0))))
+                .unwrap();
+            px[..3].copy_from_slice(&palette.colors[k].into_rgb());
+
+            let got = pal_lin[k];
+            let diff = [want[0] - got[0], want[1] - got[1], want[2] - got[2]];
+            for &(dx, dy, weight) in kernel {
+                let (nx, ny) = (if flip { x - dx } else { x + dx }, y + dy);
+                if nx < 0 || nx >= w || ny >= h {
+                    continue;
+                }
+                let n = &mut err[(ny * w + nx) as usize];
+                for c in 0..3 {
+                    n[c] += diff[c] * weight / divisor;
+                }
+            }
+        }
+    }
+}
+
+/// Squared OKLab distance.
+#[inline]
+fn dist(p: &[ColorFloat; 3], q: &[ColorFloat; 3]) -> ColorFloat {
+    (p[0] - q[0]).powi(2) + (p[1] - q[1]).powi(2) + (p[2] - q[2]).powi(2)
+}
+
+#[inline]
+fn nearest(points: &[[ColorFloat; 3]], lab: [ColorFloat; 3]) -> Option<usize> {
+    (0..points.len()).min_by(|&a, &b| dist(&points[a], &lab).total_cmp(&dist(&points[b], &lab)))
+}
+
+/// Split the colors into `n` boxes, always cutting the box with the widest spread at its
+/// weighted median. Returns each box's weighted mean.
+fn median_cut(entries: &mut [([ColorFloat; 3], ColorFloat)], n: usize) -> Vec<[ColorFloat; 3]> {
+    if entries.is_empty() || n == 0 {
+        return Vec::new();
+    }
+
+    let range = |es: &[([ColorFloat; 3], ColorFloat)]| {
+        let mut best = (0, 0.0);
+        for axis in 0..3 {
+            let (lo, hi) = es
+                .iter()
+                .fold((ColorFloat::MAX, ColorFloat::MIN), |(lo, hi), e| {
+                    (lo.min(e.0[axis]), hi.max(e.0[axis]))
+                });
+            if hi - lo > best.1 {
+                best = (axis, hi - lo);
+            }
+        }
+        best
+    };
+
+    let mut boxes = vec![(0, entries.len())];
+    while boxes.len() < n {
+        // biggest spread weighted by how many pixels would benefit
+        let pick = boxes
+            .iter()
+            .enumerate()
+            .filter(|&(_, &(s, e))| e - s > 1)
+            .map(|(i, &(s, e))| {
+                let weight: ColorFloat = entries[s..e].iter().map(|e| e.1).sum();
+                (i, range(&entries[s..e]).1 * weight.sqrt())
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((i, spread)) = pick else { break };
+        if spread <= 0.0 {
+            break;
+        }
+
+        let (s, e) = boxes[i];
+        let axis = range(&entries[s..e]).0;
+        entries[s..e].sort_by(|a, b| a.0[axis].total_cmp(&b.0[axis]));
+        let total: ColorFloat = entries[s..e].iter().map(|e| e.1).sum();
+        let mut acc = 0.0;
+        let mut cut = s + 1;
+        for (j, entry) in entries[s..e].iter().enumerate() {
+            acc += entry.1;
+            if acc >= total / 2.0 {
+                cut = s + j + 1;
+                break;
+            }
+        }
+        // both halves keep at least one color
+        let cut = cut.clamp(s + 1, e - 1);
+        boxes[i] = (s, cut);
+        boxes.push((cut, e));
+    }
+
+    boxes
+        .into_iter()
+        .map(|(s, e)| mean(&entries[s..e]))
+        .collect()
+}
+
+fn mean(entries: &[([ColorFloat; 3], ColorFloat)]) -> [ColorFloat; 3] {
+    let mut sum = [0.0; 3];
+    let mut weight = 0.0;
+    for (lab, w) in entries {
+        for (s, l) in sum.iter_mut().zip(lab) {
+            *s += l * w;
+        }
+        weight += w;
+    }
+    sum.map(|s| s / weight.max(ColorFloat::MIN_POSITIVE))
+}
+
+/// Lloyd iterations: move every center to the mean of the colors closest to it.
+fn kmeans(
+    entries: &[([ColorFloat; 3], ColorFloat)],
+    mut centers: Vec<[ColorFloat; 3]>,
+    rounds: usize,
+) -> Vec<[ColorFloat; 3]> {
+    for _ in 0..rounds {
+        let mut sums = vec![([0.0; 3], 0.0); centers.len()];
+        for (lab, w) in entries {
+            let Some(k) = nearest(&centers, *lab) else {
+                return centers;
+            };
+            for (s, l) in sums[k].0.iter_mut().zip(lab) {
+                *s += l * w;
+            }
+            sums[k].1 += w;
+        }
+        for (center, (sum, w)) in centers.iter_mut().zip(sums) {
+            // a center nobody picked stays put
+            if w > 0.0 {
+                *center = sum.map(|s| s / w);
+            }
+        }
+    }
+    centers
+}
+
+const BLUE_NOISE_SIZE: usize = 64;
+
+/// An ordered dither offset in -0.5..0.5 from the blue noise tile.
+#[inline]
+fn blue_noise(x: u16, y: u16) -> ColorFloat {
+    let ranks = BLUE_NOISE.get_or_init(build_blue_noise);
+    let i = (y as usize % BLUE_NOISE_SIZE) * BLUE_NOISE_SIZE + x as usize % BLUE_NOISE_SIZE;
+    (ranks[i] as ColorFloat + 0.5) / (BLUE_NOISE_SIZE * BLUE_NOISE_SIZE) as ColorFloat - 0.5
+}
+
+static BLUE_NOISE: OnceLock<Vec<u16>> = OnceLock::new();
+
+/// Robert Ulichney's void-and-cluster, on a wrapping 64x64 tile.
+/// Every pixel gets a rank, and any threshold of the ranks is evenly spread out.
+fn build_blue_noise() -> Vec<u16> {
+    const N: usize = BLUE_NOISE_SIZE;
+    const SIGMA: f32 = 1.5;
+
+    // gaussian falloff for every wrapped offset
+    let kernel: Vec<f32> = (0..N * N)
+        .map(|i| {
+            let wrap = |d: usize| d.min(N - d) as f32;
+            let (dx, dy) = (wrap(i % N), wrap(i / N));
+            (-(dx * dx + dy * dy) / (2.0 * SIGMA * SIGMA)).exp()
+        })
+        .collect();
+    let splat = |energy: &mut [f32], at: usize, sign: f32| {
+        let (ax, ay) = (at % N, at / N);
+        for (i, e) in energy.iter_mut().enumerate() {
+            let (dx, dy) = ((i % N + N - ax) % N, (i / N + N - ay) % N);
+            *e += sign * kernel[dy * N + dx];
+        }
+    };
+    let tightest = |on: &[bool], energy: &[f32], want: bool| {
+        let mut best = None;
+        for i in (0..N * N).filter(|&i| on[i] == want) {
+            let better = match best {
+                None => true,
+                // clusters are where ones crowd, voids where they're missing
+                Some(b) => (want && energy[i] > energy[b]) || (!want && energy[i] < energy[b]),
+            };
+            if better {
+                best = Some(i);
+            }
+        }
+        best.unwrap()
+    };
+
+    // a random starting pattern, a tenth of the pixels on
+    let mut state = 0x9e37_79b9u32;
+    let mut on = vec![false; N * N];
+    let mut energy = vec![0.0; N * N];
+    let mut ones = 0;
+    while ones < N * N / 10 {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        let i = state as usize % (N * N);
+        if !on[i] {
+            on[i] = true;
+            splat(&mut energy, i, 1.0);
+            ones += 1;
+        }
+    }
+
+    // even it out: move the most crowded one into the emptiest void until that's a no-op
+    loop {
+        let cluster = tightest(&on, &energy, true);
+        on[cluster] = false;
+        splat(&mut energy, cluster, -1.0);
+        let void = tightest(&on, &energy, false);
+        on[void] = true;
+        splat(&mut energy, void, 1.0);
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut ranks = vec![0u16; N * N];
+    // ranks below the starting pattern: take ones out, most crowded first
+    let (mut on1, mut energy1) = (on.clone(), energy.clone());
+    for rank in (0..ones).rev() {
+        let cluster = tightest(&on1, &energy1, true);
+        on1[cluster] = false;
+        splat(&mut energy1, cluster, -1.0);
+        ranks[cluster] = rank as u16;
+    }
+    // ranks above it: fill the emptiest void each time
+    for rank in ones..N * N {
+        let void = tightest(&on, &energy, false);
+        on[void] = true;
+        splat(&mut energy, void, 1.0);
+        ranks[void] = rank as u16;
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flat(w: u16, h: u16, c: Color) -> Frame {
+        let mut fb = Frame::new(w, h);
+        for px in fb.as_bytes_mut().chunks_exact_mut(4) {
+            px.copy_from_slice(&c.into_rgba());
+        }
+        fb
+    }
+
+    #[test]
+    fn blue_noise_ranks_are_a_spread_out_permutation() {
+        let ranks = BLUE_NOISE.get_or_init(build_blue_noise);
+        let mut seen = vec![false; ranks.len()];
+        for &r in ranks {
+            assert!(!seen[r as usize]);
+            seen[r as usize] = true;
+        }
+
+        // the darkest 1/16 of thresholds never touch each other
+        let n = BLUE_NOISE_SIZE;
+        let low = |x: usize, y: usize| (ranks[(y % n) * n + x % n] as usize) < n * n / 16;
+        for y in 0..n {
+            for x in 0..n {
+                if low(x, y) {
+                    assert!(!low(x + 1, y) && !low(x, y + 1), "clump at {x},{y}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dithering_keeps_the_average() {
+        let bw = Palette::new(&[Color::BLACK, Color::WHITE]);
+        let grey = Color::new(128, 128, 128, 255);
+
+        let mut plain = flat(64, 64, grey);
+        quantize(&mut plain, &bw, Dither::None);
+        assert!(
+            plain
+                .as_slice()
+                .chunks_exact(4)
+                .all(|p| p[0] == plain.as_slice()[0])
+        );
+
+        let white_share = |fb: &Frame| {
+            fb.as_slice()
+                .chunks_exact(4)
+                .filter(|p| p[0] == 255)
+                .count() as f32
+                / (64.0 * 64.0)
+        };
+        // ordered dithering keeps OKLab lightness, 128 is about 0.6
+        let l: f32 = grey.into_oklab()[0] as _;
+        for dither in [Dither::Bayer, Dither::BlueNoise] {
+            let mut fb = flat(64, 64, grey);
+            quantize(&mut fb, &bw, dither);
+            assert!(
+                (white_share(&fb) - l).abs() < 0.02,
+                "{dither:?}: {}",
+                white_share(&fb)
+            );
+        }
+        // error diffusion keeps the light, 128 is about 21.6% in linear
+        let mut fb = flat(64, 64, grey);
+        quantize(&mut fb, &bw, Dither::FloydSteinberg);
+        assert!(
+            (white_share(&fb) - 0.216).abs() < 0.01,
+            "{}",
+            white_share(&fb)
+        );
+        // atkinson drops a quarter of the error, so it only gets close
+        let mut fb = flat(64, 64, grey);
+        quantize(&mut fb, &bw, Dither::Atkinson);
+        assert!(
+            (white_share(&fb) - 0.216).abs() < 0.08,
+            "{}",
+            white_share(&fb)
+        );
+    }
+
+    #[test]
+    fn palettes_find_the_frames_colors() {
+        let colors = [
+            Color::new(30, 30, 46, 255),
+            Color::new(243, 139, 168, 255),
+            Color::new(166, 227, 161, 255),
+            Color::new(137, 180, 250, 255),
+        ];
+        let mut fb = Frame::new(8, 8);
+        for (i, px) in fb.as_bytes_mut().chunks_exact_mut(4).enumerate() {
+            px.copy_from_slice(&colors[(i * 7 / 3) % 4].into_rgba());
+        }
+        fb.as_bytes_mut()[..4].copy_from_slice(&[255, 255, 255, 0]);
+
+        let palette = Palette::from_frame(&fb, 4);
+        assert_eq!(palette.len(), 4);
+        for c in colors {
+            let found = palette.colors()[palette.nearest(c).unwrap()];
+            assert!(
+                found
+                    .into_rgb()
+                    .iter()
+                    .zip(c.into_rgb())
+                    .all(|(a, b)| a.abs_diff(b) <= 1)
+            );
+        }
+
+        let before = fb.as_slice().to_vec();
+        quantize(&mut fb, &palette, Dither::FloydSteinberg);
+        // already on the palette, so nothing moves, and transparent pixels are left alone
+        assert_eq!(&fb.as_slice()[..4], &[255, 255, 255, 0]);
+        for (a, b) in fb.as_slice().iter().zip(&before).skip(4) {
+            assert!(a.abs_diff(*b) <= 1);
+        }
+    }
+}
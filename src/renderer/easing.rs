@@ -0,0 +1,48 @@
+#![allow(dead_code)]
+
+/// How an animation moves between two keyframes.
+///
+/// # Variants
+///
+/// - `Linear` - Constant speed.
+/// - `EaseIn` - Starts slow (cubic).
+/// - `EaseOut` - Ends slow (cubic).
+/// - `EaseInOut` - Starts and ends slow (cubic), the default for camera moves.
+/// - `Hold` - Stays on the first keyframe until the next one is reached.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+    Hold,
+}
+
+impl Easing {
+    /// Map linear progress `t` (clamped to 0.0-1.0) to eased progress.
+    #[must_use]
+    #[inline]
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::Hold => {
+                if t >= 1.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
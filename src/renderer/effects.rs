@@ -0,0 +1,484 @@
+#![allow(dead_code)]
+
+use crate::{
+    color::{ColorFloat, model::Color},
+    renderer::frame::Frame,
+};
+
+/// The shape of a shadow or glow.
+///
+/// # Fields
+///
+/// - `offset` (`[ColorFloat; 2]`) - How far the shadow is moved from its node, in pixels
+///   (rounded to whole pixels). Ignored by `Effect::OuterGlow`.
+/// - `radius` (`ColorFloat`) - The blur radius. Like CSS `box-shadow`, the Gaussian
+///   standard deviation is half of this.
+/// - `spread` (`ColorFloat`) - How far the shape is grown before blurring, in pixels
+///   (rounded to whole pixels).
+/// - `color` (`Color`) - The shadow color. Its alpha scales the whole shadow.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shadow {
+    pub offset: [ColorFloat; 2],
+    pub radius: ColorFloat,
+    pub spread: ColorFloat,
+    pub color: Color,
+}
+
+impl Shadow {
+    #[must_use]
+    #[inline]
+    pub const fn new(
+        offset: [ColorFloat; 2],
+        radius: ColorFloat,
+        spread: ColorFloat,
+        color: Color,
+    ) -> Self {
+        Self {
+            offset,
+            radius,
+            spread,
+            color,
+        }
+    }
+
+    #[inline]
+    fn sigma(&self) -> ColorFloat {
+        self.radius.max(0.0) / 2.0
+    }
+
+    #[inline]
+    fn offset_px(&self) -> [i32; 2] {
+        [self.offset[0].round() as i32, self.offset[1].round() as i32]
+    }
+
+    #[inline]
+    fn spread_px(&self) -> u16 {
+        self.spread.max(0.0).round().min(u16::MAX as ColorFloat) as u16
+    }
+
+    /// How many pixels the effect can reach past its node's edges.
+    fn reach(&self, use_offset: bool) -> u32 {
+        let [dx, dy] = if use_offset { self.offset_px() } else { [0, 0] };
+        (3.0 * self.sigma()).ceil() as u32
+            + self.spread_px() as u32
+            + dx.unsigned_abs().max(dy.unsigned_abs())
+    }
+}
+
+/// An effect drawn around or inside a scene node.
+///
+/// Effects work from the node's rendered layer (its alpha is the shape)
+/// and are applied with `apply_effects`.
+///
+/// # Variants
+///
+/// - `DropShadow` - A blurred, offset copy of the shape drawn behind it.
+/// - `OuterGlow` - A blurred copy of the shape drawn behind it, centered on the node.
+/// - `InnerShadow` - A blurred shadow cast onto the inside of the shape from its edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Effect {
+    DropShadow(Shadow),
+    OuterGlow(Shadow),
+    InnerShadow(Shadow),
+}
+
+impl Effect {
+    /// How far past the node's bounds this effect draws.
+    #[must_use]
+    pub fn margin(&self) -> u32 {
+        match self {
+            Effect::DropShadow(s) => s.reach(true),
+            Effect::OuterGlow(s) => s.reach(false),
+            Effect::InnerShadow(_) => 0,
+        }
+    }
+}
+
+/// Blur a frame with a Gaussian of standard deviation `sigma`.
+///
+/// The blur is approximated with three box passes per axis and runs on
+/// premultiplied linear colors, so transparent neighbors don't leave dark fringes.
+/// Pixels outside the frame count as transparent.
+pub fn gaussian_blur(fb: &mut Frame, sigma: ColorFloat) {
+    if sigma <= 0.0 {
+        return;
+    }
+
+    let (w, h) = (fb.width() as usize, fb.height() as usize);
+    let mut px = to_premul_linear(fb);
+    blur_plane(px.as_flattened_mut(), w, h, 4, sigma);
+    write_premul_linear(fb, &px);
+}
+
+/// Render a node's layer together with its effects.
+///
+/// Returns the composited layer and the margin it was grown by on every side,
+/// so it should be drawn `margin` pixels up and left of where the node sits.
+/// Outer effects are stacked with the first one on top, like CSS `box-shadow`.
+#[must_use]
+pub fn apply_effects(layer: &Frame, effects: &[Effect]) -> (Frame, u16) {
+    let (lw, lh) = (layer.width() as u32, layer.height() as u32);
+    // keep the grown layer addressable with u16 coordinates
+    let room = (u16::MAX as u32 - lw.max(lh)) / 2;
+    let margin = effects
+        .iter()
+        .map(Effect::margin)
+        .max()
+        .unwrap_or(0)
+        .min(room) as usize;
+
+    let (w, h) = (lw as usize + 2 * margin, lh as usize + 2 * margin);
+    let mut out = Frame::new(w as u16, h as u16);
+
+    // node coverage, placed in the middle of the grown layer
+    let content = to_premul_linear(layer);
+    let mut alpha = vec![0.0; w * h];
+    for y in 0..lh as usize {
+        for x in 0..lw as usize {
+            alpha[(y + margin) * w + x + margin] = content[y * lw as usize + x][3];
+        }
+    }
+
+    // everything behind the node
+    let mut behind = vec![[0.0; 4]; w * h];
+    for effect in effects {
+        let (shadow, use_offset) = match effect {
+            Effect::DropShadow(s) => (s, true),
+            Effect::OuterGlow(s) => (s, false),
+            Effect::InnerShadow(_) => continue,
+        };
+
+        let offset = if use_offset {
+            shadow.offset_px()
+        } else {
+            [0, 0]
+        };
+        let mask = shadow_mask(
+            &alpha,
+            w,
+            h,
+            offset,
+            shadow.spread_px(),
+            shadow.sigma(),
+            0.0,
+        );
+        let tint = premul_tint(shadow.color);
+        for (dst, m) in behind.iter_mut().zip(mask) {
+            // the earlier shadows stay on top
+            let k = 1.0 - dst[3];
+            for c in 0..4 {
+                dst[c] += tint[c] * m * k;
+            }
+        }
+    }
+
+    // the node itself
+    let mut px = behind;
+    for y in 0..lh as usize {
+        for x in 0..lw as usize {
+            let src = content[y * lw as usize + x];
+            let dst = &mut px[(y + margin) * w + x + margin];
+            let k = 1.0 - src[3];
+            for c in 0..4 {
+                dst[c] = src[c] + dst[c] * k;
+            }
+        }
+    }
+
+    // inner shadows, cast from everything outside the node and kept inside it
+    for effect in effects {
+        let Effect::InnerShadow(shadow) = effect else {
+            continue;
+        };
+
+        let outside: Vec<ColorFloat> = alpha.iter().map(|a| 1.0 - a).collect();
+        let mask = shadow_mask(
+            &outside,
+            w,
+            h,
+            shadow.offset_px(),
+            shadow.spread_px(),
+            shadow.sigma(),
+            1.0,
+        );
+        let tint = premul_tint(shadow.color);
+        for ((dst, m), a) in px.iter_mut().zip(mask).zip(&alpha) {
+            let m = m * a;
+            let k = 1.0 - tint[3] * m;
+            for c in 0..4 {
+                dst[c] = tint[c] * m + dst[c] * k;
+            }
+        }
+    }
+
+    write_premul_linear(&mut out, &px);
+    (out, margin as u16)
+}
+
+// --- helpers --- //
+
+/// Offset, grow and blur a coverage mask. `outside` is the coverage assumed past the edges.
+fn shadow_mask(
+    src: &[ColorFloat],
+    w: usize,
+    h: usize,
+    offset: [i32; 2],
+    spread: u16,
+    sigma: ColorFloat,
+    outside: ColorFloat,
+) -> Vec<ColorFloat> {
+    // pad so offsetting, growing and blurring near the edges sees `outside` instead of nothing
+    let pad = (3.0 * sigma).ceil() as usize
+        + spread as usize
+        + offset[0].unsigned_abs().max(offset[1].unsigned_abs()) as usize;
+    let (pw, ph) = (w + 2 * pad, h + 2 * pad);
+
+    let mut m = vec![outside; pw * ph];
+    for y in 0..ph {
+        for x in 0..pw {
+            let sx = x as i64 - pad as i64 - offset[0] as i64;
+            let sy = y as i64 - pad as i64 - offset[1] as i64;
+            if sx >= 0 && sy >= 0 && (sx as usize) < w && (sy as usize) < h {
+                m[y * pw + x] = src[sy as usize * w + sx as usize];
+            }
+        }
+    }
+
+    if spread > 0 {
+        dilate(&mut m, pw, ph, spread as usize);
+    }
+    blur_plane(&mut m, pw, ph, 1, sigma);
+
+    let mut out = Vec::with_capacity(w * h);
+    for y in 0..h {
+        out.extend_from_slice(&m[(y + pad) * pw + pad..(y + pad) * pw + pad + w]);
+    }
+    out
+}
+
+/// Grow a mask with a square max filter of radius `r` (run once per axis).
+fn dilate(m: &mut [ColorFloat], w: usize, h: usize, r: usize) {
+    let mut tmp = m.to_vec();
+    for y in 0..h {
+        for x in 0..w {
+            let lo = x.saturating_sub(r);
+            let hi = (x + r).min(w - 1);
+            tmp[y * w + x] = m[y * w + lo..=y * w + hi]
+                .iter()
+                .copied()
+                .fold(0.0, ColorFloat::max);
+        }
+    }
+    for y in 0..h {
+        let lo = y.saturating_sub(r);
+        let hi = (y + r).min(h - 1);
+        for x in 0..w {
+            m[y * w + x] = (lo..=hi)
+                .map(|yy| tmp[yy * w + x])
+                .fold(0.0, ColorFloat::max);
+        }
+    }
+}
+
+/// Box sizes whose three passes approximate a Gaussian.
+///
+/// Source: https://www.peterkovesi.com/papers/FastGaussianSmoothing.pdf
+fn box_radii(sigma: ColorFloat) -> [usize; 3] {
+    const N: ColorFloat = 3.0;
+    let ideal = (12.0 * sigma * sigma / N + 1.0).sqrt();
+    let mut wl = ideal.floor() as i64;
+    if wl % 2 == 0 {
+        wl -= 1;
+    }
+    let wu = wl + 2;
+    let wl_f = wl as ColorFloat;
+    let m_ideal =
+        (12.0 * sigma * sigma - N * wl_f * wl_f - 4.0 * N * wl_f - 3.0 * N) / (-4.0 * wl_f - 4.0);
+    let m = m_ideal.round() as i64;
+
+    let mut radii = [0; 3];
+    for (i, r) in radii.iter_mut().enumerate() {
+        let size = if (i as i64) < m { wl } else { wu };
+        *r = ((size.max(1) - 1) / 2) as usize;
+    }
+    radii
+}
+
+/// Blur an interleaved float image of `ch` channels in place. Outside the image is zero.
+pub(crate) fn blur_plane(
+    data: &mut [ColorFloat],
+    w: usize,
+    h: usize,
+    ch: usize,
+    sigma: ColorFloat,
+) {
+    if sigma <= 0.0 || w == 0 || h == 0 {
+        return;
+    }
+
+    let mut tmp = vec![0.0; data.len()];
+    for r in box_radii(sigma) {
+        if r == 0 {
+            continue;
+        }
+        box_pass(data, &mut tmp, w, h, ch, r, true);
+        box_pass(&tmp, data, w, h, ch, r, false);
+    }
+}
+
+/// One running-sum box filter pass along rows (`horizontal`) or columns.
+fn box_pass(
+    src: &[ColorFloat],
+    dst: &mut [ColorFloat],
+    w: usize,
+    h: usize,
+    ch: usize,
+    r: usize,
+    horizontal: bool,
+) {
+    let (len, lines) = if horizontal { (w, h) } else { (h, w) };
+    let at = |line: usize, i: usize| {
+        if horizontal {
+            (line * w + i) * ch
+        } else {
+            (i * w + line) * ch
+        }
+    };
+    let norm = 1.0 / (2 * r + 1) as ColorFloat;
+
+    let mut sum = vec![0.0; ch];
+    for line in 0..lines {
+        sum.iter_mut().for_each(|s| *s = 0.0);
+        for i in 0..r.min(len) {
+            for c in 0..ch {
+                sum[c] += src[at(line, i) + c];
+            }
+        }
+
+        for i in 0..len {
+            if i + r < len {
+                let add = at(line, i + r);
+                for c in 0..ch {
+                    sum[c] += src[add + c];
+                }
+            }
+            let out = at(line, i);
+            for c in 0..ch {
+                dst[out + c] = sum[c] * norm;
+            }
+            if i >= r {
+                let sub = at(line, i - r);
+                for c in 0..ch {
+                    sum[c] -= src[sub + c];
+                }
+            }
+        }
+    }
+}
+
+#[inline]
+fn premul_tint(c: Color) -> [ColorFloat; 4] {
+    let [r, g, b, a] = c.into_linear();
+    [r * a, g * a, b * a, a]
+}
+
+fn to_premul_linear(fb: &Frame) -> Vec<[ColorFloat; 4]> {
+    fb.as_slice()
+        .chunks_exact(4)
+        .map(|p| premul_tint(Color::from_rgba([p[0], p[1], p[2], p[3]])))
+        .collect()
+}
+
+fn write_premul_linear(fb: &mut Frame, px: &[[ColorFloat; 4]]) {
+    for (dst, p) in fb.as_bytes_mut().chunks_exact_mut(4).zip(px) {
+        let a = p[3];
+        let c = if a > 0.0 {
+            Color::from_linear([p[0] / a, p[1] / a, p[2] / a, a])
+        } else {
+            Color::TRANSPARENT
+        };
+        dst.copy_from_slice(&c.into_rgba());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(size: u16, inset: u16, color: Color) -> Frame {
+        let mut fb = Frame::new(size, size);
+        let w = size as usize;
+        let data = fb.as_bytes_mut();
+        for y in inset..size - inset {
+            for x in inset..size - inset {
+                let i = (y as usize * w + x as usize) * 4;
+                data[i..i + 4].copy_from_slice(&color.into_rgba());
+            }
+        }
+        fb
+    }
+
+    fn alpha_sum(fb: &Frame) -> u64 {
+        fb.as_slice().chunks_exact(4).map(|p| p[3] as u64).sum()
+    }
+
+    #[test]
+    fn blur_keeps_coverage_and_has_no_dark_fringe() {
+        let mut fb = square(64, 24, Color::RED);
+        let before = alpha_sum(&fb);
+        gaussian_blur(&mut fb, 3.0);
+
+        let after = alpha_sum(&fb);
+        assert!(before.abs_diff(after) * 100 < before, "{before} -> {after}");
+        for p in fb.as_slice().chunks_exact(4).filter(|p| p[3] > 8) {
+            // straight alpha red must stay fully red where it fades out
+            assert_eq!(&p[..3], &[255, 0, 0], "fringe pixel {p:?}");
+        }
+    }
+
+    #[test]
+    fn box_radii_approximate_sigma() {
+        for sigma in [0.5, 1.0, 2.5, 8.0, 20.0] {
+            let variance: ColorFloat = box_radii(sigma)
+                .iter()
+                .map(|&r| {
+                    let n = (2 * r + 1) as ColorFloat;
+                    (n * n - 1.0) / 12.0
+                })
+                .sum();
+            assert!(
+                (variance.sqrt() - sigma).abs() < 0.6,
+                "sigma {sigma} got {}",
+                variance.sqrt()
+            );
+        }
+    }
+
+    #[test]
+    fn drop_shadow_is_offset_and_behind() {
+        let layer = square(16, 4, Color::WHITE);
+        let shadow = Shadow::new([6.0, 6.0], 0.0, 0.0, Color::BLACK);
+        let (out, margin) = apply_effects(&layer, &[Effect::DropShadow(shadow)]);
+        assert_eq!(margin, 6);
+
+        let px = |x: u16, y: u16| out.get_pixel(x + margin, y + margin).unwrap().into_rgba();
+        // node stays on top, shadow shows where the node isn't
+        assert_eq!(px(5, 5), [255, 255, 255, 255]);
+        assert_eq!(px(15, 15), [0, 0, 0, 255]);
+        assert_eq!(px(2, 2), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn inner_shadow_stays_inside() {
+        let layer = square(32, 8, Color::WHITE);
+        let shadow = Shadow::new([2.0, 2.0], 4.0, 0.0, Color::BLACK);
+        let (out, margin) = apply_effects(&layer, &[Effect::InnerShadow(shadow)]);
+        assert_eq!(margin, 0);
+
+        assert_eq!(out.get_pixel(4, 4).unwrap().into_rgba()[3], 0);
+        let edge = out.get_pixel(9, 9).unwrap().into_rgba();
+        let center = out.get_pixel(20, 20).unwrap().into_rgba();
+        assert!(edge[0] < center[0]);
+        assert_eq!(center, [255, 255, 255, 255]);
+    }
+}
@@ -0,0 +1,610 @@
+#![allow(dead_code)]
+
+use core::{fmt, ops::Range, time::Duration};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+};
+
+use image::{
+    Delay, ExtendedColorType, ImageEncoder, RgbaImage,
+    codecs::{
+        gif::{GifEncoder, Repeat},
+        png::{CompressionType, FilterType, PngEncoder},
+    },
+};
+
+use crate::renderer::{
+    camera::Camera,
+    dither::{self, Dither, Palette},
+    frame::Frame,
+    pool::FramePool,
+    render::Renderer,
+    scene::Scene,
+};
+
+/// What `export` writes, picked from the output path.
+///
+/// # Variants
+///
+/// - `Video` - `.mp4`, `.mov`, `.mkv` or `.webm`, encoded by piping frames to `ffmpeg`.
+/// - `Gif` - An animated GIF, quantized per frame with `dither::Palette`.
+/// - `PngSequence` - One PNG per frame. The path is a directory, a pattern with a `%d`
+///   style frame number (`out/frame_%04d.png`), or a `.png` name that gets `_%04d` added.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Video,
+    Gif,
+    PngSequence,
+}
+
+impl OutputFormat {
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        if names_dir(path) {
+            return Some(OutputFormat::PngSequence);
+        }
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "mp4" | "mov" | "mkv" | "webm" => Some(OutputFormat::Video),
+            "gif" => Some(OutputFormat::Gif),
+            "png" => Some(OutputFormat::PngSequence),
+            _ => None,
+        }
+    }
+}
+
+/// How `export` renders a scene.
+///
+/// # Fields
+///
+/// - `size` (`Option<[u16; 2]>`) - The output size. The scene is scaled to fit and centered,
+///   `None` keeps the scene's own size.
+/// - `frames` (`Option<Range<u32>>`) - Which frames to render, `None` for all of them.
+/// - `quality` (`u8`) - 0-100. Sets the video CRF, the GIF palette size and the PNG
+///   compression effort.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportOptions {
+    pub size: Option<[u16; 2]>,
+    pub frames: Option<Range<u32>>,
+    pub quality: u8,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            size: None,
+            frames: None,
+            quality: 80,
+        }
+    }
+}
+
+/// An error exporting a scene.
+///
+/// # Variants
+///
+/// - `UnsupportedOutput` - The output path doesn't name a format `OutputFormat` knows.
+/// - `EmptyRange` - The requested frames don't overlap the scene.
+/// - `Io` - Creating or writing an output file failed.
+/// - `Encode` - The image encoder failed.
+/// - `FfmpegMissing` - Video output needs `ffmpeg` on the `PATH`.
+/// - `Ffmpeg` - `ffmpeg` exited with an error, with what it printed.
+#[derive(Debug)]
+pub enum ExportError {
+    UnsupportedOutput(PathBuf),
+    EmptyRange { frames: Range<u32>, count: u32 },
+    Io { path: PathBuf, error: io::Error },
+    Encode(String),
+    FfmpegMissing,
+    Ffmpeg(String),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ExportError::*;
+        match self {
+            UnsupportedOutput(path) => write!(
+                f,
+                "don't know how to write {} (use .mp4, .mov, .mkv, .webm, .gif, .png or a directory)",
+                path.display()
+            ),
+            EmptyRange { frames, count } => write!(
+                f,
+                "frames {}..{} are outside the scene ({count} frames)",
+                frames.start, frames.end
+            ),
+            Io { path, error } => write!(f, "couldn't write {}: {error}", path.display()),
+            Encode(msg) => write!(f, "encoding failed: {msg}"),
+            FfmpegMissing => f.write_str("video output needs ffmpeg, and it isn't on the PATH"),
+            Ffmpeg(msg) => write!(f, "ffmpeg failed: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExportError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> ExportError + '_ {
+    move |error| ExportError::Io {
+        path: path.to_owned(),
+        error,
+    }
+}
+
+/// A camera that fits the scene into an output of a different size (`None` when it's the same).
+pub(crate) fn fit(scene: &Scene, size: Option<[u16; 2]>) -> Option<Camera> {
+    let [w, h] = size?;
+    if [w, h] == [scene.width, scene.height] {
+        return None;
+    }
+    let (sw, sh) = (scene.width as f32, scene.height as f32);
+    let mut cam = Camera::new(sw, sh);
+    cam.viewport = [w as f32, h as f32];
+    cam.zoom = (w as f32 / sw).min(h as f32 / sh);
+    Some(cam)
+}
+
+fn renderer(scene: &Scene, size: Option<[u16; 2]>) -> Renderer {
+    let [w, h] = size.unwrap_or([scene.width, scene.height]);
+    let mut r = Renderer::new(FramePool::new(w, h, 1));
+    r.set_camera(fit(scene, size));
+    r
+}
+
+/// Render one frame of a scene, for previews.
+///
+/// # Arguments
+///
+/// - `scene` (`&Scene`) - What to draw.
+/// - `time` (`f32`) - When, in seconds.
+/// - `size` (`Option<[u16; 2]>`) - The output size (the scene is scaled to fit), `None`
+///   for the scene's own.
+///
+/// # Returns
+///
+/// - `Frame` - The rendered frame.
+#[must_use]
+pub fn render_frame(scene: &Scene, time: f32, size: Option<[u16; 2]>) -> Frame {
+    let mut r = renderer(scene, size);
+    let mut fb = r.acquire().expect("a fresh pool has a frame");
+    scene.render(&mut r, &mut fb, time);
+    fb
+}
+
+/// Save a frame as a PNG. `quality` (0-100) only trades file size for encoding time.
+pub fn save_png(fb: &Frame, path: &Path, quality: u8) -> Result<(), ExportError> {
+    let file = File::create(path).map_err(io_error(path))?;
+    let compression = match quality {
+        0..34 => CompressionType::Fast,
+        34..67 => CompressionType::Default,
+        _ => CompressionType::Best,
+    };
+    PngEncoder::new_with_quality(BufWriter::new(file), compression, FilterType::Adaptive)
+        .write_image(
+            fb.as_slice(),
+            fb.width() as u32,
+            fb.height() as u32,
+            ExtendedColorType::Rgba8,
+        )
+        .map_err(|e| match e {
+            image::ImageError::IoError(error) => io_error(path)(error),
+            e => ExportError::Encode(e.to_string()),
+        })
+}
+
+/// Render a scene to a video, GIF or PNG sequence.
+///
+/// # Arguments
+///
+/// - `scene` (`&Scene`) - What to render.
+/// - `output` (`&Path`) - Where to, which also picks the format (see `OutputFormat`).
+/// - `options` (`&ExportOptions`) - Size, frame range and quality.
+/// - `progress` (`impl FnMut(u32, u32)`) - Called after every frame with how many are done
+///   and how many there are.
+///
+/// # Returns
+///
+/// - `Result<u32, ExportError>` - How many frames were written.
+///
+/// # Examples
+///
+/// ```no_run
+/// use codimate::renderer::{export::{self, ExportOptions}, scene_file};
+///
+/// let scene = scene_file::load("intro.toml".as_ref())?;
+/// let preview = ExportOptions { size: Some([640, 360]), quality: 50, ..Default::default() };
+/// export::export(&scene, "intro.gif".as_ref(), &preview, |done, total| eprint!("\r{done}/{total}"))?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn export(
+    scene: &Scene,
+    output: &Path,
+    options: &ExportOptions,
+    mut progress: impl FnMut(u32, u32),
+) -> Result<u32, ExportError> {
+    let format = OutputFormat::from_path(output)
+        .ok_or_else(|| ExportError::UnsupportedOutput(output.into()))?;
+    let count = scene.frame_count();
+    let frames = match &options.frames {
+        Some(r) => r.start..r.end.min(count),
+        None => 0..count,
+    };
+    if frames.is_empty() {
+        return Err(ExportError::EmptyRange {
+            frames: options.frames.clone().unwrap_or(0..0),
+            count,
+        });
+    }
+
+    let mut r = renderer(scene, options.size);
+    let [w, h] = [r.pool().width(), r.pool().height()];
+    let mut sink: Box<dyn Sink> = match format {
+        OutputFormat::Video => Box::new(Ffmpeg::spawn(output, [w, h], scene.fps, options.quality)?),
+        OutputFormat::Gif => Box::new(Gif::create(output, scene.fps, options.quality)?),
+        OutputFormat::PngSequence => Box::new(PngSequence::new(output, options.quality)?),
+    };
+
+    let total = frames.len() as u32;
+    for (done, index) in frames.enumerate() {
+        let mut fb = r
+            .acquire()
+            .expect("frames go back to the pool every iteration");
+        scene.render(&mut r, &mut fb, scene.frame_time(index));
+        let written = sink.write(&mut fb, index);
+        r.recycle(fb).expect("the frame came from this pool");
+        written?;
+        progress(done as u32 + 1, total);
+    }
+    sink.finish()?;
+    Ok(total)
+}
+
+/// Where exported frames go.
+trait Sink {
+    /// Take frame `index` (the sink may scribble on it).
+    fn write(&mut self, fb: &mut Frame, index: u32) -> Result<(), ExportError>;
+    fn finish(self: Box<Self>) -> Result<(), ExportError>;
+}
+
+struct PngSequence {
+    pattern: String,
+    quality: u8,
+}
+
+impl PngSequence {
+    fn new(output: &Path, quality: u8) -> Result<Self, ExportError> {
+        if names_dir(output) {
+            fs::create_dir_all(output).map_err(io_error(output))?;
+        }
+        Ok(Self {
+            pattern: png_pattern(output),
+            quality,
+        })
+    }
+}
+
+// a directory that may not exist yet is named with a trailing slash
+fn names_dir(output: &Path) -> bool {
+    output.is_dir() || output.to_string_lossy().ends_with(['/', '\\'])
+}
+
+/// The `%04d` pattern a PNG sequence output writes its frames to.
+fn png_pattern(output: &Path) -> String {
+    let text = output.to_string_lossy();
+    if names_dir(output) {
+        output.join("frame_%04d.png").to_string_lossy().into_owned()
+    } else if numbered(&text, 0).is_some() {
+        text.into_owned()
+    } else {
+        output.with_extension("").to_string_lossy().into_owned() + "_%04d.png"
+    }
+}
+
+/// Delete frames of a PNG sequence output, for when a scene gets shorter. Frames that
+/// aren't there are skipped.
+pub(crate) fn remove_png_frames(output: &Path, frames: Range<u32>) -> Result<(), ExportError> {
+    let pattern = png_pattern(output);
+    for index in frames {
+        let path = numbered(&pattern, index).expect("the pattern has a frame number");
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                return Err(io_error(Path::new(&path))(e));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+impl Sink for PngSequence {
+    fn write(&mut self, fb: &mut Frame, index: u32) -> Result<(), ExportError> {
+        let path = numbered(&self.pattern, index).expect("the pattern has a frame number");
+        save_png(fb, Path::new(&path), self.quality)
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), ExportError> {
+        Ok(())
+    }
+}
+
+/// Fill in the first `%d` / `%04d` of a pattern (`None` if it doesn't have one).
+fn numbered(pattern: &str, n: u32) -> Option<String> {
+    let at = pattern.find('%')?;
+    let spec = &pattern[at + 1..];
+    let d = spec.find('d')?;
+    let width = &spec[..d];
+    if !width.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let width = width.parse().unwrap_or(0);
+    Some(format!("{}{n:0width$}{}", &pattern[..at], &spec[d + 1..]))
+}
+
+struct Gif {
+    path: PathBuf,
+    encoder: GifEncoder<BufWriter<File>>,
+    delay: Delay,
+    colors: usize,
+}
+
+impl Gif {
+    fn create(path: &Path, fps: f32, quality: u8) -> Result<Self, ExportError> {
+        let file = File::create(path).map_err(io_error(path))?;
+        let mut encoder = GifEncoder::new(BufWriter::new(file));
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|e| ExportError::Encode(e.to_string()))?;
+        Ok(Self {
+            path: path.to_owned(),
+            encoder,
+            // gif delays are in 1/100 s, so odd frame rates still get rounded by the format
+            delay: Delay::from_saturating_duration(
+                Duration::try_from_secs_f32(fps.recip()).unwrap_or(Duration::MAX),
+            ),
+            colors: 2 + 254 * quality.min(100) as usize / 100,
+        })
+    }
+}
+
+impl Sink for Gif {
+    fn write(&mut self, fb: &mut Frame, _index: u32) -> Result<(), ExportError> {
+        // with 256 colors or fewer the encoder keeps our palette as is.
+        // ordered dithering doesn't crawl between frames like error diffusion does
+        let palette = Palette::from_frame(fb, self.colors);
+        dither::quantize(fb, &palette, Dither::default());
+        let image = RgbaImage::from_raw(
+            fb.width() as u32,
+            fb.height() as u32,
+            fb.as_slice().to_vec(),
+        )
+        .expect("frames are tightly packed rgba");
+        self.encoder
+            .encode_frame(image::Frame::from_parts(image, 0, 0, self.delay))
+            .map_err(|e| match e {
+                image::ImageError::IoError(error) => io_error(&self.path)(error),
+                e => ExportError::Encode(e.to_string()),
+            })
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), ExportError> {
+        // the encoder writes the trailer on drop, the BufWriter flushes on drop too
+        drop(self.encoder);
+        Ok(())
+    }
+}
+
+struct Ffmpeg {
+    output: PathBuf,
+    child: Child,
+    // `None` once it's been closed
+    stdin: Option<ChildStdin>,
+    finished: bool,
+}
+
+impl Ffmpeg {
+    fn spawn(output: &Path, [w, h]: [u16; 2], fps: f32, quality: u8) -> Result<Self, ExportError> {
+        // libx264's crf scale: ~12 is visually lossless, 40 is rough
+        let crf = 40 - 28 * quality.min(100) as u32 / 100;
+        let webm = output
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("webm"));
+        let codec = if webm { "libvpx-vp9" } else { "libx264" };
+
+        let mut child = Command::new("ffmpeg")
+            .args(["-y", "-hide_banner", "-loglevel", "error"])
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{w}x{h}"), "-r", &fps.to_string(), "-i", "-"])
+            // 4:2:0 needs even sizes
+            .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+            .args([
+                "-c:v",
+                codec,
+                "-pix_fmt",
+                "yuv420p",
+                "-crf",
+                &crf.to_string(),
+            ])
+            .args(if webm { &["-b:v", "0"][..] } else { &[] })
+            .arg(output)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::NotFound => ExportError::FfmpegMissing,
+                _ => ExportError::Ffmpeg(e.to_string()),
+            })?;
+        let stdin = child.stdin.take();
+        Ok(Self {
+            output: output.to_owned(),
+            child,
+            stdin,
+            finished: false,
+        })
+    }
+}
+
+impl Sink for Ffmpeg {
+    fn write(&mut self, fb: &mut Frame, _index: u32) -> Result<(), ExportError> {
+        self.stdin
+            .as_mut()
+            .expect("stdin is piped until finish")
+            .write_all(fb.as_slice())
+            .map_err(|e| match e.kind() {
+                // ffmpeg quit early, what it printed says why
+                io::ErrorKind::BrokenPipe => {
+                    let mut why = String::new();
+                    if let Some(mut stderr) = self.child.stderr.take() {
+                        let _ = stderr.read_to_string(&mut why);
+                    }
+                    let _ = self.child.wait();
+                    ExportError::Ffmpeg(why.trim().to_owned())
+                }
+                _ => ExportError::Ffmpeg(e.to_string()),
+            })
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), ExportError> {
+        // ffmpeg finishes the file once its input ends
+        drop(self.stdin.take());
+        let mut why = String::new();
+        if let Some(mut stderr) = self.child.stderr.take() {
+            let _ = stderr.read_to_string(&mut why);
+        }
+        let status = self
+            .child
+            .wait()
+            .map_err(|e| ExportError::Ffmpeg(e.to_string()))?;
+        if status.success() {
+            self.finished = true;
+            Ok(())
+        } else {
+            Err(ExportError::Ffmpeg(why.trim().to_owned()))
+        }
+    }
+}
+
+impl Drop for Ffmpeg {
+    // the export stopped before `finish` got to the end (a frame failed to render or
+    // write, or ffmpeg did): don't leave ffmpeg running or half a video behind
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        drop(self.stdin.take());
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_file(&self.output);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{AnimationDecoder, codecs::gif::GifDecoder};
+
+    use super::*;
+    use crate::{
+        color::model::Color,
+        renderer::scene::{Node, Shape},
+    };
+
+    fn scene() -> Scene {
+        let mut scene = Scene::new(32, 16);
+        scene.fps = 10.0;
+        scene.duration = 0.5;
+        scene.background = Color::BLUE;
+        scene.add(Node::new(
+            Shape::Rect {
+                size: [16.0, 16.0],
+                radius: 0.0,
+            },
+            [0.0, 0.0],
+            Color::RED,
+        ));
+        scene
+    }
+
+    #[test]
+    fn outputs_are_picked_by_path() {
+        assert_eq!(
+            OutputFormat::from_path("a.MP4".as_ref()),
+            Some(OutputFormat::Video)
+        );
+        assert_eq!(
+            OutputFormat::from_path("a.gif".as_ref()),
+            Some(OutputFormat::Gif)
+        );
+        assert_eq!(
+            OutputFormat::from_path("out/".as_ref()),
+            Some(OutputFormat::PngSequence)
+        );
+        assert_eq!(OutputFormat::from_path("a.txt".as_ref()), None);
+
+        assert_eq!(numbered("f_%04d.png", 7).as_deref(), Some("f_0007.png"));
+        assert_eq!(numbered("f%d.png", 12).as_deref(), Some("f12.png"));
+        assert_eq!(numbered("f.png", 1), None);
+    }
+
+    #[test]
+    fn resized_frames_fit_the_scene() {
+        // twice as tall as it needs to be, so the scene is centered with bars above and below
+        let fb = render_frame(&scene(), 0.0, Some([64, 64]));
+        assert_eq!((fb.width(), fb.height()), (64, 64));
+        assert_eq!(fb.get_pixel(8, 32), Some(Color::RED));
+        assert_eq!(fb.get_pixel(48, 32), Some(Color::BLUE));
+    }
+
+    #[test]
+    fn exports_png_sequences_and_gifs() {
+        let dir = std::env::temp_dir().join(format!("codimate-export-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let options = ExportOptions {
+            frames: Some(2..99),
+            ..Default::default()
+        };
+
+        let n = export(&scene(), &dir.join("f_%02d.png"), &options, |_, _| {}).unwrap();
+        assert_eq!(n, 3);
+        assert!(dir.join("f_02.png").exists() && dir.join("f_04.png").exists());
+        assert!(!dir.join("f_01.png").exists());
+
+        let mut calls = 0;
+        export(&scene(), &dir.join("a.gif"), &Default::default(), |_, _| {
+            calls += 1
+        })
+        .unwrap();
+        assert_eq!(calls, 5);
+        assert!(fs::read(dir.join("a.gif")).unwrap().starts_with(b"GIF89a"));
+
+        // slow frame rates keep their timing instead of being rounded to 1 fps
+        let mut slow = scene();
+        (slow.fps, slow.duration) = (0.4, 5.0);
+        export(&slow, &dir.join("slow.gif"), &Default::default(), |_, _| {}).unwrap();
+        let file = io::BufReader::new(File::open(dir.join("slow.gif")).unwrap());
+        let frames = GifDecoder::new(file)
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].delay().numer_denom_ms(), (2500, 1));
+
+        let empty = ExportOptions {
+            frames: Some(10..20),
+            ..Default::default()
+        };
+        assert!(matches!(
+            export(&scene(), &dir.join("b.gif"), &empty, |_, _| {}),
+            Err(ExportError::EmptyRange { .. })
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,10 +1,16 @@
 #![allow(dead_code)]
 
-use image::EncodableLayout;
-
-use crate::color::model::Color;
+use crate::{
+    color::{
+        ColorFloat,
+        cvd::{Cvd, Simulator},
+        model::Color,
+    },
+    renderer::sample::{self, Filter},
+};
 
 // frames -> width, height, and the actual color data
+#[derive(PartialEq, Eq)]
 pub struct Frame {
     width: u16,
     height: u16,
@@ -13,9 +19,20 @@ pub struct Frame {
 
 // note from noar: i assume we wanna add other color support like
 // rgb24 (3 bytes per pixel) and yuv420 (4:2:0 planar layout). frn just added basic rgba
+// printing every pixel is never what we want
+impl std::fmt::Debug for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Frame")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
 impl Frame {
     pub fn new(width: u16, height: u16) -> Self {
-        let data = vec![0; (width * height * 4).into()];
+        // widen before multiplying, 4k frames overflow u16 immediately
+        let data = vec![0; width as usize * height as usize * 4];
         Self {
             width,
             height,
@@ -54,10 +71,7 @@ impl Frame {
             return None;
         }
 
-        // vectors use usize so this the only way to make the compiler stop yellin
-        // i'll look into this in a little bit
-        // - noah
-        let i: usize = ((y * self.width + x) * 4).into();
+        let i = (y as usize * self.width as usize + x as usize) * 4;
         Some(Color::from_rgba([
             self.data[i],
             self.data[i + 1],
@@ -65,4 +79,70 @@ impl Frame {
             self.data[i + 3],
         ]))
     }
+
+    // how the frame looks with a color vision deficiency (severity 0..1), in place.
+    // code themes repeat the same few colors so each distinct pixel only gets simulated once
+    pub fn simulate_cvd(&mut self, cvd: Cvd, severity: ColorFloat) {
+        let sim = Simulator::new(cvd, severity);
+        let mut last: Option<([u8; 4], [u8; 4])> = None;
+        for px in self.data.chunks_exact_mut(4) {
+            let src = [px[0], px[1], px[2], px[3]];
+            let out = match last {
+                Some((from, to)) if from == src => to,
+                _ => sim.apply(Color::from_rgba(src)).into_rgba(),
+            };
+            last = Some((src, out));
+            px.copy_from_slice(&out);
+        }
+    }
+
+    /// premultiplied linear rgba at a pixel, transparent outside the frame
+    #[inline]
+    pub fn premul_linear(&self, x: i32, y: i32) -> [ColorFloat; 4] {
+        if x < 0 || y < 0 {
+            return [0.0; 4];
+        }
+        let Some(c) = self.get_pixel(x as u16, y as u16) else {
+            return [0.0; 4];
+        };
+        let [r, g, b, a] = c.into_linear();
+        [r * a, g * a, b * a, a]
+    }
+
+    // filtered sample at a continuous position (pixel centers sit on .5), transparent outside.
+    // decodes every tap so it's fine for a few lookups, turn the frame into a Texture for drawing
+    pub fn sample(&self, filter: Filter, x: f32, y: f32) -> Color {
+        let [r, g, b, a] = sample::filter_at(|x, y| self.premul_linear(x, y), filter, x, y);
+        if a <= 0.0 {
+            return Color::TRANSPARENT;
+        }
+        Color::from_linear([r / a, g / a, b / a, a])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cvd_simulation_matches_single_colors() {
+        let colors = [
+            Color::RED,
+            Color::RED,
+            Color::new(40, 200, 90, 128),
+            Color::RED,
+        ];
+        let mut fb = Frame::new(4, 1);
+        for (px, c) in fb.as_bytes_mut().chunks_exact_mut(4).zip(colors) {
+            px.copy_from_slice(&c.into_rgba());
+        }
+
+        fb.simulate_cvd(Cvd::Deuteranopia, 1.0);
+        for (x, c) in colors.into_iter().enumerate() {
+            assert_eq!(
+                fb.get_pixel(x as u16, 0),
+                Some(Cvd::Deuteranopia.simulate(c))
+            );
+        }
+    }
 }
@@ -0,0 +1,651 @@
+#![allow(dead_code)]
+
+use crate::color::{ColorFloat, model::Color};
+
+/// How many precomputed colors a gradient keeps per 0.0-1.0 run of its stops.
+/// Lookups interpolate between entries, so this only bounds how sharp a hard stop can be.
+const LUT_SIZE: usize = 1024;
+
+/// A color at a position along a gradient.
+///
+/// # Fields
+///
+/// - `offset` (`ColorFloat`) - Where the stop sits, from 0.0 (start) to 1.0 (end).
+/// - `color` (`Color`) - The color at that position.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorStop {
+    pub offset: ColorFloat,
+    pub color: Color,
+}
+
+impl ColorStop {
+    #[must_use]
+    #[inline]
+    pub const fn new(offset: ColorFloat, color: Color) -> Self {
+        Self { offset, color }
+    }
+}
+
+/// The geometry of a gradient, in device pixels.
+///
+/// # Variants
+///
+/// - `Linear` - Varies along the line from `start` (0.0) to `end` (1.0).
+/// - `Radial` - Varies from `center` (0.0) out to `radius` (1.0).
+/// - `TwoPointConical` - Interpolates between two circles, like canvas'
+///   `createRadialGradient`. The start circle is 0.0 and the end circle is 1.0.
+/// - `Conic` - Sweeps clockwise around `center`, starting at `angle` degrees
+///   (0 points right).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientKind {
+    Linear {
+        start: [ColorFloat; 2],
+        end: [ColorFloat; 2],
+    },
+    Radial {
+        center: [ColorFloat; 2],
+        radius: ColorFloat,
+    },
+    TwoPointConical {
+        start: [ColorFloat; 2],
+        start_radius: ColorFloat,
+        end: [ColorFloat; 2],
+        end_radius: ColorFloat,
+    },
+    Conic {
+        center: [ColorFloat; 2],
+        angle: ColorFloat,
+    },
+}
+
+/// What a gradient does outside of its 0.0-1.0 range.
+///
+/// # Variants
+///
+/// - `Pad` - Extend the first and last stop colors forever.
+/// - `Repeat` - Start over from the first stop.
+/// - `Reflect` - Run back and forth, mirroring every other repetition.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpreadMode {
+    #[default]
+    Pad,
+    Repeat,
+    Reflect,
+}
+
+/// The color space stops are interpolated in.
+///
+/// # Variants
+///
+/// - `Srgb` - Gamma encoded sRGB, matching most browsers' legacy behavior.
+/// - `LinearSrgb` - Linear light, physically correct but dark in the middle.
+/// - `Oklab` - Perceptually even, the default.
+/// - `Oklch` - Perceptually even and keeps chroma up, taking the shorter way around the hue wheel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    Srgb,
+    LinearSrgb,
+    #[default]
+    Oklab,
+    Oklch,
+}
+
+/// A gradient paint with arbitrary color stops.
+///
+/// Colors are interpolated in premultiplied form (except for hue), precomputed into a
+/// lookup table when the gradient is built and optionally ordered-dithered on output
+/// so slow ramps don't band in 8-bit frames.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::color::Color;
+/// use codimate::renderer::gradient::{ColorStop, Gradient, SpreadMode};
+///
+/// let glow = Gradient::radial([960.0, 540.0], 400.0, &[
+///     ColorStop::new(0.0, Color::new(137, 180, 250, 255)),
+///     ColorStop::new(1.0, Color::new(137, 180, 250, 0)),
+/// ])
+/// .with_spread(SpreadMode::Pad)
+/// .with_dither(true);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    kind: GradientKind,
+    stops: Vec<ColorStop>,
+    spread: SpreadMode,
+    interpolation: Interpolation,
+    dither: bool,
+    // straight alpha sRGB, 0.0-255.0, unquantized
+    lut: Vec<[ColorFloat; 4]>,
+}
+
+impl Gradient {
+    /// Create a gradient of any kind. Stops are sorted by offset and clamped into 0.0-1.0.
+    #[must_use]
+    pub fn new(kind: GradientKind, stops: &[ColorStop]) -> Self {
+        let mut stops = stops.to_vec();
+        for s in &mut stops {
+            s.offset = s.offset.clamp(0.0, 1.0);
+        }
+        // stable, so stops sharing an offset keep their order and make a hard edge
+        stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+
+        let mut g = Self {
+            kind,
+            stops,
+            spread: SpreadMode::default(),
+            interpolation: Interpolation::default(),
+            dither: false,
+            lut: Vec::new(),
+        };
+        g.rebuild_lut();
+        g
+    }
+
+    #[must_use]
+    pub fn linear(start: [ColorFloat; 2], end: [ColorFloat; 2], stops: &[ColorStop]) -> Self {
+        Self::new(GradientKind::Linear { start, end }, stops)
+    }
+
+    #[must_use]
+    pub fn radial(center: [ColorFloat; 2], radius: ColorFloat, stops: &[ColorStop]) -> Self {
+        Self::new(GradientKind::Radial { center, radius }, stops)
+    }
+
+    #[must_use]
+    pub fn two_point_conical(
+        start: [ColorFloat; 2],
+        start_radius: ColorFloat,
+        end: [ColorFloat; 2],
+        end_radius: ColorFloat,
+        stops: &[ColorStop],
+    ) -> Self {
+        Self::new(
+            GradientKind::TwoPointConical {
+                start,
+                start_radius,
+                end,
+                end_radius,
+            },
+            stops,
+        )
+    }
+
+    #[must_use]
+    pub fn conic(center: [ColorFloat; 2], angle: ColorFloat, stops: &[ColorStop]) -> Self {
+        Self::new(GradientKind::Conic { center, angle }, stops)
+    }
+
+    #[must_use]
+    pub fn with_spread(mut self, spread: SpreadMode) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    #[must_use]
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self.rebuild_lut();
+        self
+    }
+
+    #[must_use]
+    pub fn with_dither(mut self, dither: bool) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> GradientKind {
+        self.kind
+    }
+
+    #[must_use]
+    pub fn stops(&self) -> &[ColorStop] {
+        &self.stops
+    }
+
+    #[must_use]
+    pub fn spread(&self) -> SpreadMode {
+        self.spread
+    }
+
+    #[must_use]
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    #[must_use]
+    pub fn dither(&self) -> bool {
+        self.dither
+    }
+
+    /// Get the gradient parameter at a point, before the spread mode is applied.
+    /// `None` means the gradient doesn't cover the point at all (e.g. a degenerate line).
+    #[must_use]
+    pub fn t_at(&self, x: ColorFloat, y: ColorFloat) -> Option<ColorFloat> {
+        use GradientKind::*;
+
+        match self.kind {
+            Linear { start, end } => {
+                let (dx, dy) = (end[0] - start[0], end[1] - start[1]);
+                let len2 = dx * dx + dy * dy;
+                if len2 <= ColorFloat::EPSILON {
+                    return None;
+                }
+                Some(((x - start[0]) * dx + (y - start[1]) * dy) / len2)
+            }
+            Radial { center, radius } => {
+                if radius <= 0.0 {
+                    return None;
+                }
+                let (dx, dy) = (x - center[0], y - center[1]);
+                Some((dx * dx + dy * dy).sqrt() / radius)
+            }
+            TwoPointConical {
+                start,
+                start_radius,
+                end,
+                end_radius,
+            } => two_point_conical_t([x, y], start, start_radius, end, end_radius),
+            Conic { center, angle } => {
+                let a = (y - center[1]).atan2(x - center[0]).to_degrees() - angle;
+                Some(a.rem_euclid(360.0) / 360.0)
+            }
+        }
+    }
+
+    /// Get the 8-bit color of the gradient at a pixel, sampled at the pixel's center.
+    #[must_use]
+    pub fn shade(&self, x: u16, y: u16) -> Option<[u8; 4]> {
+        if self.lut.is_empty() {
+            return None;
+        }
+
+        let t = self.t_at(x as ColorFloat + 0.5, y as ColorFloat + 0.5)?;
+        let t = match self.spread {
+            SpreadMode::Pad => t.clamp(0.0, 1.0),
+            SpreadMode::Repeat => t - t.floor(),
+            SpreadMode::Reflect => {
+                let m = t.rem_euclid(2.0);
+                if m > 1.0 { 2.0 - m } else { m }
+            }
+        };
+
+        let c = self.sample_lut(t);
+        let bias = if self.dither { bayer8(x, y) } else { 0.0 };
+        let q = |v: ColorFloat| (v + 0.5 + bias).floor().clamp(0.0, 255.0) as u8;
+        Some([
+            q(c[0]),
+            q(c[1]),
+            q(c[2]),
+            (c[3] + 0.5).floor().clamp(0.0, 255.0) as u8,
+        ])
+    }
+
+    // --- private methods --- //
+
+    #[inline]
+    fn sample_lut(&self, t: ColorFloat) -> [ColorFloat; 4] {
+        let pos = t * (LUT_SIZE - 1) as ColorFloat;
+        let i = (pos as usize).min(LUT_SIZE - 2);
+        let f = pos - i as ColorFloat;
+        let (a, b) = (self.lut[i], self.lut[i + 1]);
+        [
+            a[0] + (b[0] - a[0]) * f,
+            a[1] + (b[1] - a[1]) * f,
+            a[2] + (b[2] - a[2]) * f,
+            a[3] + (b[3] - a[3]) * f,
+        ]
+    }
+
+    fn rebuild_lut(&mut self) {
+        self.lut.clear();
+        if self.stops.is_empty() {
+            return;
+        }
+
+        self.lut.reserve(LUT_SIZE);
+        for i in 0..LUT_SIZE {
+            let t = i as ColorFloat / (LUT_SIZE - 1) as ColorFloat;
+            self.lut.push(self.eval(t));
+        }
+    }
+
+    /// Evaluate the stops at `t` in the gradient's interpolation space.
+    fn eval(&self, t: ColorFloat) -> [ColorFloat; 4] {
+        let first = self.stops[0];
+        let last = self.stops[self.stops.len() - 1];
+        if t <= first.offset {
+            return to_srgb255(self.interpolation, encode(self.interpolation, first.color));
+        }
+        if t >= last.offset {
+            return to_srgb255(self.interpolation, encode(self.interpolation, last.color));
+        }
+
+        // last stop starting at or before t, so a repeated offset jumps straight to the later color
+        let i = self.stops.partition_point(|s| s.offset <= t) - 1;
+        let (a, b) = (self.stops[i], self.stops[i + 1]);
+        let span = b.offset - a.offset;
+        let f = if span > 0.0 {
+            (t - a.offset) / span
+        } else {
+            1.0
+        };
+
+        let ea = encode(self.interpolation, a.color);
+        let eb = encode(self.interpolation, b.color);
+        to_srgb255(self.interpolation, mix(self.interpolation, ea, eb, f))
+    }
+}
+
+/// A color in the interpolation space: three premultiplied components and alpha
+/// (OKLCH keeps hue unpremultiplied in slot 2).
+fn encode(space: Interpolation, c: Color) -> [ColorFloat; 4] {
+    let [r, g, b, a] = c.into_linear();
+    let [x, y, z] = match space {
+        Interpolation::Srgb => {
+            let [r, g, b] = c.into_rgb();
+            [
+                r as ColorFloat / 255.0,
+                g as ColorFloat / 255.0,
+                b as ColorFloat / 255.0,
+            ]
+        }
+        Interpolation::LinearSrgb => [r, g, b],
+        Interpolation::Oklab => Color::linear_to_oklab([r, g, b]),
+        Interpolation::Oklch => {
+            let [l, a, b] = Color::linear_to_oklab([r, g, b]);
+            let h = b.atan2(a).to_degrees().rem_euclid(360.0);
+            [l, (a * a + b * b).sqrt(), h]
+        }
+    };
+    match space {
+        Interpolation::Oklch => [x * a, y * a, z, a],
+        _ => [x * a, y * a, z * a, a],
+    }
+}
+
+fn mix(
+    space: Interpolation,
+    a: [ColorFloat; 4],
+    b: [ColorFloat; 4],
+    t: ColorFloat,
+) -> [ColorFloat; 4] {
+    let lerp = |x: ColorFloat, y: ColorFloat| x + (y - x) * t;
+
+    if space != Interpolation::Oklch {
+        return [
+            lerp(a[0], b[0]),
+            lerp(a[1], b[1]),
+            lerp(a[2], b[2]),
+            lerp(a[3], b[3]),
+        ];
+    }
+
+    // same rules as Color::lerp_oklch: grays borrow the other hue, then take the short way
+    let (ca, cb) = (a[1], b[1]);
+    let (ha, hb) = if ca < 1e-5 {
+        (b[2], b[2])
+    } else if cb < 1e-5 {
+        (a[2], a[2])
+    } else {
+        (a[2], b[2])
+    };
+    let mut dh = hb - ha;
+    if dh > 180.0 {
+        dh -= 360.0;
+    }
+    if dh <= -180.0 {
+        dh += 360.0;
+    }
+    [
+        lerp(a[0], b[0]),
+        lerp(ca, cb),
+        (ha + dh * t).rem_euclid(360.0),
+        lerp(a[3], b[3]),
+    ]
+}
+
+/// Unpremultiply and convert an interpolated color to straight alpha sRGB in 0.0-255.0.
+fn to_srgb255(space: Interpolation, c: [ColorFloat; 4]) -> [ColorFloat; 4] {
+    let a = c[3];
+    if a <= 0.0 {
+        return [0.0; 4];
+    }
+
+    let [x, y, z] = match space {
+        Interpolation::Oklch => {
+            let (l, ch) = (c[0] / a, c[1] / a);
+            let h = c[2].to_radians();
+            [l, ch * h.cos(), ch * h.sin()]
+        }
+        _ => [c[0] / a, c[1] / a, c[2] / a],
+    };
+    let srgb = match space {
+        Interpolation::Srgb => [x, y, z],
+        Interpolation::LinearSrgb => [
+            Color::encode_srgb_unit(x),
+            Color::encode_srgb_unit(y),
+            Color::encode_srgb_unit(z),
+        ],
+        Interpolation::Oklab | Interpolation::Oklch => {
+            let lin = Color::oklab_to_linear([x, y, z]);
+            [
+                Color::encode_srgb_unit(lin[0]),
+                Color::encode_srgb_unit(lin[1]),
+                Color::encode_srgb_unit(lin[2]),
+            ]
+        }
+    };
+
+    [
+        srgb[0].clamp(0.0, 1.0) * 255.0,
+        srgb[1].clamp(0.0, 1.0) * 255.0,
+        srgb[2].clamp(0.0, 1.0) * 255.0,
+        a.clamp(0.0, 1.0) * 255.0,
+    ]
+}
+
+/// Solve for the largest `t` whose circle passes through `p` with a non-negative radius.
+fn two_point_conical_t(
+    p: [ColorFloat; 2],
+    c0: [ColorFloat; 2],
+    r0: ColorFloat,
+    c1: [ColorFloat; 2],
+    r1: ColorFloat,
+) -> Option<ColorFloat> {
+    // |p - c(t)| = r(t) with c(t) = c0 + t*cd and r(t) = r0 + t*dr
+    // expands to a*t^2 - 2*b*t + c = 0
+    let cd = [c1[0] - c0[0], c1[1] - c0[1]];
+    let pd = [p[0] - c0[0], p[1] - c0[1]];
+    let dr = r1 - r0;
+
+    let a = cd[0] * cd[0] + cd[1] * cd[1] - dr * dr;
+    let b = pd[0] * cd[0] + pd[1] * cd[1] + r0 * dr;
+    let c = pd[0] * pd[0] + pd[1] * pd[1] - r0 * r0;
+    let radius_ok = |t: ColorFloat| r0 + t * dr >= 0.0;
+
+    if a.abs() <= 1e-6 {
+        if b.abs() <= 1e-6 {
+            return None;
+        }
+        let t = c / (2.0 * b);
+        return radius_ok(t).then_some(t);
+    }
+
+    let disc = b * b - a * c;
+    if disc < 0.0 {
+        return None;
+    }
+    let sq = disc.sqrt();
+    let (t1, t2) = ((b + sq) / a, (b - sq) / a);
+    let (hi, lo) = if t1 >= t2 { (t1, t2) } else { (t2, t1) };
+    if radius_ok(hi) {
+        Some(hi)
+    } else if radius_ok(lo) {
+        Some(lo)
+    } else {
+        None
+    }
+}
+
+/// 8x8 Bayer threshold matrix, used for ordered dithering.
+pub(crate) const BAYER_8X8: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// An ordered dither offset in -0.5..0.5 code values for a pixel.
+#[inline]
+pub(crate) fn bayer8(x: u16, y: u16) -> ColorFloat {
+    (BAYER_8X8[(y & 7) as usize][(x & 7) as usize] as ColorFloat + 0.5) / 64.0 - 0.5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK_TO_WHITE: [ColorStop; 2] = [
+        ColorStop::new(0.0, Color::BLACK),
+        ColorStop::new(1.0, Color::WHITE),
+    ];
+
+    #[test]
+    fn linear_hits_its_end_stops() {
+        let g = Gradient::linear([10.0, 0.0], [90.0, 0.0], &BLACK_TO_WHITE);
+        assert_eq!(g.shade(5, 0), Some([0, 0, 0, 255]));
+        assert_eq!(g.shade(95, 0), Some([255, 255, 255, 255]));
+        // pad keeps going past the ends
+        assert_eq!(g.shade(500, 0), Some([255, 255, 255, 255]));
+
+        let mid = g.shade(49, 0).unwrap();
+        assert!(mid[0] > 64 && mid[0] < 192);
+    }
+
+    #[test]
+    fn spread_modes() {
+        let base = Gradient::linear([0.0, 0.0], [10.0, 0.0], &BLACK_TO_WHITE)
+            .with_interpolation(Interpolation::Srgb);
+        let at = |g: &Gradient, x: u16| g.shade(x, 0).unwrap()[0];
+
+        let repeat = base.clone().with_spread(SpreadMode::Repeat);
+        assert_eq!(at(&repeat, 2), at(&repeat, 12));
+
+        let reflect = base.with_spread(SpreadMode::Reflect);
+        assert_eq!(at(&reflect, 2), at(&reflect, 17));
+        assert!(at(&reflect, 12) > at(&reflect, 17));
+    }
+
+    #[test]
+    fn hard_stops_switch_color_without_blending() {
+        let g = Gradient::linear(
+            [0.0, 0.0],
+            [100.0, 0.0],
+            &[
+                ColorStop::new(0.0, Color::RED),
+                ColorStop::new(0.5, Color::RED),
+                ColorStop::new(0.5, Color::BLUE),
+                ColorStop::new(1.0, Color::BLUE),
+            ],
+        );
+        assert_eq!(g.shade(40, 0), Some([255, 0, 0, 255]));
+        assert_eq!(g.shade(60, 0), Some([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn two_point_conical_matches_radial_when_concentric() {
+        let radial = Gradient::radial([32.0, 32.0], 20.0, &BLACK_TO_WHITE);
+        let conical =
+            Gradient::two_point_conical([32.0, 32.0], 0.0, [32.0, 32.0], 20.0, &BLACK_TO_WHITE);
+        for (x, y) in [(32, 32), (40, 35), (10, 60), (50, 20)] {
+            let (a, b) = (radial.shade(x, y).unwrap(), conical.shade(x, y).unwrap());
+            assert!(
+                a.iter().zip(b).all(|(a, b)| a.abs_diff(b) <= 1),
+                "{a:?} vs {b:?} at {x},{y}"
+            );
+        }
+    }
+
+    #[test]
+    fn conic_sweeps_clockwise_from_its_angle() {
+        let g = Gradient::conic([0.0, 0.0], 0.0, &BLACK_TO_WHITE)
+            .with_interpolation(Interpolation::Srgb);
+        let t = |x: f32, y: f32| g.t_at(x as ColorFloat, y as ColorFloat).unwrap();
+        assert!(t(10.0, 0.001) < 0.01);
+        assert!((t(0.0, 10.0) - 0.25).abs() < 1e-4);
+        assert!((t(-10.0, 0.0) - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn fading_gradient_tints_what_is_under_it() {
+        use crate::renderer::{pool::FramePool, render::Renderer, tile::Tiling};
+
+        let mut r = Renderer::new(FramePool::new(64, 8, 1));
+        r.set_tiling(Tiling::Serial);
+        let mut fb = r.acquire().unwrap();
+        r.begin_frame();
+        r.queue_clear(Color::BLUE);
+        let fade = [
+            ColorStop::new(0.0, Color::RED),
+            ColorStop::new(1.0, Color::RED.with_alpha(0)),
+        ];
+        let fade = Gradient::linear([0.0, 0.0], [64.0, 0.0], &fade);
+        r.queue_rect(0, 0, 64, 8, fade);
+        r.end_frame(&mut fb);
+
+        let px = |x| fb.get_pixel(x, 4).unwrap().into_rgba();
+        // mostly red on the left, the backdrop shows through on the right
+        let left = px(1);
+        assert!(left[0] > 240 && left[2] < 64, "{left:?}");
+        assert_eq!(px(63)[3], 255);
+        assert!(px(63)[2] > 240, "{:?}", px(63));
+        // halfway is a mix of both, not a hole
+        let mid = px(32);
+        assert_eq!(mid[3], 255);
+        assert!(mid[0] > 64 && mid[2] > 64, "{mid:?}");
+    }
+
+    #[test]
+    fn dithering_tracks_a_ramp_between_code_values() {
+        // a 64px ramp across a single code value bands into two flat halves without dithering
+        let stops = [
+            ColorStop::new(0.0, Color::new(100, 100, 100, 255)),
+            ColorStop::new(1.0, Color::new(101, 101, 101, 255)),
+        ];
+        let plain = Gradient::linear([0.0, 0.0], [64.0, 0.0], &stops)
+            .with_interpolation(Interpolation::Srgb);
+        let dithered = plain.clone().with_dither(true);
+
+        let block_error = |g: &Gradient, bx: u16| {
+            let mut sum = 0.0;
+            let mut ideal = 0.0;
+            for y in 0..8 {
+                for x in bx * 8..bx * 8 + 8 {
+                    sum += g.shade(x, y).unwrap()[0] as ColorFloat;
+                    ideal += 100.0 + (x as ColorFloat + 0.5) / 64.0;
+                }
+            }
+            ((sum - ideal) / 64.0).abs()
+        };
+
+        let worst_plain = (0..8)
+            .map(|b| block_error(&plain, b))
+            .fold(0.0, ColorFloat::max);
+        let worst_dithered = (0..8)
+            .map(|b| block_error(&dithered, b))
+            .fold(0.0, ColorFloat::max);
+        assert!(worst_plain > 0.3);
+        assert!(
+            worst_dithered < 0.1,
+            "dithered block error {worst_dithered}"
+        );
+    }
+}
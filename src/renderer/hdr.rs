@@ -0,0 +1,655 @@
+#![allow(dead_code)]
+
+use core::fmt;
+
+use half::f16;
+
+use crate::{
+    color::{ColorFloat, linear::LinearColor, model::Color},
+    renderer::{
+        command::{DrawCmd, PixelRect},
+        effects,
+        frame::Frame,
+        sample,
+    },
+};
+
+/// An error from converting an `HdrFrame` back to 8 bits.
+///
+/// # Variants
+///
+/// - `SizeMismatch` - The target frame isn't the size of the HDR frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HdrError {
+    SizeMismatch {
+        expected: (u16, u16),
+        found: (u16, u16),
+    },
+}
+
+impl fmt::Display for HdrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HdrError::SizeMismatch { expected, found } => write!(
+                f,
+                "tone mapping a {}x{} hdr frame into a {}x{} frame",
+                expected.0, expected.1, found.0, found.1
+            ),
+        }
+    }
+}
+impl std::error::Error for HdrError {}
+
+/// A float channel an `HdrFrame` can store.
+///
+/// `f32` keeps everything, `f16` halves the memory and still reaches 65504 with
+/// about three significant digits, which is plenty for glow buffers.
+pub trait HdrChannel: Copy + Default + Send + Sync + 'static {
+    fn from_f32(v: f32) -> Self;
+    fn to_f32(self) -> f32;
+}
+
+impl HdrChannel for f32 {
+    #[inline]
+    fn from_f32(v: f32) -> Self {
+        v
+    }
+
+    #[inline]
+    fn to_f32(self) -> f32 {
+        self
+    }
+}
+
+impl HdrChannel for f16 {
+    #[inline]
+    fn from_f32(v: f32) -> Self {
+        f16::from_f32(v)
+    }
+
+    #[inline]
+    fn to_f32(self) -> f32 {
+        f16::to_f32(self)
+    }
+}
+
+/// How `HdrFrame::tone_map` squeezes unbounded linear light into 0.0-1.0.
+///
+/// # Variants
+///
+/// - `Clamp` - No curve, everything above 1.0 clips to white.
+/// - `Reinhard` - `c / (1 + c)` per channel. Soft, never quite reaches white.
+/// - `ReinhardExtended` - Reinhard that reaches white at the given linear value.
+/// - `AcesFilmic` - Krzysztof Narkowicz's fit of the ACES filmic curve. Punchy, saturated highlights.
+/// - `Agx` - A fit of Blender's AgX. Bright saturated colors desaturate toward white
+///   the way film does instead of skewing hue.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ToneMap {
+    Clamp,
+    Reinhard,
+    ReinhardExtended {
+        white: f32,
+    },
+    #[default]
+    AcesFilmic,
+    Agx,
+}
+
+impl ToneMap {
+    /// Map linear RGB (any non-negative value) to display linear RGB (0.0-1.0).
+    #[must_use]
+    pub fn apply(self, rgb: [f32; 3]) -> [f32; 3] {
+        let rgb = rgb.map(|c| c.max(0.0));
+        let out = match self {
+            ToneMap::Clamp => rgb,
+            ToneMap::Reinhard => rgb.map(|c| c / (1.0 + c)),
+            ToneMap::ReinhardExtended { white } => {
+                let w2 = (white * white).max(f32::MIN_POSITIVE);
+                rgb.map(|c| c * (1.0 + c / w2) / (1.0 + c))
+            }
+            ToneMap::AcesFilmic => rgb.map(|x| {
+                // source: https://knarkowicz.wordpress.com/2016/01/06/aces-filmic-tone-mapping-curve/
+                (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+            }),
+            ToneMap::Agx => agx(rgb),
+        };
+        out.map(|c| c.clamp(0.0, 1.0))
+    }
+}
+
+// source: https://iolite-engine.com/blog_posts/minimal_agx_implementation
+// (the glsl matrices are column major, these are the same values row major)
+#[rustfmt::skip]
+const AGX_INSET: [[f32; 3]; 3] = [
+    [0.842_479_1, 0.078_433_6, 0.079_223_75],
+    [0.042_328_24, 0.878_468_6, 0.079_166_13],
+    [0.042_375_65, 0.078_433_6, 0.879_143],
+];
+#[rustfmt::skip]
+const AGX_OUTSET: [[f32; 3]; 3] = [
+    [1.196_879, -0.098_020_88, -0.099_029_74],
+    [-0.052_896_85, 1.151_903_1, -0.098_961_18],
+    [-0.052_971_64, -0.098_043_45, 1.151_073_7],
+];
+const AGX_MIN_EV: f32 = -12.473_93;
+const AGX_MAX_EV: f32 = 4.026_069;
+
+fn agx(rgb: [f32; 3]) -> [f32; 3] {
+    let mul =
+        |m: &[[f32; 3]; 3], v: [f32; 3]| m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2]);
+
+    let encoded = mul(&AGX_INSET, rgb).map(|c| {
+        let ev = c.max(1e-10).log2().clamp(AGX_MIN_EV, AGX_MAX_EV);
+        let x = (ev - AGX_MIN_EV) / (AGX_MAX_EV - AGX_MIN_EV);
+
+        // sigmoid contrast curve, the output is roughly gamma 2.2 encoded
+        let (x2, x4) = (x * x, x * x * x * x);
+        15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x
+            - 0.00232
+    });
+    mul(&AGX_OUTSET, encoded).map(|c| c.max(0.0).powf(2.2))
+}
+
+/// A frame of linear light that can go above 1.0, for bloom and glow passes.
+///
+/// Pixels are premultiplied linear sRGB, stored as `f32` or `f16` (`HdrFrame<f16>`).
+/// Get back to an 8 bit `Frame` with `tone_map`.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::renderer::hdr::{HdrFrame, ToneMap};
+/// use codimate::color::linear::LinearColor;
+///
+/// let mut hdr = HdrFrame::<f32>::new(1920, 1080);
+/// hdr.clear(LinearColor::new(0.01, 0.01, 0.02, 1.0));
+/// hdr.set_pixel(960, 540, LinearColor::new(40.0, 30.0, 10.0, 1.0));
+/// hdr.bloom(1.0, 12.0, 0.8);
+/// let out = hdr.tone_map(ToneMap::Agx, 0.0);
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct HdrFrame<T: HdrChannel = f32> {
+    width: u16,
+    height: u16,
+    data: Vec<T>,
+}
+
+// same as Frame, never print the pixels
+impl<T: HdrChannel> std::fmt::Debug for HdrFrame<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HdrFrame")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: HdrChannel> HdrFrame<T> {
+    /// A transparent frame.
+    #[must_use]
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![T::default(); width as usize * height as usize * 4],
+        }
+    }
+
+    /// Decode an 8 bit frame into linear light.
+    #[must_use]
+    pub fn from_frame(fb: &Frame) -> Self {
+        let mut hdr = Self::new(fb.width(), fb.height());
+        hdr.composite(fb, 0, 0, 1.0);
+        hdr
+    }
+
+    #[must_use]
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    #[must_use]
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Premultiplied linear RGBA, four channels per pixel.
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    #[inline]
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        (x < self.width && y < self.height)
+            .then(|| (y as usize * self.width as usize + x as usize) * 4)
+    }
+
+    #[inline]
+    fn premul(&self, i: usize) -> [f32; 4] {
+        [0, 1, 2, 3].map(|c| self.data[i + c].to_f32())
+    }
+
+    #[inline]
+    fn store(&mut self, i: usize, px: [f32; 4]) {
+        for (c, v) in px.into_iter().enumerate() {
+            self.data[i + c] = T::from_f32(v);
+        }
+    }
+
+    /// The straight alpha color at a pixel.
+    #[must_use]
+    pub fn get_pixel(&self, x: u16, y: u16) -> Option<LinearColor> {
+        let [r, g, b, a] = self.premul(self.index(x, y)?);
+        Some(LinearColor::new(r as _, g as _, b as _, a as _).unpremultiply())
+    }
+
+    pub fn set_pixel(&mut self, x: u16, y: u16, color: LinearColor) {
+        if let Some(i) = self.index(x, y) {
+            self.store(i, premul_f32(color));
+        }
+    }
+
+    /// Fill every pixel with one color.
+    pub fn clear(&mut self, color: LinearColor) {
+        let px = premul_f32(color).map(T::from_f32);
+        for dst in self.data.chunks_exact_mut(4) {
+            dst.copy_from_slice(&px);
+        }
+    }
+
+    /// Source-over an 8 bit layer with its top left corner at (x, y).
+    ///
+    /// `intensity` scales the layer's light (not its alpha), so a white layer at
+    /// intensity 4.0 is four times brighter than display white. That's how emissive
+    /// text and glowing strokes get into the frame.
+    pub fn composite(&mut self, src: &Frame, x: i32, y: i32, intensity: f32) {
+        let (fw, fh) = (self.width as i32, self.height as i32);
+        let (sw, sh) = (src.width() as i32, src.height() as i32);
+        let (x0, y0) = (x.max(0), y.max(0));
+        let (x1, y1) = ((x + sw).min(fw), (y + sh).min(fh));
+
+        let src_data = src.as_slice();
+        for dy in y0..y1 {
+            for dx in x0..x1 {
+                let s = (((dy - y) * sw + (dx - x)) * 4) as usize;
+                let p = &src_data[s..s + 4];
+                if p[3] == 0 {
+                    continue;
+                }
+
+                let [r, g, b, a]: [f32; 4] = Color::from_rgba([p[0], p[1], p[2], p[3]])
+                    .into_linear()
+                    .map(|c| c as _);
+                let fg = [r, g, b].map(|c| c * a * intensity);
+
+                let d = ((dy * fw + dx) * 4) as usize;
+                let bg = self.premul(d);
+                let k = 1.0 - a;
+                self.store(
+                    d,
+                    [
+                        fg[0] + bg[0] * k,
+                        fg[1] + bg[1] * k,
+                        fg[2] + bg[2] * k,
+                        a + bg[3] * k,
+                    ],
+                );
+            }
+        }
+    }
+
+    /// Rasterize recorded draw commands straight into the frame.
+    ///
+    /// Every command composites in premultiplied linear float, so overlapping light keeps
+    /// adding up past 1.0 instead of clipping at display white the way it would in an 8 bit
+    /// frame. `intensity` scales the light each command adds (> 1.0 for emissive shapes).
+    pub fn rasterize(&mut self, cmds: &[DrawCmd], intensity: f32) {
+        for cmd in cmds {
+            self.draw_cmd(cmd, intensity, None);
+        }
+    }
+
+    fn draw_cmd(&mut self, cmd: &DrawCmd, intensity: f32, clip: Option<&Coverage>) {
+        let mut area = PixelRect::new(0, 0, self.width, self.height);
+        if let Some(b) = cmd.bounds() {
+            area = area.intersect(b);
+        }
+        if let Some(c) = clip {
+            area = area.intersect(c.area);
+        }
+        if area.is_empty() {
+            return;
+        }
+
+        let replace = matches!(cmd, DrawCmd::Clear(_));
+        match cmd {
+            DrawCmd::Clear(paint)
+            | DrawCmd::Pixel { paint, .. }
+            | DrawCmd::HSpan { paint, .. }
+            | DrawCmd::Rect { paint, .. } => {
+                self.fill(area, intensity, replace, clip, None, |x, y| {
+                    paint.shade(x, y)
+                });
+            }
+            DrawCmd::FillPath { path, paint } => {
+                let cov = path.coverage(area);
+                self.fill(area, intensity, false, clip, Some(&cov), |x, y| {
+                    paint.shade(x, y)
+                });
+            }
+            DrawCmd::Image {
+                image,
+                transform,
+                filter,
+            } => {
+                // textures are 8 bit anyway, so resample into a transparent layer and
+                // composite that
+                let stride = area.width() as usize * 4;
+                let mut layer = vec![0; stride * area.height() as usize];
+                sample::draw_transformed(area, &mut layer, area, image, transform, *filter);
+                self.fill(area, intensity, false, clip, None, |x, y| {
+                    let i = (y - area.y0) as usize * stride + (x - area.x0) as usize * 4;
+                    Some([layer[i], layer[i + 1], layer[i + 2], layer[i + 3]])
+                });
+            }
+            DrawCmd::Clipped { cmd, clip: chain } => {
+                let mut values = chain
+                    .coverage(area)
+                    .unwrap_or_else(|| vec![1.0; area.width() as usize * area.height() as usize]);
+                // nested clips multiply
+                if let Some(outer) = clip {
+                    let w = area.width() as usize;
+                    for y in area.y0..area.y1 {
+                        for x in area.x0..area.x1 {
+                            values[(y - area.y0) as usize * w + (x - area.x0) as usize] *=
+                                outer.at(x, y);
+                        }
+                    }
+                }
+                self.draw_cmd(cmd, intensity, Some(&Coverage { area, values }));
+            }
+        }
+    }
+
+    /// Composite `src` (8 bit straight alpha) over `area`, or replace what's there, mixed
+    /// in by the clip and per pixel coverage (`cov` holds `area` row by row).
+    fn fill(
+        &mut self,
+        area: PixelRect,
+        intensity: f32,
+        replace: bool,
+        clip: Option<&Coverage>,
+        cov: Option<&[f32]>,
+        src: impl Fn(u16, u16) -> Option<[u8; 4]>,
+    ) {
+        let w = area.width() as usize;
+        for y in area.y0..area.y1 {
+            for x in area.x0..area.x1 {
+                let Some(p) = src(x, y) else {
+                    continue;
+                };
+                let c = cov.map_or(1.0, |cov| {
+                    cov[(y - area.y0) as usize * w + (x - area.x0) as usize]
+                }) * clip.map_or(1.0, |clip| clip.at(x, y));
+                if c <= 0.0 || (p[3] == 0 && !replace) {
+                    continue;
+                }
+
+                let [r, g, b, a]: [f32; 4] = Color::from_rgba(p).into_linear().map(|v| v as _);
+                let fg = [r * a * intensity, g * a * intensity, b * a * intensity, a];
+                let i = (y as usize * self.width as usize + x as usize) * 4;
+                let bg = self.premul(i);
+                let k = if replace { 0.0 } else { 1.0 - a };
+                // coverage mixes premultiplied values, so edges don't darken
+                self.store(
+                    i,
+                    [0, 1, 2, 3].map(|n| bg[n] + (fg[n] + bg[n] * k - bg[n]) * c.min(1.0)),
+                );
+            }
+        }
+    }
+
+    /// Add a blurred copy of everything brighter than `threshold` back on top.
+    ///
+    /// # Arguments
+    ///
+    /// - `threshold` (`f32`) - Linear light below this doesn't bloom (1.0 = display white).
+    /// - `sigma` (`f32`) - Gaussian standard deviation of the bloom, in pixels.
+    /// - `strength` (`f32`) - How much of the blurred light is added.
+    pub fn bloom(&mut self, threshold: f32, sigma: f32, strength: f32) {
+        let (w, h) = (self.width as usize, self.height as usize);
+        let mut bright: Vec<ColorFloat> = self
+            .data
+            .chunks_exact(4)
+            .flat_map(|p| [0, 1, 2].map(|c| (p[c].to_f32() - threshold).max(0.0) as ColorFloat))
+            .collect();
+        effects::blur_plane(&mut bright, w, h, 3, sigma as _);
+
+        for (dst, glow) in self.data.chunks_exact_mut(4).zip(bright.chunks_exact(3)) {
+            let mut a = dst[3].to_f32();
+            for c in 0..3 {
+                let add: f32 = glow[c] as _;
+                let add = add * strength;
+                dst[c] = T::from_f32(dst[c].to_f32() + add);
+                // light spilling onto transparent pixels makes them a bit opaque
+                a = a.max(add.min(1.0));
+            }
+            dst[3] = T::from_f32(a);
+        }
+    }
+
+    /// Tone map into a new 8 bit frame.
+    ///
+    /// `exposure` is in stops, so +1.0 doubles the light before the curve.
+    /// Encoding goes through `Color::from_linear` (the lookup table with `srgb_lut`).
+    #[must_use]
+    pub fn tone_map(&self, op: ToneMap, exposure: f32) -> Frame {
+        let mut fb = Frame::new(self.width, self.height);
+        self.encode(&mut fb, op, exposure);
+        fb
+    }
+
+    /// `tone_map` into an existing frame, e.g. one from the FramePool.
+    ///
+    /// # Errors
+    ///
+    /// `HdrError::SizeMismatch` if `fb` isn't the same size as this frame.
+    pub fn tone_map_into(
+        &self,
+        fb: &mut Frame,
+        op: ToneMap,
+        exposure: f32,
+    ) -> Result<(), HdrError> {
+        if (fb.width(), fb.height()) != (self.width, self.height) {
+            return Err(HdrError::SizeMismatch {
+                expected: (self.width, self.height),
+                found: (fb.width(), fb.height()),
+            });
+        }
+        self.encode(fb, op, exposure);
+        Ok(())
+    }
+
+    // fb has to be this frame's size
+    fn encode(&self, fb: &mut Frame, op: ToneMap, exposure: f32) {
+        let scale = exposure.exp2();
+        for (dst, p) in fb
+            .as_bytes_mut()
+            .chunks_exact_mut(4)
+            .zip(self.data.chunks_exact(4))
+        {
+            let a = p[3].to_f32().clamp(0.0, 1.0);
+            if a <= 0.0 {
+                dst.copy_from_slice(&[0; 4]);
+                continue;
+            }
+            let rgb = [0, 1, 2].map(|c| p[c].to_f32() / a * scale);
+            let [r, g, b] = op.apply(rgb);
+            let c = Color::from_linear([r as _, g as _, b as _, a as _]);
+            dst.copy_from_slice(&c.into_rgba());
+        }
+    }
+}
+
+/// Clip coverage over an area, row by row. Everything outside it is hidden.
+struct Coverage {
+    area: PixelRect,
+    values: Vec<f32>,
+}
+
+impl Coverage {
+    #[inline]
+    fn at(&self, x: u16, y: u16) -> f32 {
+        let a = self.area;
+        if x < a.x0 || y < a.y0 || x >= a.x1 || y >= a.y1 {
+            return 0.0;
+        }
+        self.values[(y - a.y0) as usize * a.width() as usize + (x - a.x0) as usize]
+    }
+}
+
+#[inline]
+fn premul_f32(c: LinearColor) -> [f32; 4] {
+    let p = c.premultiply();
+    [p.r as _, p.g as _, p.b as _, p.a as _]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves_are_monotonic_and_bounded() {
+        for op in [
+            ToneMap::Reinhard,
+            ToneMap::ReinhardExtended { white: 4.0 },
+            ToneMap::AcesFilmic,
+            ToneMap::Agx,
+        ] {
+            let mut last = -1.0;
+            for i in 0..200 {
+                let v = i as f32 * 0.1;
+                let [r, g, b] = op.apply([v; 3]);
+                assert!(r >= last - 1e-6 && r <= 1.0, "{op:?} at {v}: {r}");
+                assert!(
+                    (r - g).abs() < 1e-3 && (g - b).abs() < 1e-3,
+                    "{op:?} greys stay grey"
+                );
+                last = r;
+            }
+            assert!(op.apply([0.0; 3])[0] < 0.01, "{op:?} black");
+        }
+
+        assert_eq!(ToneMap::Reinhard.apply([1.0, 3.0, 0.0]), [0.5, 0.75, 0.0]);
+        let white = ToneMap::ReinhardExtended { white: 4.0 }.apply([4.0; 3]);
+        assert!((white[0] - 1.0).abs() < 1e-6);
+        assert!(ToneMap::AcesFilmic.apply([100.0; 3])[0] > 0.99);
+    }
+
+    #[test]
+    fn values_above_one_survive_until_tone_mapping() {
+        let mut hdr = HdrFrame::<f16>::new(2, 1);
+        hdr.set_pixel(0, 0, LinearColor::new(8.0, 2.0, 0.5, 1.0));
+        hdr.set_pixel(1, 0, LinearColor::new(8.0, 2.0, 0.5, 0.5));
+        let p = hdr.get_pixel(0, 0).unwrap();
+        assert_eq!([p.r, p.g, p.b, p.a], [8.0, 2.0, 0.5, 1.0]);
+
+        let clamped = hdr.tone_map(ToneMap::Clamp, 0.0);
+        assert_eq!(
+            clamped.get_pixel(0, 0).unwrap().into_rgba(),
+            [255, 255, 188, 255]
+        );
+        // two stops down brings the blue into range and red still clips
+        let darker = hdr.tone_map(ToneMap::Clamp, -2.0);
+        assert_eq!(darker.get_pixel(0, 0).unwrap().into_rgba()[0], 255);
+        assert!(darker.get_pixel(0, 0).unwrap().into_rgba()[2] < 188);
+        // straight alpha comes back out
+        assert_eq!(
+            clamped.get_pixel(1, 0).unwrap().into_rgba(),
+            [255, 255, 188, 128]
+        );
+    }
+
+    #[test]
+    fn frames_round_trip_through_hdr() {
+        let mut fb = Frame::new(3, 1);
+        fb.as_bytes_mut()
+            .copy_from_slice(&[255, 0, 0, 255, 12, 200, 99, 128, 0, 0, 0, 0]);
+        let hdr = HdrFrame::<f32>::from_frame(&fb);
+        assert_eq!(hdr.tone_map(ToneMap::Clamp, 0.0), fb);
+    }
+
+    #[test]
+    fn bloom_spreads_only_bright_light() {
+        let mut hdr = HdrFrame::<f32>::new(21, 21);
+        hdr.clear(LinearColor::BLACK);
+        hdr.set_pixel(10, 10, LinearColor::new(50.0, 50.0, 50.0, 1.0));
+        hdr.set_pixel(2, 2, LinearColor::new(0.9, 0.9, 0.9, 1.0));
+        hdr.bloom(1.0, 2.0, 1.0);
+
+        let near = hdr.get_pixel(12, 10).unwrap();
+        assert!(near.r > 0.1 && near.r == near.b, "{near:?}");
+        // light below the threshold doesn't glow
+        assert_eq!(hdr.get_pixel(3, 2).unwrap().r, 0.0);
+        // stored as f32, so compare loosely with color_double_precision
+        assert!((hdr.get_pixel(2, 2).unwrap().r - 0.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn emissive_layers_composite_above_white() {
+        let mut layer = Frame::new(1, 1);
+        layer.as_bytes_mut().copy_from_slice(&[255, 255, 255, 255]);
+        let mut hdr = HdrFrame::<f32>::new(2, 1);
+        hdr.composite(&layer, 1, 0, 4.0);
+        assert_eq!(hdr.get_pixel(1, 0).unwrap().r, 4.0);
+        assert_eq!(hdr.get_pixel(0, 0).unwrap().a, 0.0);
+    }
+
+    #[test]
+    fn tone_map_into_checks_the_size() {
+        let hdr = HdrFrame::<f32>::new(4, 2);
+        let mut fb = Frame::new(2, 4);
+        assert_eq!(
+            hdr.tone_map_into(&mut fb, ToneMap::Clamp, 0.0),
+            Err(HdrError::SizeMismatch {
+                expected: (4, 2),
+                found: (2, 4)
+            })
+        );
+        let mut fb = Frame::new(4, 2);
+        assert!(hdr.tone_map_into(&mut fb, ToneMap::Clamp, 0.0).is_ok());
+    }
+
+    #[test]
+    fn queued_draws_composite_in_float() {
+        use crate::renderer::{path::Path, pool::FramePool, render::Renderer};
+
+        let mut r = Renderer::new(FramePool::new(8, 1, 1));
+        let mut hdr = HdrFrame::<f32>::new(8, 1);
+        r.begin_frame();
+        r.queue_clear(Color::BLACK);
+        let half_white = Color::WHITE.with_alpha(128);
+        r.queue_rect(0, 0, 4, 1, half_white);
+        r.queue_rect(0, 0, 4, 1, half_white);
+        r.push_clip(PixelRect::new(6, 0, 8, 1));
+        r.queue_fill_path(Path::rect(0.0, 0.0, 8.0, 1.0), Color::WHITE);
+        r.pop_clip();
+        r.end_frame_hdr(&mut hdr, 4.0);
+
+        // two half covers let through a quarter of the black, without rounding to 8 bits
+        // in between
+        let a: ColorFloat = 128.0 / 255.0;
+        let expected = 4.0 * (1.0 - (1.0 - a) * (1.0 - a));
+        let p = hdr.get_pixel(1, 0).unwrap();
+        assert!((p.r - expected).abs() < 1e-4, "{p:?} vs {expected}");
+        assert_eq!(p.a, 1.0);
+        // the path only shows through its clip, and goes past display white
+        assert_eq!(hdr.get_pixel(5, 0).unwrap().r, 0.0);
+        assert_eq!(hdr.get_pixel(7, 0).unwrap().r, 4.0);
+    }
+}
@@ -1,6 +1,27 @@
+pub mod camera;
+pub mod clip;
+pub mod command;
+pub mod dither;
+pub mod easing;
+pub mod effects;
+pub mod export;
 pub mod frame;
+pub mod gradient;
+pub mod hdr;
+pub mod paint;
+pub mod path;
+pub mod pool;
 pub mod render; // (frame may end up in here tbh)
+pub mod sample;
+pub mod scene;
+#[cfg(feature = "scene_file")]
+pub mod scene_file;
+pub mod text;
+pub mod tile;
+pub mod transform;
+#[cfg(feature = "scene_file")]
+pub mod watch;
 
 // planned modules
 // pub mod shapes;
-// pub mod conversions; (codec conversions)
\ No newline at end of file
+// pub mod conversions; (codec conversions)
@@ -0,0 +1,59 @@
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use crate::{color::model::Color, renderer::gradient::Gradient};
+
+/// What a shape gets filled with.
+///
+/// Gradients sit behind an `Arc` so recorded commands stay cheap to clone.
+///
+/// # Variants
+///
+/// - `Solid` - One flat color.
+/// - `Gradient` - A linear, radial, two-point conical or conic gradient.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Paint {
+    Solid(Color),
+    Gradient(Arc<Gradient>),
+}
+
+impl Paint {
+    /// Get the 8-bit color the paint puts at a pixel (`None` leaves the pixel untouched).
+    #[must_use]
+    #[inline]
+    pub fn shade(&self, x: u16, y: u16) -> Option<[u8; 4]> {
+        match self {
+            Paint::Solid(c) => Some(c.into_rgba()),
+            Paint::Gradient(g) => g.shade(x, y),
+        }
+    }
+
+    /// The color of a solid paint, so kernels can take their fast path.
+    #[must_use]
+    #[inline]
+    pub fn as_solid(&self) -> Option<Color> {
+        match self {
+            Paint::Solid(c) => Some(*c),
+            Paint::Gradient(_) => None,
+        }
+    }
+}
+
+impl From<Color> for Paint {
+    fn from(value: Color) -> Self {
+        Paint::Solid(value)
+    }
+}
+
+impl From<Gradient> for Paint {
+    fn from(value: Gradient) -> Self {
+        Paint::Gradient(Arc::new(value))
+    }
+}
+
+impl From<Arc<Gradient>> for Paint {
+    fn from(value: Arc<Gradient>) -> Self {
+        Paint::Gradient(value)
+    }
+}
@@ -0,0 +1,468 @@
+#![allow(dead_code)]
+
+use std::sync::OnceLock;
+
+use ab_glyph_rasterizer::{Rasterizer, point};
+
+use crate::renderer::{command::PixelRect, transform::Transform2D};
+
+/// How far (in pixels) a flattened curve may stray from the real one.
+const FLATTEN_TOLERANCE: f32 = 0.1;
+
+/// Rows per cached coverage band. Bands start at multiples of this in device space,
+/// so every tile reads coverage computed over the exact same region.
+const COVERAGE_BAND: u16 = 64;
+
+/// One element of a path outline.
+///
+/// # Variants
+///
+/// - `MoveTo` - Start a new subpath at a point.
+/// - `LineTo` - A straight line to a point.
+/// - `QuadTo` - A quadratic Bézier curve through a control point to a point.
+/// - `CubicTo` - A cubic Bézier curve through two control points to a point.
+/// - `Close` - A straight line back to the start of the subpath.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathEl {
+    MoveTo([f32; 2]),
+    LineTo([f32; 2]),
+    QuadTo([f32; 2], [f32; 2]),
+    CubicTo([f32; 2], [f32; 2], [f32; 2]),
+    Close,
+}
+
+/// A vector outline in device pixels, filled with the nonzero rule.
+///
+/// Open subpaths are closed implicitly when filled.
+/// Coverage is rasterized lazily in fixed row bands and cached on the path, so a path
+/// shared by many tiles is only rasterized once and every tile sees identical values.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::renderer::path::Path;
+///
+/// let mut tri = Path::new();
+/// tri.move_to([10.0, 10.0]).line_to([90.0, 10.0]).line_to([50.0, 80.0]).close();
+///
+/// let window = Path::rounded_rect(100.0, 100.0, 640.0, 360.0, 16.0);
+/// ```
+#[derive(Default)]
+pub struct Path {
+    els: Vec<PathEl>,
+    bands: OnceLock<Vec<OnceLock<Vec<f32>>>>,
+}
+
+impl Path {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn elements(&self) -> &[PathEl] {
+        &self.els
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.els.is_empty()
+    }
+
+    pub fn move_to(&mut self, p: [f32; 2]) -> &mut Self {
+        self.push(PathEl::MoveTo(p))
+    }
+
+    pub fn line_to(&mut self, p: [f32; 2]) -> &mut Self {
+        self.push(PathEl::LineTo(p))
+    }
+
+    pub fn quad_to(&mut self, c: [f32; 2], p: [f32; 2]) -> &mut Self {
+        self.push(PathEl::QuadTo(c, p))
+    }
+
+    pub fn cubic_to(&mut self, c1: [f32; 2], c2: [f32; 2], p: [f32; 2]) -> &mut Self {
+        self.push(PathEl::CubicTo(c1, c2, p))
+    }
+
+    pub fn close(&mut self) -> &mut Self {
+        self.push(PathEl::Close)
+    }
+
+    /// Append an element, dropping any cached coverage.
+    pub fn push(&mut self, el: PathEl) -> &mut Self {
+        self.els.push(el);
+        self.bands = OnceLock::new();
+        self
+    }
+
+    /// A rectangle outline.
+    #[must_use]
+    pub fn rect(x: f32, y: f32, width: f32, height: f32) -> Self {
+        let mut p = Self::new();
+        p.move_to([x, y])
+            .line_to([x + width, y])
+            .line_to([x + width, y + height])
+            .line_to([x, y + height])
+            .close();
+        p
+    }
+
+    /// A rectangle with circular corners (the radius is capped at half the shorter side).
+    #[must_use]
+    pub fn rounded_rect(x: f32, y: f32, width: f32, height: f32, radius: f32) -> Self {
+        let r = radius.clamp(0.0, width.min(height) / 2.0);
+        if r <= 0.0 {
+            return Self::rect(x, y, width, height);
+        }
+
+        // cubic circle approximation constant
+        let k = r * (1.0 - 0.552_284_8);
+        let (x1, y1) = (x + width, y + height);
+
+        let mut p = Self::new();
+        p.move_to([x + r, y])
+            .line_to([x1 - r, y])
+            .cubic_to([x1 - k, y], [x1, y + k], [x1, y + r])
+            .line_to([x1, y1 - r])
+            .cubic_to([x1, y1 - k], [x1 - k, y1], [x1 - r, y1])
+            .line_to([x + r, y1])
+            .cubic_to([x + k, y1], [x, y1 - k], [x, y1 - r])
+            .line_to([x, y + r])
+            .cubic_to([x, y + k], [x + k, y], [x + r, y])
+            .close();
+        p
+    }
+
+    /// An axis aligned ellipse.
+    #[must_use]
+    pub fn ellipse(cx: f32, cy: f32, rx: f32, ry: f32) -> Self {
+        let (kx, ky) = (rx * 0.552_284_8, ry * 0.552_284_8);
+
+        let mut p = Self::new();
+        p.move_to([cx + rx, cy])
+            .cubic_to([cx + rx, cy + ky], [cx + kx, cy + ry], [cx, cy + ry])
+            .cubic_to([cx - kx, cy + ry], [cx - rx, cy + ky], [cx - rx, cy])
+            .cubic_to([cx - rx, cy - ky], [cx - kx, cy - ry], [cx, cy - ry])
+            .cubic_to([cx + kx, cy - ry], [cx + rx, cy - ky], [cx + rx, cy])
+            .close();
+        p
+    }
+
+    #[must_use]
+    pub fn circle(cx: f32, cy: f32, r: f32) -> Self {
+        Self::ellipse(cx, cy, r, r)
+    }
+
+    /// A copy of the path with every point mapped through a transform.
+    ///
+    /// Affine maps keep Béziers Béziers, so only points move and the result is
+    /// rasterized fresh at its new size instead of being stretched.
+    #[must_use]
+    pub fn transformed(&self, t: &Transform2D) -> Self {
+        let els = self
+            .els
+            .iter()
+            .map(|el| match *el {
+                PathEl::MoveTo(p) => PathEl::MoveTo(t.apply(p)),
+                PathEl::LineTo(p) => PathEl::LineTo(t.apply(p)),
+                PathEl::QuadTo(c, p) => PathEl::QuadTo(t.apply(c), t.apply(p)),
+                PathEl::CubicTo(c1, c2, p) => PathEl::CubicTo(t.apply(c1), t.apply(c2), t.apply(p)),
+                PathEl::Close => PathEl::Close,
+            })
+            .collect();
+        Self {
+            els,
+            bands: OnceLock::new(),
+        }
+    }
+
+    /// The bounding box of every point (control points included) as `[x0, y0, x1, y1]`.
+    #[must_use]
+    pub fn bounds(&self) -> Option<[f32; 4]> {
+        let mut b: Option<[f32; 4]> = None;
+        let mut add = |p: [f32; 2]| {
+            let r = b.get_or_insert([p[0], p[1], p[0], p[1]]);
+            r[0] = r[0].min(p[0]);
+            r[1] = r[1].min(p[1]);
+            r[2] = r[2].max(p[0]);
+            r[3] = r[3].max(p[1]);
+        };
+
+        for el in &self.els {
+            match *el {
+                PathEl::MoveTo(p) | PathEl::LineTo(p) => add(p),
+                PathEl::QuadTo(c, p) => {
+                    add(c);
+                    add(p);
+                }
+                PathEl::CubicTo(c1, c2, p) => {
+                    add(c1);
+                    add(c2);
+                    add(p);
+                }
+                PathEl::Close => {}
+            }
+        }
+        b
+    }
+
+    /// The device pixels the path can touch, clamped to the `u16` device space.
+    #[must_use]
+    pub fn pixel_bounds(&self) -> Option<PixelRect> {
+        let [x0, y0, x1, y1] = self.bounds()?;
+        let clamp = |v: f32| v.clamp(0.0, u16::MAX as f32) as u16;
+        let r = PixelRect::new(
+            clamp(x0.floor()),
+            clamp(y0.floor()),
+            clamp(x1.ceil()),
+            clamp(y1.ceil()),
+        );
+        (!r.is_empty()).then_some(r)
+    }
+
+    /// Flatten the outline into closed polylines, as a list of line segments.
+    #[must_use]
+    pub fn flatten(&self) -> Vec<([f32; 2], [f32; 2])> {
+        let mut lines = Vec::new();
+        let mut start = [0.0; 2];
+        let mut cur = [0.0; 2];
+
+        for el in &self.els {
+            match *el {
+                PathEl::MoveTo(p) => {
+                    if cur != start {
+                        lines.push((cur, start));
+                    }
+                    start = p;
+                    cur = p;
+                }
+                PathEl::LineTo(p) => {
+                    lines.push((cur, p));
+                    cur = p;
+                }
+                PathEl::QuadTo(c, p) => {
+                    let n = segments(dist(cur, c) + dist(c, p));
+                    let mut prev = cur;
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let mt = 1.0 - t;
+                        let q = [
+                            mt * mt * cur[0] + 2.0 * mt * t * c[0] + t * t * p[0],
+                            mt * mt * cur[1] + 2.0 * mt * t * c[1] + t * t * p[1],
+                        ];
+                        lines.push((prev, q));
+                        prev = q;
+                    }
+                    cur = p;
+                }
+                PathEl::CubicTo(c1, c2, p) => {
+                    let n = segments(dist(cur, c1) + dist(c1, c2) + dist(c2, p));
+                    let mut prev = cur;
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let mt = 1.0 - t;
+                        let (a, b, c, d) =
+                            (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+                        let q = [
+                            a * cur[0] + b * c1[0] + c * c2[0] + d * p[0],
+                            a * cur[1] + b * c1[1] + c * c2[1] + d * p[1],
+                        ];
+                        lines.push((prev, q));
+                        prev = q;
+                    }
+                    cur = p;
+                }
+                PathEl::Close => {
+                    if cur != start {
+                        lines.push((cur, start));
+                    }
+                    cur = start;
+                }
+            }
+        }
+        if cur != start {
+            lines.push((cur, start));
+        }
+        lines
+    }
+
+    /// Get the path's anti-aliased coverage (0.0-1.0) over a device area.
+    ///
+    /// The result holds one value per pixel of `area`, row by row.
+    #[must_use]
+    pub fn coverage(&self, area: PixelRect) -> Vec<f32> {
+        let w = area.width() as usize;
+        let mut out = vec![0.0; w * area.height() as usize];
+        let Some(b) = self.pixel_bounds() else {
+            return out;
+        };
+        let hit = area.intersect(b);
+        if hit.is_empty() {
+            return out;
+        }
+
+        let first = b.y0 / COVERAGE_BAND;
+        let bands = self.bands.get_or_init(|| {
+            let last = (b.y1 - 1) / COVERAGE_BAND;
+            (first..=last).map(|_| OnceLock::new()).collect()
+        });
+
+        for band in hit.y0 / COVERAGE_BAND..=(hit.y1 - 1) / COVERAGE_BAND {
+            let region = PixelRect::new(
+                b.x0,
+                (band * COVERAGE_BAND).max(b.y0),
+                b.x1,
+                band.saturating_add(1)
+                    .saturating_mul(COVERAGE_BAND)
+                    .min(b.y1),
+            );
+            let cov = bands[(band - first) as usize].get_or_init(|| self.rasterize(region));
+
+            let part = region.intersect(hit);
+            let rw = region.width() as usize;
+            for y in part.y0..part.y1 {
+                let src = (y - region.y0) as usize * rw + (part.x0 - region.x0) as usize;
+                let dst = (y - area.y0) as usize * w + (part.x0 - area.x0) as usize;
+                let len = part.width() as usize;
+                out[dst..dst + len].copy_from_slice(&cov[src..src + len]);
+            }
+        }
+        out
+    }
+
+    /// Rasterize coverage over a region from scratch.
+    fn rasterize(&self, region: PixelRect) -> Vec<f32> {
+        let (w, h) = (region.width() as usize, region.height() as usize);
+        let mut out = vec![0.0; w * h];
+        if w == 0 || h == 0 {
+            return out;
+        }
+
+        // one guard column on each side: geometry left or right of the region gets squashed
+        // onto them, which keeps its winding without leaking into neighboring rows
+        let rw = w + 2;
+        let mut r = Rasterizer::new(rw, h);
+        let (ox, oy) = (region.x0 as f32 - 1.0, region.y0 as f32);
+        let max_x = (rw - 1) as f32;
+
+        for (a, b) in self.flatten() {
+            let a = [a[0] - ox, a[1] - oy];
+            let b = [b[0] - ox, b[1] - oy];
+            for (a, b) in clip_x(a, b, 0.0, max_x) {
+                r.draw_line(point(a[0], a[1]), point(b[0], b[1]));
+            }
+        }
+
+        r.for_each_pixel(|i, c| {
+            let (x, y) = (i % rw, i / rw);
+            if x >= 1 && x <= w {
+                out[y * w + x - 1] = c.min(1.0);
+            }
+        });
+        out
+    }
+}
+
+// the coverage cache is an implementation detail, leave it out of clones/comparisons/debug output
+
+impl Clone for Path {
+    fn clone(&self) -> Self {
+        Self {
+            els: self.els.clone(),
+            bands: OnceLock::new(),
+        }
+    }
+}
+
+impl PartialEq for Path {
+    fn eq(&self, other: &Self) -> bool {
+        self.els == other.els
+    }
+}
+
+impl core::fmt::Debug for Path {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Path").field("els", &self.els).finish()
+    }
+}
+
+#[inline]
+fn dist(a: [f32; 2], b: [f32; 2]) -> f32 {
+    ((b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2)).sqrt()
+}
+
+/// How many lines to split a curve with this control polygon length into.
+#[inline]
+fn segments(len: f32) -> usize {
+    ((len / (8.0 * FLATTEN_TOLERANCE)).sqrt().ceil() as usize).clamp(1, 256)
+}
+
+/// Split a line at `lo` and `hi` on the x axis and squash the outside pieces onto those edges.
+fn clip_x(a: [f32; 2], b: [f32; 2], lo: f32, hi: f32) -> Vec<([f32; 2], [f32; 2])> {
+    let mut cuts = vec![0.0, 1.0];
+    if a[0] != b[0] {
+        for edge in [lo, hi] {
+            let t = (edge - a[0]) / (b[0] - a[0]);
+            if t > 0.0 && t < 1.0 {
+                cuts.push(t);
+            }
+        }
+    }
+    cuts.sort_by(f32::total_cmp);
+
+    let at = |t: f32| {
+        [
+            (a[0] + (b[0] - a[0]) * t).clamp(lo, hi),
+            a[1] + (b[1] - a[1]) * t,
+        ]
+    };
+    cuts.windows(2).map(|w| (at(w[0]), at(w[1]))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total(cov: &[f32]) -> f32 {
+        cov.iter().sum()
+    }
+
+    #[test]
+    fn rect_coverage_is_its_area() {
+        let p = Path::rect(2.5, 3.0, 10.0, 4.0);
+        let cov = p.coverage(PixelRect::new(0, 0, 20, 20));
+        assert!((total(&cov) - 40.0).abs() < 1e-3);
+        // half covered left column
+        assert!((cov[4 * 20 + 2] - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn coverage_is_the_same_when_split_into_tiles() {
+        let p = Path::circle(32.0, 32.0, 20.0);
+        let whole = p.coverage(PixelRect::new(0, 0, 64, 64));
+
+        for (x0, y0) in [(0, 0), (16, 16), (32, 0), (48, 40)] {
+            let area = PixelRect::new(x0, y0, x0 + 16, y0 + 16);
+            let part = p.coverage(area);
+            for y in 0..16usize {
+                for x in 0..16usize {
+                    let a = whole[(y + y0 as usize) * 64 + x + x0 as usize];
+                    let b = part[y * 16 + x];
+                    assert!(
+                        (a - b).abs() < 1e-4,
+                        "tile {x0},{y0} pixel {x},{y}: {a} vs {b}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn circle_area_is_close() {
+        let p = Path::circle(50.0, 50.0, 30.0);
+        let cov = p.coverage(PixelRect::new(0, 0, 100, 100));
+        let expected = core::f32::consts::PI * 30.0 * 30.0;
+        assert!((total(&cov) - expected).abs() / expected < 0.005);
+    }
+}
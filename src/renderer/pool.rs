@@ -0,0 +1,182 @@
+#![allow(dead_code)]
+
+use core::fmt;
+use std::collections::VecDeque;
+
+use crate::renderer::frame::Frame;
+
+/// An error caused by misusing a `FramePool`.
+///
+/// # Variants
+///
+/// - `Exhausted` - Every frame the pool may allocate is currently handed out.
+///   Release a frame (usually once a sink is done with it) before acquiring another.
+/// - `SizeMismatch` - A released frame doesn't have the pool's resolution.
+/// - `Foreign` - A released frame isn't one of the frames the pool has handed out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolError {
+    Exhausted {
+        capacity: usize,
+    },
+    SizeMismatch {
+        expected: (u16, u16),
+        found: (u16, u16),
+    },
+    Foreign,
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use PoolError::*;
+        match self {
+            Exhausted { capacity } => {
+                write!(f, "frame pool exhausted (all {capacity} frames in flight)")
+            }
+            SizeMismatch { expected, found } => write!(
+                f,
+                "released frame is {}x{} but the pool holds {}x{} frames",
+                found.0, found.1, expected.0, expected.1
+            ),
+            Foreign => f.write_str("released a frame the pool never handed out"),
+        }
+    }
+}
+impl std::error::Error for PoolError {}
+
+/// A fixed-capacity pool of same-sized frames that recycles their allocations.
+///
+/// Frames are allocated lazily up to `capacity`, handed out by `FramePool::acquire`
+/// and come back through `FramePool::release` once a sink has consumed them.
+/// Released frames are reused oldest first, like a ring buffer.
+/// Recycled frames keep their old pixels, so clear them before drawing.
+pub struct FramePool {
+    width: u16,
+    height: u16,
+    capacity: usize,
+    free: VecDeque<Frame>,
+    // where the pixels of each frame handed out live, so releases can tell our frames
+    // from ones that just happen to be the same size
+    in_flight: Vec<usize>,
+}
+
+impl FramePool {
+    /// Create an empty pool that will allocate at most `capacity` frames.
+    pub fn new(width: u16, height: u16, capacity: usize) -> Self {
+        Self {
+            width,
+            height,
+            capacity,
+            free: VecDeque::with_capacity(capacity),
+            in_flight: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// number of frames handed out and not released yet
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// number of frames that can be acquired before the pool runs dry
+    pub fn available(&self) -> usize {
+        self.capacity - self.in_flight.len()
+    }
+
+    /// Hand out a frame to render into, reusing a released one when possible.
+    pub fn acquire(&mut self) -> Result<Frame, PoolError> {
+        if self.in_flight.len() >= self.capacity {
+            return Err(PoolError::Exhausted {
+                capacity: self.capacity,
+            });
+        }
+
+        let frame = match self.free.pop_front() {
+            Some(frame) => frame,
+            None => Frame::new(self.width, self.height),
+        };
+        self.in_flight.push(address(&frame));
+        Ok(frame)
+    }
+
+    /// Give a frame back so its allocation can be reused.
+    pub fn release(&mut self, frame: Frame) -> Result<(), PoolError> {
+        if (frame.width(), frame.height()) != (self.width, self.height) {
+            return Err(PoolError::SizeMismatch {
+                expected: (self.width, self.height),
+                found: (frame.width(), frame.height()),
+            });
+        }
+        let Some(i) = self.in_flight.iter().position(|&a| a == address(&frame)) else {
+            return Err(PoolError::Foreign);
+        };
+
+        self.in_flight.swap_remove(i);
+        self.free.push_back(frame);
+        Ok(())
+    }
+}
+
+// frames can't grow or shrink, so a frame keeps its allocation for as long as it lives
+fn address(frame: &Frame) -> usize {
+    frame.as_slice().as_ptr() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_when_exhausted_instead_of_stalling() {
+        let mut pool = FramePool::new(4, 4, 2);
+        let a = pool.acquire().unwrap();
+        let _b = pool.acquire().unwrap();
+        assert_eq!(
+            pool.acquire().err(),
+            Some(PoolError::Exhausted { capacity: 2 })
+        );
+
+        pool.release(a).unwrap();
+        assert!(pool.acquire().is_ok());
+    }
+
+    #[test]
+    fn recycles_allocations() {
+        let mut pool = FramePool::new(8, 8, 1);
+        let frame = pool.acquire().unwrap();
+        let ptr = frame.as_slice().as_ptr();
+        pool.release(frame).unwrap();
+
+        assert_eq!(pool.acquire().unwrap().as_slice().as_ptr(), ptr);
+    }
+
+    #[test]
+    fn rejects_frames_it_does_not_own() {
+        let mut pool = FramePool::new(8, 8, 2);
+        assert_eq!(pool.release(Frame::new(8, 8)), Err(PoolError::Foreign));
+
+        let held = pool.acquire().unwrap();
+        // one of ours being out doesn't make any frame of the right size ours
+        assert_eq!(pool.release(Frame::new(8, 8)), Err(PoolError::Foreign));
+        assert_eq!(
+            pool.release(Frame::new(4, 4)),
+            Err(PoolError::SizeMismatch {
+                expected: (8, 8),
+                found: (4, 4)
+            })
+        );
+        assert_eq!(pool.in_flight(), 1);
+        pool.release(held).unwrap();
+        assert_eq!(pool.in_flight(), 0);
+    }
+}
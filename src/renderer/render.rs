@@ -2,37 +2,83 @@
 
 /* ik this is jank so i'm gonna figure out how to modularize so 
 every submodule that's a part of src/folder is prefixed by crate::folder */
-use crate::{color::model::Color, renderer::frame::Frame};
+use std::sync::Arc;
+
+use ab_glyph::Font;
+
+use crate::{
+    color::model::Color,
+    renderer::{
+        camera::Camera,
+        clip::{Clip, ClipChain, Mask},
+        command::{DrawCmd, PixelRect},
+        effects::{self, Effect},
+        frame::Frame,
+        hdr::{HdrChannel, HdrFrame},
+        paint::Paint,
+        path::Path,
+        pool::{FramePool, PoolError},
+        sample::{self, Filter, Texture},
+        text,
+        tile::{self, Tiling},
+        transform::Transform2D,
+    },
+};
 
 pub struct Renderer {
-    current: usize,
-    queue: Vec<Frame>,
+    pool: FramePool,
+    commands: Vec<DrawCmd>,
+    tiling: Tiling,
+    clips: Vec<Clip>,
+    // snapshot of `clips` shared by every command queued under it
+    clip_chain: Option<Arc<ClipChain>>,
+    // world space -> device space is camera.view() * transform
+    transform: Transform2D,
+    saved_transforms: Vec<Transform2D>,
+    camera: Option<Camera>,
 }
 
 impl Renderer {
-    // doing queue based on index now
-    pub fn new(queue: Vec<Frame>) -> Self {
+    // frames come out of the pool, get rendered into, go to a sink and come back via recycle()
+    pub fn new(pool: FramePool) -> Self {
         Self {
-            current: 0,
-            queue,
+            pool,
+            commands: Vec::new(),
+            tiling: Tiling::default(),
+            clips: Vec::new(),
+            clip_chain: None,
+            transform: Transform2D::IDENTITY,
+            saved_transforms: Vec::new(),
+            camera: None,
         }
     }
 
-    /// borrows the current frame and allows us to fuck w it
-    pub fn current(&mut self) -> &Frame {
-        &mut self.queue[self.current]
+    pub fn pool(&self) -> &FramePool {
+        &self.pool
     }
 
-    /// moves to the next frame in the queue
-    pub fn advance(&mut self) {
-        // will prolly make this throw an error soon
-        if self.current + 1 < self.queue.len() {return}
-        self.current += 1;
+    /// grabs a frame to render into. errors out if every frame is still sitting in a sink
+    pub fn acquire(&mut self) -> Result<Frame, PoolError> {
+        self.pool.acquire()
+    }
+
+    /// hands a frame back once the sink is done with it so its allocation gets reused
+    pub fn recycle(&mut self, frame: Frame) -> Result<(), PoolError> {
+        self.pool.release(frame)
+    }
+
+    /// how end_frame splits up the work (tiled across all cores by default)
+    pub fn tiling(&self) -> Tiling {
+        self.tiling
+    }
+
+    pub fn set_tiling(&mut self, tiling: Tiling) {
+        self.tiling = tiling;
     }
 
     /// shitty chunking approach that we have to use frn cuz i'm too lazy to make this on u32. we'll deal w this later
     pub fn clear(&mut self, fb: &mut Frame, color: Color) {
-        let packed = [color.r, color.g, color.b, color.a];
+        let packed = color.into_rgba();
         
         for chunk in fb.as_bytes_mut().chunks_exact_mut(4) {
             chunk.copy_from_slice(&packed);
@@ -55,10 +101,7 @@ impl Renderer {
         let offset = (y * w + x) * 4;
         let data = fb.as_bytes_mut();
 
-        data[offset] = color.r;
-        data[offset + 1] = color.g;
-        data[offset + 2] = color.b;
-        data[offset + 3] = color.a;
+        data[offset..offset + 4].copy_from_slice(&color.into_rgba());
     }
 
     /// plot the span of one row from x0 to x1
@@ -89,25 +132,276 @@ impl Renderer {
 
         // pack 4 bytes at a time
         for chunk in row_slice.chunks_exact_mut(4) {
-            chunk.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+            chunk.copy_from_slice(&color.into_rgba());
         }
     }
 
     /// solid rectangle fill. legit just hspan for row in rows
     pub fn rect(&mut self, fb: &mut Frame, x: u16, y: u16, width: u16, height: u16, color: Color) {
-        for _ in y..height {
-            self.hspan(fb, y, x, width, color);
+        let x1 = x.saturating_add(width).min(fb.width());
+        for row in y..y.saturating_add(height) {
+            self.hspan(fb, row, x, x1, color);
+        }
+    }
+
+    /// source-over blit of a whole frame with its top left corner at (x, y). blends in linear space
+    pub fn blit_over(&mut self, fb: &mut Frame, src: &Frame, x: i32, y: i32) {
+        let (fw, fh) = (fb.width() as i32, fb.height() as i32);
+        let (sw, sh) = (src.width() as i32, src.height() as i32);
+
+        // clip the source against the frame once instead of per pixel
+        let (x0, y0) = (x.max(0), y.max(0));
+        let (x1, y1) = ((x + sw).min(fw), (y + sh).min(fh));
+        if x0 >= x1 || y0 >= y1 {
+            return;
+        }
+
+        let src_data = src.as_slice();
+        let dst_data = fb.as_bytes_mut();
+        for dy in y0..y1 {
+            for dx in x0..x1 {
+                let s = (((dy - y) * sw + (dx - x)) * 4) as usize;
+                let d = ((dy * fw + dx) * 4) as usize;
+                let sp = &src_data[s..s + 4];
+
+                // a == 0 skip, a == 255 copy, everything else blends
+                match sp[3] {
+                    0 => {}
+                    255 => dst_data[d..d + 4].copy_from_slice(sp),
+                    _ => {
+                        let fg = Color::from_rgba([sp[0], sp[1], sp[2], sp[3]]);
+                        let bg = Color::from_rgba([
+                            dst_data[d],
+                            dst_data[d + 1],
+                            dst_data[d + 2],
+                            dst_data[d + 3],
+                        ]);
+                        dst_data[d..d + 4].copy_from_slice(&fg.over(bg).into_rgba());
+                    }
+                }
+            }
         }
     }
 
-    /// will be used for the draw queue
+    /// source-over blit of an image through a transform (image px -> device px), resampled with
+    /// filter. immediate so it ignores the camera, use queue_image for world space
+    pub fn blit_transformed(
+        &mut self,
+        fb: &mut Frame,
+        src: &Texture,
+        transform: Transform2D,
+        filter: Filter,
+    ) {
+        let full = PixelRect::new(0, 0, fb.width(), fb.height());
+        let rect = sample::transformed_bounds(src, &transform, filter).intersect(full);
+        if rect.is_empty() {
+            return;
+        }
+        sample::draw_transformed(full, fb.as_bytes_mut(), rect, src, &transform, filter);
+    }
+
+    /// draws a node's rendered layer at (x, y) with its shadows/glows around it.
+    /// immediate only for now, blurs reach across tiles so these don't go in the draw queue
+    pub fn draw_layer(
+        &mut self,
+        fb: &mut Frame,
+        layer: &Frame,
+        x: i32,
+        y: i32,
+        effects: &[Effect],
+    ) {
+        if effects.is_empty() {
+            self.blit_over(fb, layer, x, y);
+            return;
+        }
+
+        let (composited, margin) = effects::apply_effects(layer, effects);
+        self.blit_over(fb, &composited, x - margin as i32, y - margin as i32);
+    }
+
+    /// starts recording a new draw queue, dropping anything left over from the last frame.
+    /// the transform goes back to identity, the camera sticks around
     pub fn begin_frame(&mut self) {
-        todo!("[NOT IMPLEMENTED] waiting on implementation.");
+        self.commands.clear();
+        self.clips.clear();
+        self.clip_chain = None;
+        self.transform = Transform2D::IDENTITY;
+        self.saved_transforms.clear();
+    }
+
+    /// the current object -> world transform
+    pub fn transform(&self) -> Transform2D {
+        self.transform
+    }
+
+    pub fn set_transform(&mut self, transform: Transform2D) {
+        self.transform = transform;
+    }
+
+    /// saves the current transform and applies `transform` in front of it
+    /// (so it acts in the local space of whatever's already pushed)
+    pub fn push_transform(&mut self, transform: Transform2D) {
+        self.saved_transforms.push(self.transform);
+        self.transform = self.transform * transform;
+    }
+
+    /// restores whatever transform was active before the matching push_transform()
+    pub fn pop_transform(&mut self) -> Option<Transform2D> {
+        let saved = self.saved_transforms.pop()?;
+        Some(std::mem::replace(&mut self.transform, saved))
+    }
+
+    pub fn camera(&self) -> Option<&Camera> {
+        self.camera.as_ref()
+    }
+
+    /// looks at the world through a camera (None = world space is device space).
+    /// animate it by setting a new one (e.g. CameraTrack::sample) before each frame
+    pub fn set_camera(&mut self, camera: Option<Camera>) {
+        self.camera = camera;
+    }
+
+    /// everything queued in world space (paths, text, images, path clips) goes through this.
+    /// pixel rects/spans/clears stay in device space
+    pub fn view_transform(&self) -> Transform2D {
+        match &self.camera {
+            Some(cam) => cam.view() * self.transform,
+            None => self.transform,
+        }
+    }
+
+    // paths are geometry so transforming them is exact, zooming in just rasterizes more detail
+    fn to_device(&self, path: Arc<Path>) -> Arc<Path> {
+        let view = self.view_transform();
+        if view.is_identity() {
+            path
+        } else {
+            Arc::new(path.transformed(&view))
+        }
+    }
+
+    /// record a draw command. nothing touches pixels until end_frame.
+    /// whatever clips/masks are on the stack right now get applied to it
+    pub fn push(&mut self, cmd: DrawCmd) {
+        let cmd = match &self.clip_chain {
+            Some(clip) => DrawCmd::Clipped {
+                cmd: Box::new(cmd),
+                clip: Arc::clone(clip),
+            },
+            None => cmd,
+        };
+        self.commands.push(cmd);
+    }
+
+    /// nests a clip (rect, path or mask) inside the current ones until the matching pop_clip().
+    /// only the draw queue respects clips, the immediate kernels above don't
+    pub fn push_clip(&mut self, clip: impl Into<Clip>) {
+        let clip = match clip.into() {
+            Clip::Path(path) => Clip::Path(self.to_device(path)),
+            clip => clip,
+        };
+        self.clips.push(clip);
+        self.rebuild_clip_chain();
+    }
+
+    /// shorthand for push_clip(Clip::Mask(..))
+    pub fn push_mask(&mut self, mask: Mask) {
+        self.push_clip(mask);
+    }
+
+    /// removes the innermost clip
+    pub fn pop_clip(&mut self) -> Option<Clip> {
+        let clip = self.clips.pop();
+        self.rebuild_clip_chain();
+        clip
+    }
+
+    pub fn clip_depth(&self) -> usize {
+        self.clips.len()
+    }
+
+    fn rebuild_clip_chain(&mut self) {
+        self.clip_chain =
+            (!self.clips.is_empty()).then(|| Arc::new(ClipChain::new(self.clips.clone())));
+    }
+
+    /// recorded anti-aliased path fill, through the current transform and camera
+    pub fn queue_fill_path(&mut self, path: impl Into<Arc<Path>>, paint: impl Into<Paint>) {
+        let path = self.to_device(path.into());
+        self.push(DrawCmd::FillPath {
+            path,
+            paint: paint.into(),
+        });
+    }
+
+    /// recorded text fill. glyphs are outlines so they stay sharp however far the camera zooms.
+    /// `origin` is the start of the first baseline, size is the line height in px
+    pub fn queue_text(
+        &mut self,
+        font: &impl Font,
+        text: &str,
+        size: f32,
+        origin: [f32; 2],
+        paint: impl Into<Paint>,
+    ) {
+        self.queue_fill_path(text::text_path(font, text, size, origin), paint);
+    }
+
+    /// recorded image draw. `transform` places the image in world space (image px -> world),
+    /// then the current transform and camera apply on top. strong downscales read from mipmaps
+    pub fn queue_image(
+        &mut self,
+        image: impl Into<Arc<Texture>>,
+        transform: Transform2D,
+        filter: Filter,
+    ) {
+        self.push(DrawCmd::Image {
+            image: image.into(),
+            transform: self.view_transform() * transform,
+            filter,
+        });
+    }
+
+    /// recorded version of clear()
+    pub fn queue_clear(&mut self, paint: impl Into<Paint>) {
+        self.push(DrawCmd::Clear(paint.into()));
+    }
+
+    /// recorded version of hspan()
+    pub fn queue_hspan(&mut self, y: u16, x0: u16, x1: u16, paint: impl Into<Paint>) {
+        self.push(DrawCmd::HSpan {
+            y,
+            x0,
+            x1,
+            paint: paint.into(),
+        });
+    }
+
+    /// recorded version of rect(). takes a color or a gradient
+    pub fn queue_rect(&mut self, x: u16, y: u16, width: u16, height: u16, paint: impl Into<Paint>) {
+        self.push(DrawCmd::Rect {
+            rect: PixelRect::from_xywh(x, y, width, height),
+            paint: paint.into(),
+        });
+    }
+
+    /// rasterizes the draw queue into fb (tiled unless set_tiling said otherwise)
+    pub fn end_frame(&mut self, fb: &mut Frame) {
+        match self.tiling {
+            Tiling::Serial => tile::rasterize_serial(fb, &self.commands),
+            Tiling::Tiled { tile_size, threads } => {
+                tile::rasterize_tiled(fb, &self.commands, tile_size, threads)
+            }
+        }
+        self.commands.clear();
     }
 
-    /// will also be used for the draw queue
-    pub fn end_frame(&mut self, _fb: &mut Frame) {
-        todo!("[NOT IMPLEMENTED] waiting on implementation.");
+    /// rasterizes the draw queue straight into an hdr frame, in float so light above 1.0 survives
+    /// until bloom/tone mapping. every command's light is scaled by intensity (> 1.0 for glowy
+    /// stuff). always serial, tiling only applies to 8 bit frames
+    pub fn end_frame_hdr<T: HdrChannel>(&mut self, hdr: &mut HdrFrame<T>, intensity: f32) {
+        hdr.rasterize(&self.commands, intensity);
+        self.commands.clear();
     }
 }
 
@@ -0,0 +1,477 @@
+#![allow(dead_code)]
+
+use core::f32::consts::PI;
+use std::sync::OnceLock;
+
+use crate::{
+    color::{ColorFloat, model::Color},
+    renderer::{command::PixelRect, frame::Frame, transform::Transform2D},
+};
+
+/// How an image is reconstructed between its pixels.
+///
+/// Every filter works on premultiplied linear values and treats everything outside of
+/// the image as transparent, so edges fade out instead of smearing or darkening.
+///
+/// # Variants
+///
+/// - `Nearest` - The closest pixel. Blocky, for pixel art.
+/// - `Bilinear` - Blend the 2x2 closest pixels.
+/// - `Bicubic` - Catmull-Rom over 4x4 pixels. Sharper than bilinear when scaling up.
+/// - `Lanczos3` - Windowed sinc over 6x6 pixels. The sharpest, and the slowest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Filter {
+    Nearest,
+    #[default]
+    Bilinear,
+    Bicubic,
+    Lanczos3,
+}
+
+impl Filter {
+    /// How many pixels away from the sample point the filter reaches.
+    #[must_use]
+    #[inline]
+    pub fn radius(self) -> f32 {
+        match self {
+            Filter::Nearest => 0.5,
+            Filter::Bilinear => 1.0,
+            Filter::Bicubic => 2.0,
+            Filter::Lanczos3 => 3.0,
+        }
+    }
+
+    /// The filter kernel at a distance `d` (in pixels) from the sample point.
+    #[must_use]
+    #[inline]
+    fn weight(self, d: f32) -> f32 {
+        let d = d.abs();
+        match self {
+            Filter::Nearest => {
+                if d < 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Filter::Bilinear => (1.0 - d).max(0.0),
+            Filter::Bicubic => {
+                // Catmull-Rom (Keys with a = -0.5)
+                if d < 1.0 {
+                    1.5 * d * d * d - 2.5 * d * d + 1.0
+                } else if d < 2.0 {
+                    -0.5 * d * d * d + 2.5 * d * d - 4.0 * d + 2.0
+                } else {
+                    0.0
+                }
+            }
+            Filter::Lanczos3 => {
+                if d < 1e-6 {
+                    1.0
+                } else if d < 3.0 {
+                    let x = PI * d;
+                    3.0 * x.sin() * (x / 3.0).sin() / (x * x)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Reconstruct a premultiplied linear pixel at a continuous position (pixel centers on `.5`).
+///
+/// `fetch` reads one pixel and returns transparent outside of the image.
+pub(crate) fn filter_at(
+    fetch: impl Fn(i32, i32) -> [ColorFloat; 4],
+    filter: Filter,
+    x: f32,
+    y: f32,
+) -> [ColorFloat; 4] {
+    if filter == Filter::Nearest {
+        return fetch(x.floor() as i32, y.floor() as i32);
+    }
+
+    let taps = filter.radius() as i32;
+    let (x, y) = (x - 0.5, y - 0.5);
+    let (bx, by) = (x.floor() as i32, y.floor() as i32);
+
+    let mut wx = [0.0; 6];
+    let mut wy = [0.0; 6];
+    for i in 0..(2 * taps) as usize {
+        let offset = i as i32 - taps + 1;
+        wx[i] = filter.weight(x - (bx + offset) as f32);
+        wy[i] = filter.weight(y - (by + offset) as f32);
+    }
+    // lanczos doesn't quite sum to one, keep flat areas flat
+    let (sx, sy) = (wx.iter().sum::<f32>(), wy.iter().sum::<f32>());
+
+    let mut out = [0.0; 4];
+    for (j, &wy) in wy.iter().enumerate().take((2 * taps) as usize) {
+        if wy == 0.0 {
+            continue;
+        }
+        for (i, &wx) in wx.iter().enumerate().take((2 * taps) as usize) {
+            if wx == 0.0 {
+                continue;
+            }
+            let w: ColorFloat = (wx * wy / (sx * sy)) as _;
+            let px = fetch(bx + i as i32 - taps + 1, by + j as i32 - taps + 1);
+            for (o, c) in out.iter_mut().zip(px) {
+                *o += c * w;
+            }
+        }
+    }
+
+    // cubic and sinc kernels ring, keep the result a valid premultiplied color
+    let a = out[3].clamp(0.0, 1.0);
+    [
+        out[0].clamp(0.0, a),
+        out[1].clamp(0.0, a),
+        out[2].clamp(0.0, a),
+        a,
+    ]
+}
+
+/// One mip level: premultiplied linear pixels.
+struct Level {
+    width: usize,
+    height: usize,
+    px: Vec<[ColorFloat; 4]>,
+}
+
+impl Level {
+    #[inline]
+    fn fetch(&self, x: i32, y: i32) -> [ColorFloat; 4] {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return [0.0; 4];
+        }
+        self.px[y as usize * self.width + x as usize]
+    }
+
+    /// Half the size with a 2x2 box filter. Odd edges average in transparent pixels,
+    /// which keeps every level covering exactly the same area as the base.
+    fn downsample(&self) -> Level {
+        let (w, h) = (self.width.div_ceil(2), self.height.div_ceil(2));
+        let mut px = Vec::with_capacity(w * h);
+        for y in 0..h as i32 {
+            for x in 0..w as i32 {
+                let mut sum = [0.0; 4];
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    for (s, c) in sum.iter_mut().zip(self.fetch(2 * x + dx, 2 * y + dy)) {
+                        *s += c * 0.25;
+                    }
+                }
+                px.push(sum);
+            }
+        }
+        Level {
+            width: w,
+            height: h,
+            px,
+        }
+    }
+}
+
+/// An image prepared for filtered drawing.
+///
+/// Pixels are converted to premultiplied linear floats once, and the mip chain
+/// (used when the image is drawn smaller than half size) is built the first time it's needed.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::renderer::{frame::Frame, sample::{Filter, Texture}};
+///
+/// let tex = Texture::new(&Frame::new(256, 256));
+/// let px = tex.sample(Filter::Bicubic, 12.3, 40.0);
+/// ```
+pub struct Texture {
+    base: Level,
+    mips: OnceLock<Vec<Level>>,
+}
+
+impl Texture {
+    #[must_use]
+    pub fn new(frame: &Frame) -> Self {
+        let px = frame
+            .as_slice()
+            .chunks_exact(4)
+            .map(|p| {
+                let [r, g, b, a] = Color::from_rgba([p[0], p[1], p[2], p[3]]).into_linear();
+                [r * a, g * a, b * a, a]
+            })
+            .collect();
+        Self {
+            base: Level {
+                width: frame.width() as usize,
+                height: frame.height() as usize,
+                px,
+            },
+            mips: OnceLock::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn width(&self) -> u16 {
+        self.base.width as u16
+    }
+
+    #[must_use]
+    pub fn height(&self) -> u16 {
+        self.base.height as u16
+    }
+
+    /// How many levels the full mip chain has, the base included.
+    #[must_use]
+    pub fn mip_levels(&self) -> usize {
+        1 + self.mips().len()
+    }
+
+    fn mips(&self) -> &[Level] {
+        self.mips.get_or_init(|| {
+            let mut levels: Vec<Level> = Vec::new();
+            loop {
+                let prev = levels.last().unwrap_or(&self.base);
+                if prev.width <= 1 && prev.height <= 1 {
+                    break;
+                }
+                let next = prev.downsample();
+                levels.push(next);
+            }
+            levels
+        })
+    }
+
+    fn level(&self, i: usize) -> &Level {
+        if i == 0 {
+            return &self.base;
+        }
+        let mips = self.mips();
+        mips.get(i - 1).or(mips.last()).unwrap_or(&self.base)
+    }
+
+    /// A premultiplied linear sample of the full resolution image.
+    #[must_use]
+    pub fn sample(&self, filter: Filter, x: f32, y: f32) -> [ColorFloat; 4] {
+        filter_at(|x, y| self.base.fetch(x, y), filter, x, y)
+    }
+
+    /// A premultiplied linear sample at a level of detail (`log2` of how many image pixels
+    /// fall on one output pixel). Between levels the two closest are blended (trilinear),
+    /// `Nearest` just picks the closest level.
+    #[must_use]
+    pub fn sample_lod(&self, filter: Filter, x: f32, y: f32, lod: f32) -> [ColorFloat; 4] {
+        if lod <= 0.0 {
+            return self.sample(filter, x, y);
+        }
+
+        let at = |level: usize| {
+            let s = 1.0 / (1u32 << level.min(31)) as f32;
+            let l = self.level(level);
+            filter_at(|x, y| l.fetch(x, y), filter, x * s, y * s)
+        };
+
+        if filter == Filter::Nearest {
+            return at(lod.round() as usize);
+        }
+        let lo = lod.floor() as usize;
+        let f: ColorFloat = (lod - lod.floor()) as _;
+        let (a, b) = (at(lo), at(lo + 1));
+        core::array::from_fn(|i| a[i] + (b[i] - a[i]) * f)
+    }
+}
+
+// textures are big, compare their pixels but never print them
+impl PartialEq for Texture {
+    fn eq(&self, other: &Self) -> bool {
+        (self.base.width, self.base.height) == (other.base.width, other.base.height)
+            && self.base.px == other.base.px
+    }
+}
+
+impl core::fmt::Debug for Texture {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Texture")
+            .field("width", &self.base.width)
+            .field("height", &self.base.height)
+            .finish_non_exhaustive()
+    }
+}
+
+impl From<&Frame> for Texture {
+    fn from(value: &Frame) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<Frame> for Texture {
+    fn from(value: Frame) -> Self {
+        Self::new(&value)
+    }
+}
+
+/// How many image pixels one device pixel spans under the inverse of `t` (the longer axis).
+#[inline]
+fn footprint(inv: &Transform2D) -> f32 {
+    inv.a.hypot(inv.b).max(inv.c.hypot(inv.d))
+}
+
+/// The device pixels a transformed texture can touch, filter reach included.
+#[must_use]
+pub(crate) fn transformed_bounds(tex: &Texture, t: &Transform2D, filter: Filter) -> PixelRect {
+    let Some(inv) = t.invert() else {
+        return PixelRect::EMPTY;
+    };
+    // mip levels round their size up and blur over twice the footprint
+    let pad = (filter.radius() + 1.0) * 2.0 * footprint(&inv).max(1.0);
+    let [x0, y0, x1, y1] = t.map_rect([
+        -pad,
+        -pad,
+        tex.width() as f32 + pad,
+        tex.height() as f32 + pad,
+    ]);
+
+    let clamp = |v: f32| v.clamp(0.0, u16::MAX as f32) as u16;
+    let r = PixelRect::new(
+        clamp(x0.floor()),
+        clamp(y0.floor()),
+        clamp(x1.ceil()),
+        clamp(y1.ceil()),
+    );
+    if r.is_empty() { PixelRect::EMPTY } else { r }
+}
+
+/// Source-over a transformed texture into `rect` (already clipped to `area`).
+///
+/// Every pixel center is mapped back into the image, sampled with `filter` from the
+/// mip level matching the scale, and blended in premultiplied linear space.
+pub(crate) fn draw_transformed(
+    area: PixelRect,
+    data: &mut [u8],
+    rect: PixelRect,
+    tex: &Texture,
+    t: &Transform2D,
+    filter: Filter,
+) {
+    // a degenerate transform squashes the image to nothing
+    let Some(inv) = t.invert() else {
+        return;
+    };
+    let lod = footprint(&inv).log2();
+    let stride = area.width() as usize * 4;
+
+    // the bounding box of a rotated image is mostly empty, skip pixels whose sample
+    // point is too far out for any filter tap to land on the image
+    let reach = (filter.radius() + 1.0) * lod.ceil().exp2().max(1.0);
+    let (w, h) = (tex.width() as f32, tex.height() as f32);
+
+    for y in rect.y0..rect.y1 {
+        let row = (y - area.y0) as usize * stride;
+        for x in rect.x0..rect.x1 {
+            let [u, v] = inv.apply([x as f32 + 0.5, y as f32 + 0.5]);
+            if u < -reach || v < -reach || u > w + reach || v > h + reach {
+                continue;
+            }
+            let src = tex.sample_lod(filter, u, v, lod);
+            if src[3] <= 0.0 {
+                continue;
+            }
+
+            let i = row + (x - area.x0) as usize * 4;
+            let px = &mut data[i..i + 4];
+            let [dr, dg, db, da] = Color::from_rgba([px[0], px[1], px[2], px[3]]).into_linear();
+            let k = 1.0 - src[3];
+            let out = [
+                src[0] + dr * da * k,
+                src[1] + dg * da * k,
+                src[2] + db * da * k,
+                src[3] + da * k,
+            ];
+            let a = out[3].min(1.0);
+            px.copy_from_slice(
+                &Color::from_linear([out[0] / a, out[1] / a, out[2] / a, a]).into_rgba(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(w: u16, h: u16, rgba: [u8; 4]) -> Frame {
+        let mut fb = Frame::new(w, h);
+        for px in fb.as_bytes_mut().chunks_exact_mut(4) {
+            px.copy_from_slice(&rgba);
+        }
+        fb
+    }
+
+    const FILTERS: [Filter; 4] = [
+        Filter::Nearest,
+        Filter::Bilinear,
+        Filter::Bicubic,
+        Filter::Lanczos3,
+    ];
+
+    #[test]
+    fn flat_images_stay_flat_inside() {
+        let tex = Texture::new(&solid(16, 16, [200, 100, 50, 255]));
+        let expect = Color::new(200, 100, 50, 255).into_linear();
+        for f in FILTERS {
+            let s = tex.sample(f, 7.3, 8.9);
+            for (s, e) in s.iter().zip(expect) {
+                assert!((s - e).abs() < 1e-4, "{f:?}: {s} vs {e}");
+            }
+        }
+    }
+
+    #[test]
+    fn samples_hit_pixel_centers_exactly() {
+        let mut fb = solid(4, 1, [0, 0, 0, 255]);
+        fb.as_bytes_mut()[4..8].copy_from_slice(&[255, 255, 255, 255]);
+        let tex = Texture::new(&fb);
+        for f in FILTERS {
+            let s = tex.sample(f, 1.5, 0.5);
+            assert!((s[0] - 1.0).abs() < 1e-4, "{f:?}: {s:?}");
+            assert!(tex.sample(f, 2.5, 0.5)[0] < 1e-4, "{f:?}");
+        }
+    }
+
+    #[test]
+    fn edges_have_no_dark_fringe() {
+        // opaque white next to fully transparent *black*: straight-alpha filtering
+        // would drag the edge toward gray
+        let mut fb = Frame::new(2, 1);
+        fb.as_bytes_mut()
+            .copy_from_slice(&[255, 255, 255, 255, 0, 0, 0, 0]);
+        let tex = Texture::new(&fb);
+        for f in FILTERS {
+            let s = tex.sample(f, 1.0, 0.5);
+            if s[3] > 0.0 {
+                assert!((s[0] / s[3] - 1.0).abs() < 1e-4, "{f:?}: {s:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn mips_average_down_to_one_pixel() {
+        // a checkerboard averages out to 50% gray (in linear light)
+        let mut fb = Frame::new(8, 8);
+        for (i, px) in fb.as_bytes_mut().chunks_exact_mut(4).enumerate() {
+            let v = if (i % 8 + i / 8).is_multiple_of(2) {
+                255
+            } else {
+                0
+            };
+            px.copy_from_slice(&[v, v, v, 255]);
+        }
+        let tex = Texture::new(&fb);
+        assert_eq!(tex.mip_levels(), 4);
+
+        let s = tex.sample_lod(Filter::Bilinear, 4.0, 4.0, 3.0);
+        assert!((s[0] - 0.5).abs() < 1e-4, "{s:?}");
+        assert!((s[3] - 1.0).abs() < 1e-4, "{s:?}");
+    }
+}
@@ -0,0 +1,841 @@
+#![allow(dead_code)]
+
+use core::fmt;
+
+use ab_glyph::FontArc;
+
+use crate::{
+    color::{ColorFloat, model::Color},
+    renderer::{
+        easing::Easing,
+        frame::Frame,
+        path::Path,
+        render::Renderer,
+        text::{self, line_metrics, text_path, text_width},
+        transform::Transform2D,
+    },
+};
+
+/// A handle to a node in a `Scene`, handed out by `Scene::add`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// What a node draws, in its own space with the node's position at the origin.
+///
+/// # Variants
+///
+/// - `Text` - A run of text with its top left corner at the origin. `\n` starts a new line.
+/// - `Code` - A code block, optionally on a padded background panel (the panel's
+///   top left corner is at the origin).
+/// - `Rect` - A rectangle of `size` with its top left corner at the origin, optionally rounded.
+/// - `Circle` - A circle centered on the origin.
+/// - `Line` - A straight line of `width` px from the origin to `end`.
+/// - `Polygon` - A closed polygon through `points`.
+#[derive(Clone, Debug)]
+pub enum Shape {
+    Text {
+        text: String,
+        size: f32,
+        font: Option<FontArc>,
+    },
+    Code {
+        source: String,
+        size: f32,
+        font: Option<FontArc>,
+        background: Option<Color>,
+        padding: f32,
+    },
+    Rect {
+        size: [f32; 2],
+        radius: f32,
+    },
+    Circle {
+        radius: f32,
+    },
+    Line {
+        end: [f32; 2],
+        width: f32,
+    },
+    Polygon {
+        points: Vec<[f32; 2]>,
+    },
+}
+
+impl Shape {
+    /// What scene files call this kind of shape.
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Shape::Text { .. } => "text",
+            Shape::Code { .. } => "code",
+            Shape::Rect { .. } => "rect",
+            Shape::Circle { .. } => "circle",
+            Shape::Line { .. } => "line",
+            Shape::Polygon { .. } => "polygon",
+        }
+    }
+
+    /// Check that the shape can be drawn: font sizes are positive, and rect sizes, radii,
+    /// line widths and padding aren't negative. None of them can be NaN or infinite.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<(), ShapeError>` - The first field that's out of range.
+    pub fn check(&self) -> Result<(), ShapeError> {
+        let kind = self.kind();
+        let not_negative = |v: f32| v.is_finite() && v >= 0.0;
+        let (field, positive) = match *self {
+            Shape::Text { size, .. } | Shape::Code { size, .. }
+                if !(size.is_finite() && size > 0.0) =>
+            {
+                ("size", true)
+            }
+            Shape::Code { padding, .. } if !not_negative(padding) => ("padding", false),
+            Shape::Rect { size, .. } if !size.into_iter().all(not_negative) => ("size", false),
+            Shape::Rect { radius, .. } | Shape::Circle { radius } if !not_negative(radius) => {
+                ("radius", false)
+            }
+            Shape::Line { width, .. } if !not_negative(width) => ("width", false),
+            _ => return Ok(()),
+        };
+        Err(if positive {
+            ShapeError::NotPositive { kind, field }
+        } else {
+            ShapeError::Negative { kind, field }
+        })
+    }
+}
+
+/// Why `Shape::check` turned a shape down.
+///
+/// # Variants
+///
+/// - `NotPositive` - A font size is zero, negative or not finite.
+/// - `Negative` - A rect size, radius, line width or padding is negative or not finite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShapeError {
+    NotPositive {
+        kind: &'static str,
+        field: &'static str,
+    },
+    Negative {
+        kind: &'static str,
+        field: &'static str,
+    },
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShapeError::NotPositive { kind, field } => {
+                write!(f, "`{field}` of a {kind} object is a positive number")
+            }
+            ShapeError::Negative { kind, field } => {
+                write!(f, "`{field}` of a {kind} object can't be negative")
+            }
+        }
+    }
+}
+impl std::error::Error for ShapeError {}
+
+/// One object in a scene.
+///
+/// Nodes rotate and scale around the middle of what they draw.
+///
+/// # Fields
+///
+/// - `name` (`Option<String>`) - What scene files and `Scene::find` call the node.
+/// - `shape` (`Shape`) - What gets drawn.
+/// - `position` (`[f32; 2]`) - Where the shape's origin lands in world space.
+/// - `color` (`Color`) - The fill (or text) color.
+/// - `opacity` (`f32`) - 0.0-1.0, multiplied into the color's alpha.
+/// - `scale` (`f32`) - Uniform scale around the node's center.
+/// - `rotation` (`f32`) - Clockwise rotation around the node's center in degrees.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::{color::model::Color, renderer::scene::{Node, Shape}};
+///
+/// let card = Node {
+///     name: Some("card".into()),
+///     opacity: 0.0,
+///     ..Node::new(Shape::Rect { size: [400.0, 240.0], radius: 16.0 }, [760.0, 420.0], Color::WHITE)
+/// };
+/// ```
+#[derive(Clone, Debug)]
+pub struct Node {
+    pub name: Option<String>,
+    pub shape: Shape,
+    pub position: [f32; 2],
+    pub color: Color,
+    pub opacity: f32,
+    pub scale: f32,
+    pub rotation: f32,
+}
+
+impl Node {
+    /// An unnamed, fully opaque, untransformed node.
+    #[must_use]
+    pub fn new(shape: Shape, position: [f32; 2], color: Color) -> Self {
+        Self {
+            name: None,
+            shape,
+            position,
+            color,
+            opacity: 1.0,
+            scale: 1.0,
+            rotation: 0.0,
+        }
+    }
+}
+
+/// A node property a tween can animate.
+///
+/// # Variants
+///
+/// - `Position` - Both coordinates, takes `Value::Point`.
+/// - `X` - The horizontal position, takes `Value::Number`.
+/// - `Y` - The vertical position, takes `Value::Number`.
+/// - `Opacity` - Takes `Value::Number`.
+/// - `Scale` - Takes `Value::Number`.
+/// - `Rotation` - In degrees, takes `Value::Number`.
+/// - `Color` - Interpolated in OKLCH, takes `Value::Color`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Property {
+    Position,
+    X,
+    Y,
+    Opacity,
+    Scale,
+    Rotation,
+    Color,
+}
+
+impl Property {
+    pub const ALL: [Property; 7] = [
+        Property::Position,
+        Property::X,
+        Property::Y,
+        Property::Opacity,
+        Property::Scale,
+        Property::Rotation,
+        Property::Color,
+    ];
+
+    /// The name scene files use.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Property::Position => "position",
+            Property::X => "x",
+            Property::Y => "y",
+            Property::Opacity => "opacity",
+            Property::Scale => "scale",
+            Property::Rotation => "rotation",
+            Property::Color => "color",
+        }
+    }
+
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.name() == name)
+    }
+
+    /// Whether a value is the right kind for this property.
+    #[must_use]
+    pub const fn accepts(self, value: &Value) -> bool {
+        matches!(
+            (self, value),
+            (Property::Position, Value::Point(_))
+                | (
+                    Property::X
+                        | Property::Y
+                        | Property::Opacity
+                        | Property::Scale
+                        | Property::Rotation,
+                    Value::Number(_)
+                )
+                | (Property::Color, Value::Color(_))
+        )
+    }
+}
+
+impl fmt::Display for Property {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A keyframe value.
+///
+/// # Variants
+///
+/// - `Number` - For `X`, `Y`, `Opacity`, `Scale` and `Rotation`.
+/// - `Point` - For `Position`.
+/// - `Color` - For `Color`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value {
+    Number(f32),
+    Point([f32; 2]),
+    Color(Color),
+}
+
+impl Value {
+    fn lerp(self, to: Value, t: f32) -> Value {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        match (self, to) {
+            (Value::Number(a), Value::Number(b)) => Value::Number(mix(a, b)),
+            (Value::Point(a), Value::Point(b)) => Value::Point([mix(a[0], b[0]), mix(a[1], b[1])]),
+            (Value::Color(a), Value::Color(b)) => Value::Color(a.lerp_oklch(b, t as ColorFloat)),
+            // kinds are checked when the tween is added
+            (_, to) => to,
+        }
+    }
+}
+
+/// An animation of one node property over a span of the timeline.
+///
+/// Without `from` the tween starts from wherever the property is when it begins, so tweens
+/// on the same property chain. With `from` the property also sits at that value until the
+/// tween starts, if it's the first tween on that property (so a fade in keeps the node hidden
+/// until then).
+///
+/// # Fields
+///
+/// - `node` (`NodeId`) - The node to animate.
+/// - `property` (`Property`) - Which property.
+/// - `from` (`Option<Value>`) - The start value, `None` to continue from the current one.
+/// - `to` (`Value`) - The end value.
+/// - `start` (`f32`) - When it starts, in seconds.
+/// - `duration` (`f32`) - How long it runs, in seconds. 0.0 jumps straight to `to`.
+/// - `easing` (`Easing`) - How it moves.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tween {
+    pub node: NodeId,
+    pub property: Property,
+    pub from: Option<Value>,
+    pub to: Value,
+    pub start: f32,
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+/// Why `Scene::animate` rejected a tween.
+///
+/// # Variants
+///
+/// - `UnknownNode` - The node id isn't from this scene.
+/// - `WrongValue` - `from` or `to` isn't the kind of value the property takes.
+/// - `BadTiming` - The start or duration is negative or not finite.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TweenError {
+    UnknownNode(NodeId),
+    WrongValue { property: Property, value: Value },
+    BadTiming { start: f32, duration: f32 },
+}
+
+impl fmt::Display for TweenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use TweenError::*;
+        match self {
+            UnknownNode(id) => write!(f, "node {} isn't in this scene", id.0),
+            WrongValue { property, value } => {
+                let expected = match property {
+                    Property::Position => "a point",
+                    Property::Color => "a color",
+                    _ => "a number",
+                };
+                write!(f, "`{property}` takes {expected}, got {value:?}")
+            }
+            BadTiming { start, duration } => write!(
+                f,
+                "tween timing must be finite and non-negative (start {start}, duration {duration})"
+            ),
+        }
+    }
+}
+impl std::error::Error for TweenError {}
+
+/// The animated properties of a node at one point in time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NodeState {
+    pub position: [f32; 2],
+    pub color: Color,
+    pub opacity: f32,
+    pub scale: f32,
+    pub rotation: f32,
+}
+
+impl NodeState {
+    fn get(&self, property: Property) -> Value {
+        match property {
+            Property::Position => Value::Point(self.position),
+            Property::X => Value::Number(self.position[0]),
+            Property::Y => Value::Number(self.position[1]),
+            Property::Opacity => Value::Number(self.opacity),
+            Property::Scale => Value::Number(self.scale),
+            Property::Rotation => Value::Number(self.rotation),
+            Property::Color => Value::Color(self.color),
+        }
+    }
+
+    fn set(&mut self, property: Property, value: Value) {
+        match (property, value) {
+            (Property::Position, Value::Point(p)) => self.position = p,
+            (Property::X, Value::Number(x)) => self.position[0] = x,
+            (Property::Y, Value::Number(y)) => self.position[1] = y,
+            (Property::Opacity, Value::Number(o)) => self.opacity = o,
+            (Property::Scale, Value::Number(s)) => self.scale = s,
+            (Property::Rotation, Value::Number(r)) => self.rotation = r,
+            (Property::Color, Value::Color(c)) => self.color = c,
+            _ => {}
+        }
+    }
+}
+
+/// A scene graph: a canvas, the nodes on it and a timeline of tweens.
+///
+/// Scene files (`scene_file`), the Python API and Rust code all build one of these,
+/// and every frame is drawn from it by time.
+///
+/// # Fields
+///
+/// - `width` (`u16`) - The canvas width in px.
+/// - `height` (`u16`) - The canvas height in px.
+/// - `fps` (`f32`) - Frames per second.
+/// - `duration` (`f32`) - Length in seconds.
+/// - `background` (`Color`) - What every frame is cleared to.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::{
+///     color::model::Color,
+///     renderer::{easing::Easing, scene::*},
+/// };
+///
+/// let mut scene = Scene::new(1280, 720);
+/// let title = scene.add(Node {
+///     name: Some("title".into()),
+///     ..Node::new(Shape::Text { text: "Binary search".into(), size: 64.0, font: None }, [80.0, 60.0], Color::WHITE)
+/// });
+/// scene.animate(Tween {
+///     node: title,
+///     property: Property::Opacity,
+///     from: Some(Value::Number(0.0)),
+///     to: Value::Number(1.0),
+///     start: 0.5,
+///     duration: 1.0,
+///     easing: Easing::EaseOut,
+/// })?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Scene {
+    pub width: u16,
+    pub height: u16,
+    pub fps: f32,
+    pub duration: f32,
+    pub background: Color,
+    font: FontArc,
+    code_font: FontArc,
+    nodes: Vec<Node>,
+    // sorted by start, so later tweens build on earlier ones
+    tweens: Vec<Tween>,
+}
+
+impl Scene {
+    /// An empty 30 fps, 5 second scene on black, using the bundled font.
+    #[must_use]
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            fps: 30.0,
+            duration: 5.0,
+            background: Color::BLACK,
+            font: text::default_font(),
+            code_font: text::default_font(),
+            nodes: Vec::new(),
+            tweens: Vec::new(),
+        }
+    }
+
+    /// The font text nodes use unless they bring their own.
+    pub fn set_font(&mut self, font: FontArc) {
+        self.font = font;
+    }
+
+    /// The font code blocks use unless they bring their own.
+    pub fn set_code_font(&mut self, font: FontArc) {
+        self.code_font = font;
+    }
+
+    /// Add a node on top of everything added so far.
+    pub fn add(&mut self, node: Node) -> NodeId {
+        self.nodes.push(node);
+        NodeId(self.nodes.len() - 1)
+    }
+
+    #[must_use]
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id.0)
+    }
+
+    #[must_use]
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(id.0)
+    }
+
+    /// Look a node up by name.
+    #[must_use]
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .position(|n| n.name.as_deref() == Some(name))
+            .map(NodeId)
+    }
+
+    /// Every node, back to front.
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &Node)> {
+        self.nodes.iter().enumerate().map(|(i, n)| (NodeId(i), n))
+    }
+
+    /// Every tween, by start time.
+    #[must_use]
+    pub fn tweens(&self) -> &[Tween] {
+        &self.tweens
+    }
+
+    /// Add a tween to the timeline.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<(), TweenError>` - An error if the node isn't in the scene, a value doesn't
+    ///   fit the property or the timing is negative.
+    pub fn animate(&mut self, tween: Tween) -> Result<(), TweenError> {
+        if tween.node.0 >= self.nodes.len() {
+            return Err(TweenError::UnknownNode(tween.node));
+        }
+        for value in tween.from.iter().chain([&tween.to]) {
+            if !tween.property.accepts(value) {
+                return Err(TweenError::WrongValue {
+                    property: tween.property,
+                    value: *value,
+                });
+            }
+        }
+        let ok = |v: f32| v.is_finite() && v >= 0.0;
+        if !ok(tween.start) || !ok(tween.duration) {
+            return Err(TweenError::BadTiming {
+                start: tween.start,
+                duration: tween.duration,
+            });
+        }
+
+        let at = self.tweens.partition_point(|t| t.start <= tween.start);
+        self.tweens.insert(at, tween);
+        Ok(())
+    }
+
+    /// How many frames the scene renders to.
+    #[must_use]
+    pub fn frame_count(&self) -> u32 {
+        (self.duration * self.fps).ceil().max(0.0) as u32
+    }
+
+    /// When a frame is shown, in seconds.
+    #[must_use]
+    pub fn frame_time(&self, frame: u32) -> f32 {
+        frame as f32 / self.fps
+    }
+
+    /// A node's animated properties at a point in time.
+    ///
+    /// # Panics
+    ///
+    /// If `id` isn't from this scene.
+    #[must_use]
+    pub fn state_at(&self, id: NodeId, time: f32) -> NodeState {
+        let node = &self.nodes[id.0];
+        let mut state = NodeState {
+            position: node.position,
+            color: node.color,
+            opacity: node.opacity,
+            scale: node.scale,
+            rotation: node.rotation,
+        };
+
+        let mut started = [false; Property::ALL.len()];
+        for tween in self.tweens.iter().filter(|t| t.node == id) {
+            let first = !core::mem::replace(&mut started[tween.property as usize], true);
+            if time < tween.start {
+                // the first tween holds its `from` until it starts
+                if let (true, Some(from)) = (first, tween.from) {
+                    state.set(tween.property, from);
+                }
+                continue;
+            }
+
+            let from = tween.from.unwrap_or_else(|| state.get(tween.property));
+            let t = if tween.duration > 0.0 {
+                (time - tween.start) / tween.duration
+            } else {
+                1.0
+            };
+            state.set(tween.property, from.lerp(tween.to, tween.easing.apply(t)));
+        }
+        state
+    }
+
+    /// Queue every node as it looks at `time`. Call between `begin_frame` and `end_frame`.
+    pub fn draw(&self, r: &mut Renderer, time: f32) {
+        r.queue_clear(self.background);
+        for (id, node) in self.nodes() {
+            self.draw_node(r, node, self.state_at(id, time));
+        }
+    }
+
+    /// Draw the frame at `time` into `fb`.
+    pub fn render(&self, r: &mut Renderer, fb: &mut Frame, time: f32) {
+        r.begin_frame();
+        self.draw(r, time);
+        r.end_frame(fb);
+    }
+
+    fn draw_node(&self, r: &mut Renderer, node: &Node, state: NodeState) {
+        let fade = |c: Color| {
+            let a = c.into_rgba()[3] as f32 * state.opacity.clamp(0.0, 1.0);
+            c.with_alpha(a.round() as u8)
+        };
+        if fade(state.color).into_rgba()[3] == 0 {
+            return;
+        }
+
+        let mut layers = Vec::with_capacity(2);
+        match &node.shape {
+            Shape::Text { text, size, font } => {
+                let font = font.as_ref().unwrap_or(&self.font);
+                let (ascent, _) = line_metrics(font, *size);
+                layers.push((text_path(font, text, *size, [0.0, ascent]), state.color));
+            }
+            Shape::Code {
+                source,
+                size,
+                font,
+                background,
+                padding,
+            } => {
+                let font = font.as_ref().unwrap_or(&self.code_font);
+                let (ascent, advance) = line_metrics(font, *size);
+                if let Some(bg) = background {
+                    let width = source
+                        .lines()
+                        .map(|l| text_width(font, l, *size))
+                        .fold(0.0, f32::max);
+                    let lines = source.lines().count().max(1) as f32;
+                    let height = *size + advance * (lines - 1.0);
+                    let panel = Path::rounded_rect(
+                        0.0,
+                        0.0,
+                        width + 2.0 * padding,
+                        height + 2.0 * padding,
+                        padding / 2.0,
+                    );
+                    layers.push((panel, *bg));
+                }
+                let origin = [*padding, *padding + ascent];
+                layers.push((text_path(font, source, *size, origin), state.color));
+            }
+            Shape::Rect { size, radius } => layers.push((
+                Path::rounded_rect(0.0, 0.0, size[0], size[1], *radius),
+                state.color,
+            )),
+            Shape::Circle { radius } => layers.push((Path::circle(0.0, 0.0, *radius), state.color)),
+            Shape::Line { end, width } => {
+                let len = end[0].hypot(end[1]);
+                if len > 0.0 {
+                    // the normal, half a line width long
+                    let (nx, ny) = (-end[1] / len * width / 2.0, end[0] / len * width / 2.0);
+                    let mut p = Path::new();
+                    p.move_to([nx, ny])
+                        .line_to([end[0] + nx, end[1] + ny])
+                        .line_to([end[0] - nx, end[1] - ny])
+                        .line_to([-nx, -ny])
+                        .close();
+                    layers.push((p, state.color));
+                }
+            }
+            Shape::Polygon { points } => {
+                let mut p = Path::new();
+                for (i, &pt) in points.iter().enumerate() {
+                    if i == 0 {
+                        p.move_to(pt);
+                    } else {
+                        p.line_to(pt);
+                    }
+                }
+                if !points.is_empty() {
+                    p.close();
+                }
+                layers.push((p, state.color));
+            }
+        }
+
+        // scale and rotate around the middle of the whole node
+        let Some([x0, y0, x1, y1]) =
+            layers
+                .iter()
+                .filter_map(|(p, _)| p.bounds())
+                .reduce(|a, b| {
+                    [
+                        a[0].min(b[0]),
+                        a[1].min(b[1]),
+                        a[2].max(b[2]),
+                        a[3].max(b[3]),
+                    ]
+                })
+        else {
+            return;
+        };
+        let center = [(x0 + x1) / 2.0, (y0 + y1) / 2.0];
+        let transform = Transform2D::translate(-center[0], -center[1])
+            .then(Transform2D::scale(state.scale, state.scale))
+            .then(Transform2D::rotate_deg(state.rotation))
+            .then(Transform2D::translate(
+                center[0] + state.position[0],
+                center[1] + state.position[1],
+            ));
+
+        r.push_transform(transform);
+        for (path, color) in layers {
+            r.queue_fill_path(path, fade(color));
+        }
+        r.pop_transform();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{pool::FramePool, tile::Tiling};
+
+    fn fade_in(scene: &mut Scene, node: NodeId) {
+        scene
+            .animate(Tween {
+                node,
+                property: Property::Opacity,
+                from: Some(Value::Number(0.0)),
+                to: Value::Number(1.0),
+                start: 1.0,
+                duration: 1.0,
+                easing: Easing::Linear,
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn tweens_hold_and_chain() {
+        let mut scene = Scene::new(64, 64);
+        let id = scene.add(Node::new(
+            Shape::Circle { radius: 4.0 },
+            [0.0, 0.0],
+            Color::WHITE,
+        ));
+        fade_in(&mut scene, id);
+        // no `from`, so it picks up where the position is at 2s
+        for (start, to) in [(2.0, [10.0, 0.0]), (0.0, [20.0, 20.0])] {
+            scene
+                .animate(Tween {
+                    node: id,
+                    property: Property::Position,
+                    from: None,
+                    to: Value::Point(to),
+                    start,
+                    duration: 1.0,
+                    easing: Easing::Linear,
+                })
+                .unwrap();
+        }
+
+        assert_eq!(scene.state_at(id, 0.0).opacity, 0.0);
+        assert_eq!(scene.state_at(id, 1.5).opacity, 0.5);
+        assert_eq!(scene.state_at(id, 9.0).opacity, 1.0);
+        assert_eq!(scene.state_at(id, 0.5).position, [10.0, 10.0]);
+        assert_eq!(scene.state_at(id, 2.5).position, [15.0, 10.0]);
+        assert_eq!(scene.state_at(id, 3.0).position, [10.0, 0.0]);
+    }
+
+    #[test]
+    fn bad_tweens_are_rejected() {
+        let mut scene = Scene::new(64, 64);
+        let id = scene.add(Node::new(
+            Shape::Circle { radius: 4.0 },
+            [0.0, 0.0],
+            Color::WHITE,
+        ));
+        let tween = Tween {
+            node: id,
+            property: Property::Color,
+            from: None,
+            to: Value::Number(1.0),
+            start: 0.0,
+            duration: 1.0,
+            easing: Easing::Linear,
+        };
+        assert!(matches!(
+            scene.animate(tween),
+            Err(TweenError::WrongValue { .. })
+        ));
+        let tween = Tween {
+            to: Value::Color(Color::RED),
+            duration: -1.0,
+            ..tween
+        };
+        assert!(matches!(
+            scene.animate(tween),
+            Err(TweenError::BadTiming { .. })
+        ));
+        let tween = Tween {
+            node: NodeId(3),
+            duration: 1.0,
+            ..tween
+        };
+        assert_eq!(
+            scene.animate(tween),
+            Err(TweenError::UnknownNode(NodeId(3)))
+        );
+    }
+
+    #[test]
+    fn frames_draw_nodes_where_they_are() {
+        let mut scene = Scene::new(64, 64);
+        scene.background = Color::BLUE;
+        let id = scene.add(Node::new(
+            Shape::Rect {
+                size: [16.0, 16.0],
+                radius: 0.0,
+            },
+            [8.0, 8.0],
+            Color::RED,
+        ));
+        fade_in(&mut scene, id);
+
+        let mut r = Renderer::new(FramePool::new(64, 64, 1));
+        r.set_tiling(Tiling::Serial);
+        let mut fb = r.acquire().unwrap();
+        scene.render(&mut r, &mut fb, 0.0);
+        assert_eq!(fb.get_pixel(16, 16), Some(Color::BLUE));
+
+        // halfway through the fade it's a mix of both, not already fully red
+        scene.render(&mut r, &mut fb, 1.5);
+        let half = Color::RED.with_alpha(128).over(Color::BLUE);
+        assert_eq!(half.into_rgba()[3], 255);
+        assert_eq!(fb.get_pixel(16, 16), Some(half));
+
+        scene.render(&mut r, &mut fb, 2.0);
+        assert_eq!(fb.get_pixel(16, 16), Some(Color::RED));
+        assert_eq!(fb.get_pixel(30, 16), Some(Color::BLUE));
+    }
+}
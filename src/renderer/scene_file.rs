@@ -0,0 +1,1168 @@
+// scene files describe a whole `Scene` without writing code. same document in any of
+// TOML, JSON or RON (picked by extension):
+//
+//     [canvas]
+//     width = 1280
+//     height = 720
+//     fps = 30                  # optional, 30
+//     duration = 4.0
+//     background = "#1e1e2e"    # optional, black. any CSS color
+//     font = "fonts/Inter.ttf"  # optional, for text objects (the bundled font otherwise)
+//     code_font = "mono.ttf"    # optional, for code objects
+//
+//     [[objects]]
+//     id = "title"              # optional, what the timeline calls it
+//     type = "text"             # text, code, rect, circle, line or polygon
+//     text = "Binary search"
+//     size = 64                 # line height
+//     position = [80, 60]       # optional, [0, 0]
+//     color = "oklch(0.9 0.03 270)"
+//
+//     [[objects]]
+//     id = "code"
+//     type = "code"
+//     file = "search.py"        # or source = "..."
+//     size = 28
+//     background = "#181825"    # optional panel, with `padding` around the text
+//
+//     [[timeline]]
+//     target = "title"
+//     property = "opacity"      # position, x, y, opacity, scale, rotation or color
+//     from = 0.0                # optional, continues from the current value otherwise
+//     to = 1.0
+//     start = 0.5               # optional, right after the previous step ends
+//     duration = 1.0            # optional, 1 second
+//     easing = "ease_out"       # optional, ease_in_out
+//
+// every object also takes `color`, `opacity`, `scale` and `rotation`. shapes take:
+// rect `size = [w, h]` and `radius`, circle `radius`, line `end = [dx, dy]` and `width`,
+// polygon `points = [[x, y], ...]`. paths in the file are relative to the file.
+//
+// everything is checked while it's deserialized so errors come back with the line and
+// column from the format's own parser, including ones that need the whole document
+// (like a timeline step naming an object that doesn't exist). that takes two passes:
+// the first only collects object ids, the second reads everything knowing them.
+
+#![allow(dead_code)]
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt, fs, io,
+    num::NonZeroU16,
+    path::{Path, PathBuf},
+};
+
+use ab_glyph::{FontArc, FontVec};
+use serde::Deserialize;
+
+use crate::{
+    color::model::Color,
+    renderer::{
+        easing::Easing,
+        scene::{Node, Property, Scene, Shape, Tween, TweenError, Value},
+    },
+};
+
+/// The formats a scene file can be written in.
+///
+/// # Variants
+///
+/// - `Toml` - `.toml`
+/// - `Json` - `.json`
+/// - `Ron` - `.ron`, with implicit `Some` so optional fields are written plainly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Toml,
+    Json,
+    Ron,
+}
+
+impl Format {
+    /// Pick a format from a file extension.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "toml" => Some(Format::Toml),
+            "json" => Some(Format::Json),
+            "ron" => Some(Format::Ron),
+            _ => None,
+        }
+    }
+}
+
+/// An error loading a scene file.
+///
+/// # Variants
+///
+/// - `Io` - The scene file couldn't be read.
+/// - `UnknownFormat` - The extension isn't `.toml`, `.json` or `.ron`.
+/// - `Invalid` - The file doesn't parse or doesn't describe a valid scene.
+///   `position` is the 1-based line and column when the format knows it.
+#[derive(Debug)]
+pub enum SceneFileError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    UnknownFormat(PathBuf),
+    Invalid {
+        path: Option<PathBuf>,
+        position: Option<(usize, usize)>,
+        message: String,
+    },
+}
+
+impl fmt::Display for SceneFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SceneFileError::*;
+        match self {
+            Io { path, error } => write!(f, "couldn't read {}: {error}", path.display()),
+            UnknownFormat(path) => write!(
+                f,
+                "{}: unknown scene format (expected .toml, .json or .ron)",
+                path.display()
+            ),
+            Invalid {
+                path,
+                position,
+                message,
+            } => {
+                let path = path.as_deref().unwrap_or(Path::new("<scene>"));
+                match position {
+                    Some((line, column)) => {
+                        write!(f, "{}:{line}:{column}: {message}", path.display())
+                    }
+                    None => write!(f, "{}: {message}", path.display()),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneFileError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// A file a scene file reads besides itself.
+///
+/// # Variants
+///
+/// - `Code` - A code object's `file`. Its text ends up in the scene.
+/// - `Font` - A `font` or `code_font`. Only the parsed font ends up in the scene.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Dependency {
+    Code(PathBuf),
+    Font(PathBuf),
+}
+
+impl Dependency {
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        match self {
+            Dependency::Code(path) | Dependency::Font(path) => path,
+        }
+    }
+}
+
+/// Load a scene file, picking the format from its extension.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - The scene file. Code files and fonts it names are relative to it.
+///
+/// # Returns
+///
+/// - `Result<Scene, SceneFileError>` - The scene, or what's wrong with the file and where.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::renderer::scene_file;
+///
+/// match scene_file::load("intro.toml".as_ref()) {
+///     Ok(scene) => println!("{} frames", scene.frame_count()),
+///     Err(e) => eprintln!("{e}"), // intro.toml:12:9: unknown object `titel`
+/// }
+/// ```
+pub fn load(path: &Path) -> Result<Scene, SceneFileError> {
+    load_tracked(path).0
+}
+
+/// Load a scene file and list the files it read, for watching them (see `watch`).
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - The scene file.
+///
+/// # Returns
+///
+/// - `(Result<Scene, SceneFileError>, Vec<Dependency>)` - The scene as `load` returns it, and
+///   every code file and font the file named, joined onto its directory. Files that were
+///   missing are listed too, and on an error the list stops where reading did.
+pub fn load_tracked(path: &Path) -> (Result<Scene, SceneFileError>, Vec<Dependency>) {
+    let Some(format) = Format::from_path(path) else {
+        return (
+            Err(SceneFileError::UnknownFormat(path.to_owned())),
+            Vec::new(),
+        );
+    };
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) => {
+            let error = SceneFileError::Io {
+                path: path.to_owned(),
+                error,
+            };
+            return (Err(error), Vec::new());
+        }
+    };
+    let base = path.parent().unwrap_or(Path::new(""));
+
+    let (scene, read) = parse_tracked(&source, format, base);
+    let scene = scene.map_err(|e| match e {
+        SceneFileError::Invalid {
+            position, message, ..
+        } => SceneFileError::Invalid {
+            path: Some(path.to_owned()),
+            position,
+            message,
+        },
+        e => e,
+    });
+    (scene, read)
+}
+
+/// Build a scene from the text of a scene file.
+///
+/// # Arguments
+///
+/// - `source` (`&str`) - The file contents.
+/// - `format` (`Format`) - What it's written in.
+/// - `base` (`&Path`) - What paths in the file are relative to.
+///
+/// # Returns
+///
+/// - `Result<Scene, SceneFileError>` - The scene, or an `Invalid` error without a path.
+pub fn parse(source: &str, format: Format, base: &Path) -> Result<Scene, SceneFileError> {
+    parse_tracked(source, format, base).0
+}
+
+fn parse_tracked(
+    source: &str,
+    format: Format,
+    base: &Path,
+) -> (Result<Scene, SceneFileError>, Vec<Dependency>) {
+    let _guard = ContextGuard::enter(base);
+
+    let scene = (|| {
+        let ids: Ids = deserialize(source, format)?;
+        with_context(|cx| cx.ids = Some(ids.objects.into_iter().filter_map(|o| o.id).collect()));
+        let doc: Document = deserialize(source, format)?;
+        doc.build().map_err(|e| SceneFileError::Invalid {
+            path: None,
+            position: None,
+            message: e.to_string(),
+        })
+    })();
+    (scene, with_context(|cx| std::mem::take(&mut cx.read)))
+}
+
+fn deserialize<T: for<'de> Deserialize<'de>>(
+    source: &str,
+    format: Format,
+) -> Result<T, SceneFileError> {
+    let invalid = |position, message: &str| SceneFileError::Invalid {
+        path: None,
+        position,
+        message: message.to_owned(),
+    };
+    match format {
+        Format::Toml => toml::from_str(source).map_err(|e| {
+            // tables in a [[array]] don't have spans, so errors in them point at the first
+            // header. the failing element's own header is better
+            let header = with_context(|cx| cx.failed).and_then(|(array, index)| {
+                let header = format!("[[{array}]]");
+                let line = source
+                    .lines()
+                    .enumerate()
+                    // `[[objects]] # background` is still the header
+                    .filter(|(_, l)| l.split_once('#').map_or(*l, |(l, _)| l).trim() == header)
+                    .nth(index)?
+                    .0;
+                Some((line + 1, 1))
+            });
+            let position = header.or_else(|| e.span().map(|span| line_column(source, span.start)));
+            invalid(position, e.message().trim_end())
+        }),
+        Format::Json => serde_json::from_str(source).map_err(|e| {
+            let message = e.to_string();
+            // the message ends with its own " at line 3 column 7"
+            let message = message
+                .rsplit_once(" at line ")
+                .map_or(&*message, |(m, _)| m);
+            let position =
+                failed_element(source).or((e.line() > 0).then(|| (e.line(), e.column())));
+            invalid(position, message)
+        }),
+        Format::Ron => ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(source)
+            .map_err(|e| {
+                let position = failed_element(source).unwrap_or((e.position.line, e.position.col));
+                invalid(Some(position), &e.code.to_string())
+            }),
+    }
+}
+
+/// Where the element that failed `check_element` starts, in JSON or RON.
+///
+/// Checks on a whole element only run once it's been read, so the parser's own position is
+/// already past it (usually on the next element).
+fn failed_element(source: &str) -> Option<(usize, usize)> {
+    let (array, index) = with_context(|cx| cx.failed)?;
+    element_start(source, array, index).map(|offset| line_column(source, offset))
+}
+
+/// Byte offset of element `index` in the array under the top level key `array`.
+/// Skips strings and comments, anything else only needs brackets and commas.
+fn element_start(source: &str, array: &str, index: usize) -> Option<usize> {
+    let bytes = source.as_bytes();
+    let (mut i, mut depth) = (0, 0);
+    // the last key-ish token at depth 1, and whether we're inside the array
+    let mut key: Option<&str> = None;
+    let mut found_key = false;
+    let mut in_array = false;
+    let (mut count, mut want) = (0, true);
+
+    while i < bytes.len() {
+        let start = i;
+        let b = bytes[i];
+        if b.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        if source[i..].starts_with("//") {
+            i = source[i..].find('\n').map_or(bytes.len(), |n| i + n);
+            continue;
+        }
+        if source[i..].starts_with("/*") {
+            i = source[i + 2..]
+                .find("*/")
+                .map_or(bytes.len(), |n| i + n + 4);
+            continue;
+        }
+
+        // an element starts at the first token after `[` or `,`
+        if in_array && depth == 2 && want && !matches!(b, b',' | b']') {
+            if count == index {
+                return Some(start);
+            }
+            count += 1;
+            want = false;
+        }
+
+        match b {
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i += 1;
+                key = source.get(start + 1..i - 1);
+                continue;
+            }
+            b'{' | b'[' | b'(' => {
+                depth += 1;
+                if found_key && depth == 2 && b == b'[' {
+                    in_array = true;
+                }
+                found_key = false;
+            }
+            b'}' | b']' | b')' => {
+                depth -= 1;
+                if in_array && depth < 2 {
+                    return None;
+                }
+            }
+            b',' if depth == 2 => want = true,
+            b':' if depth == 1 => found_key = key == Some(array),
+            _ if b.is_ascii_alphanumeric() || b == b'_' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                key = Some(&source[start..i]);
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// 1-based line and column (in chars) of a byte offset.
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+// what the checks inside deserialization need to know about the rest of the file
+
+struct Context {
+    base: PathBuf,
+    // every object id, once the first pass has found them
+    ids: Option<HashSet<String>>,
+    // ids seen so far in the second pass, to catch duplicates
+    seen: HashSet<String>,
+    // how many elements of each array have been checked, and which one failed first
+    checked: HashMap<&'static str, usize>,
+    failed: Option<(&'static str, usize)>,
+    // every file read so far, for `load_tracked`
+    read: Vec<Dependency>,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Option<Context>> = const { RefCell::new(None) };
+}
+
+struct ContextGuard;
+
+impl ContextGuard {
+    fn enter(base: &Path) -> Self {
+        CONTEXT.set(Some(Context {
+            base: base.to_owned(),
+            ids: None,
+            seen: HashSet::new(),
+            checked: HashMap::new(),
+            failed: None,
+            read: Vec::new(),
+        }));
+        ContextGuard
+    }
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        CONTEXT.set(None);
+    }
+}
+
+fn with_context<T>(f: impl FnOnce(&mut Context) -> T) -> T {
+    CONTEXT.with_borrow_mut(|cx| {
+        f(cx.as_mut()
+            .expect("scene file context is set while parsing"))
+    })
+}
+
+/// Run the checks for the next element of a top level array, remembering which one failed
+/// for formats that can't say where it is themselves.
+fn check_element<T>(
+    array: &'static str,
+    check: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let result = check();
+    with_context(|cx| {
+        let index = cx.checked.entry(array).or_default();
+        if result.is_err() && cx.failed.is_none() {
+            cx.failed = Some((array, *index));
+        }
+        *index += 1;
+    });
+    result
+}
+
+fn read_relative(path: &Path, kind: fn(PathBuf) -> Dependency) -> Result<Vec<u8>, String> {
+    let full = with_context(|cx| {
+        let full = cx.base.join(path);
+        let dependency = kind(full.clone());
+        if !cx.read.contains(&dependency) {
+            cx.read.push(dependency);
+        }
+        full
+    });
+    fs::read(&full).map_err(|e| format!("couldn't read {}: {e}", full.display()))
+}
+
+fn read_font(path: &Path) -> Result<FontArc, String> {
+    let font = FontVec::try_from_vec(read_relative(path, Dependency::Font)?)
+        .map_err(|_| format!("{} isn't a TrueType/OpenType font", path.display()))?;
+    Ok(FontArc::new(font))
+}
+
+// first pass
+
+#[derive(Deserialize)]
+struct Ids {
+    #[serde(default)]
+    objects: Vec<IdOnly>,
+}
+
+#[derive(Deserialize)]
+struct IdOnly {
+    id: Option<String>,
+}
+
+// second pass
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Document {
+    canvas: Canvas,
+    #[serde(default)]
+    objects: Vec<Object>,
+    #[serde(default)]
+    timeline: Vec<Step>,
+}
+
+impl Document {
+    fn build(self) -> Result<Scene, TweenError> {
+        let canvas = self.canvas;
+        let mut scene = Scene::new(canvas.width.get(), canvas.height.get());
+        scene.fps = canvas.fps.0;
+        scene.duration = canvas.duration.0;
+        scene.background = canvas.background;
+        if let Some(FontFile(font)) = canvas.font {
+            scene.set_font(font);
+        }
+        if let Some(FontFile(font)) = canvas.code_font {
+            scene.set_code_font(font);
+        }
+
+        for Object(node) in self.objects {
+            scene.add(node);
+        }
+
+        // steps without a start run one after another
+        let mut cursor = 0.0;
+        for step in self.timeline {
+            let node = scene
+                .find(&step.target)
+                .expect("targets are checked while deserializing");
+            let start = step.start.unwrap_or(cursor);
+            scene.animate(Tween {
+                node,
+                property: step.property,
+                from: step.from,
+                to: step.to,
+                start,
+                duration: step.duration,
+                easing: step.easing,
+            })?;
+            cursor = start + step.duration;
+        }
+        Ok(scene)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Canvas {
+    width: NonZeroU16,
+    height: NonZeroU16,
+    #[serde(default = "Positive::default_fps")]
+    fps: Positive,
+    duration: Positive,
+    #[serde(default = "Canvas::default_background")]
+    background: Color,
+    #[serde(default)]
+    font: Option<FontFile>,
+    #[serde(default)]
+    code_font: Option<FontFile>,
+}
+
+impl Canvas {
+    fn default_background() -> Color {
+        Color::BLACK
+    }
+}
+
+/// A finite number above zero.
+#[derive(Deserialize)]
+#[serde(try_from = "f32")]
+struct Positive(f32);
+
+impl Positive {
+    fn default_fps() -> Self {
+        Positive(30.0)
+    }
+}
+
+impl TryFrom<f32> for Positive {
+    type Error = String;
+
+    fn try_from(v: f32) -> Result<Self, String> {
+        if v.is_finite() && v > 0.0 {
+            Ok(Positive(v))
+        } else {
+            Err(format!("expected a positive number, got {v}"))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(try_from = "PathBuf")]
+struct FontFile(FontArc);
+
+impl TryFrom<PathBuf> for FontFile {
+    type Error = String;
+
+    fn try_from(path: PathBuf) -> Result<Self, String> {
+        read_font(&path).map(FontFile)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Kind {
+    Text,
+    Code,
+    Rect,
+    Circle,
+    Line,
+    Polygon,
+}
+
+/// `size` is a font size for text and code, and `[w, h]` for rects.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Size {
+    Number(f32),
+    Pair([f32; 2]),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawObject {
+    id: Option<String>,
+    #[serde(rename = "type")]
+    kind: Kind,
+    #[serde(default)]
+    position: [f32; 2],
+    color: Option<Color>,
+    opacity: Option<f32>,
+    scale: Option<f32>,
+    rotation: Option<f32>,
+
+    text: Option<String>,
+    source: Option<String>,
+    file: Option<PathBuf>,
+    size: Option<Size>,
+    font: Option<PathBuf>,
+    background: Option<Color>,
+    padding: Option<f32>,
+    radius: Option<f32>,
+    end: Option<[f32; 2]>,
+    width: Option<f32>,
+    points: Option<Vec<[f32; 2]>>,
+}
+
+impl RawObject {
+    /// The first shape field that doesn't belong on this kind of object.
+    fn stray_field(&self) -> Option<&'static str> {
+        use Kind::*;
+        let fields = [
+            ("text", self.text.is_some(), &[Text][..]),
+            ("source", self.source.is_some(), &[Code]),
+            ("file", self.file.is_some(), &[Code]),
+            ("size", self.size.is_some(), &[Text, Code, Rect]),
+            ("font", self.font.is_some(), &[Text, Code]),
+            ("background", self.background.is_some(), &[Code]),
+            ("padding", self.padding.is_some(), &[Code]),
+            ("radius", self.radius.is_some(), &[Rect, Circle]),
+            ("end", self.end.is_some(), &[Line]),
+            ("width", self.width.is_some(), &[Line]),
+            ("points", self.points.is_some(), &[Polygon]),
+        ];
+        fields
+            .into_iter()
+            .find(|(_, set, kinds)| *set && !kinds.contains(&self.kind))
+            .map(|(name, ..)| name)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(try_from = "RawObject")]
+struct Object(Node);
+
+impl TryFrom<RawObject> for Object {
+    type Error = String;
+
+    fn try_from(raw: RawObject) -> Result<Self, String> {
+        check_element("objects", || Self::check(raw))
+    }
+}
+
+impl Object {
+    fn check(raw: RawObject) -> Result<Self, String> {
+        let stray = raw.stray_field();
+        let font_size = |default| match raw.size {
+            None => default,
+            Some(Size::Number(s)) => s,
+            // not a font size at all, turned down with the other bad sizes below
+            Some(Size::Pair(_)) => f32::NAN,
+        };
+        let font = raw.font.as_deref().map(read_font).transpose()?;
+        let shape = match raw.kind {
+            Kind::Text => Shape::Text {
+                text: raw.text.ok_or("text objects need `text`")?,
+                size: font_size(32.0),
+                font,
+            },
+            Kind::Code => {
+                let source = match (raw.source, raw.file) {
+                    (Some(source), None) => source,
+                    (None, Some(file)) => {
+                        let bytes = read_relative(&file, Dependency::Code)?;
+                        let text = String::from_utf8(bytes)
+                            .map_err(|_| format!("{} isn't UTF-8", file.display()))?;
+                        text.trim_end_matches(['\n', '\r']).to_owned()
+                    }
+                    _ => return Err("code objects need one of `source` or `file`".into()),
+                };
+                Shape::Code {
+                    source,
+                    size: font_size(24.0),
+                    font,
+                    background: raw.background,
+                    padding: raw.padding.unwrap_or(if raw.background.is_some() {
+                        24.0
+                    } else {
+                        0.0
+                    }),
+                }
+            }
+            Kind::Rect => Shape::Rect {
+                size: match raw.size {
+                    Some(Size::Pair(size)) => size,
+                    _ => return Err("rect objects need `size = [width, height]`".into()),
+                },
+                radius: raw.radius.unwrap_or(0.0),
+            },
+            Kind::Circle => Shape::Circle {
+                radius: raw.radius.ok_or("circle objects need `radius`")?,
+            },
+            Kind::Line => Shape::Line {
+                end: raw.end.ok_or("line objects need `end = [dx, dy]`")?,
+                width: raw.width.unwrap_or(2.0),
+            },
+            Kind::Polygon => Shape::Polygon {
+                points: raw.points.ok_or("polygon objects need `points`")?,
+            },
+        };
+
+        let kind = shape.kind();
+        if let Some(field) = stray {
+            return Err(format!("`{field}` doesn't apply to {kind} objects"));
+        }
+        if let Some(id) = &raw.id
+            && !with_context(|cx| cx.seen.insert(id.clone()))
+        {
+            return Err(format!("duplicate object id `{id}`"));
+        }
+        shape.check().map_err(|e| e.to_string())?;
+        // an infinite scale or rotation makes a transform nothing can be drawn with
+        for (field, value) in [
+            ("opacity", raw.opacity),
+            ("scale", raw.scale),
+            ("rotation", raw.rotation),
+        ] {
+            if let Some(v) = value.filter(|v| !v.is_finite()) {
+                return Err(format!("`{field}` has to be a finite number, got {v}"));
+            }
+        }
+
+        Ok(Object(Node {
+            name: raw.id,
+            opacity: raw.opacity.unwrap_or(1.0),
+            scale: raw.scale.unwrap_or(1.0),
+            rotation: raw.rotation.unwrap_or(0.0),
+            ..Node::new(shape, raw.position, raw.color.unwrap_or(Color::WHITE))
+        }))
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum EasingName {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+    Hold,
+}
+
+impl From<EasingName> for Easing {
+    fn from(value: EasingName) -> Self {
+        match value {
+            EasingName::Linear => Easing::Linear,
+            EasingName::EaseIn => Easing::EaseIn,
+            EasingName::EaseOut => Easing::EaseOut,
+            EasingName::EaseInOut => Easing::EaseInOut,
+            EasingName::Hold => Easing::Hold,
+        }
+    }
+}
+
+/// A tween value as written, sorted out by the property it's for.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(untagged)]
+enum RawValue {
+    Number(f32),
+    Point([f32; 2]),
+    Color(Color),
+}
+
+impl From<RawValue> for Value {
+    fn from(value: RawValue) -> Self {
+        match value {
+            RawValue::Number(n) => Value::Number(n),
+            RawValue::Point(p) => Value::Point(p),
+            RawValue::Color(c) => Value::Color(c),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawStep {
+    target: String,
+    property: String,
+    from: Option<RawValue>,
+    to: RawValue,
+    start: Option<f32>,
+    #[serde(default = "RawStep::default_duration")]
+    duration: f32,
+    #[serde(default)]
+    easing: EasingName,
+}
+
+impl RawStep {
+    fn default_duration() -> f32 {
+        1.0
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(try_from = "RawStep")]
+struct Step {
+    target: String,
+    property: Property,
+    from: Option<Value>,
+    to: Value,
+    start: Option<f32>,
+    duration: f32,
+    easing: Easing,
+}
+
+impl TryFrom<RawStep> for Step {
+    type Error = String;
+
+    fn try_from(raw: RawStep) -> Result<Self, String> {
+        check_element("timeline", || Self::check(raw))
+    }
+}
+
+impl Step {
+    fn check(raw: RawStep) -> Result<Self, String> {
+        let known = with_context(|cx| cx.ids.as_ref().is_none_or(|ids| ids.contains(&raw.target)));
+        if !known {
+            return Err(format!("unknown object `{}`", raw.target));
+        }
+        let property = Property::from_name(&raw.property).ok_or_else(|| {
+            let names: Vec<_> = Property::ALL.iter().map(|p| p.name()).collect();
+            format!(
+                "unknown property `{}`, expected one of {}",
+                raw.property,
+                names.join(", ")
+            )
+        })?;
+
+        let from = raw.from.map(Value::from);
+        let to = Value::from(raw.to);
+        for value in from.iter().chain([&to]) {
+            if !property.accepts(value) {
+                let value = *value;
+                return Err(TweenError::WrongValue { property, value }.to_string());
+            }
+        }
+        let ok = |v: f32| v.is_finite() && v >= 0.0;
+        if !raw.start.is_none_or(ok) || !ok(raw.duration) {
+            return Err(TweenError::BadTiming {
+                start: raw.start.unwrap_or(0.0),
+                duration: raw.duration,
+            }
+            .to_string());
+        }
+
+        Ok(Step {
+            target: raw.target,
+            property,
+            from,
+            to,
+            start: raw.start,
+            duration: raw.duration,
+            easing: raw.easing.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r##"
+[canvas]
+width = 320
+height = 180
+duration = 2.0
+background = "#1e1e2e"
+
+[[objects]]
+id = "title"
+type = "text"
+text = "Hello"
+size = 24
+position = [16, 16]
+color = "oklch(0.9 0.03 270)"
+
+[[objects]]
+id = "box"
+type = "rect"
+size = [40, 20]
+radius = 4
+position = [16, 80]
+
+[[timeline]]
+target = "title"
+property = "opacity"
+from = 0
+to = 1
+duration = 0.5
+
+[[timeline]]
+target = "box"
+property = "position"
+to = [200, 80]
+easing = "linear"
+"##;
+
+    const JSON: &str = r##"{
+  "canvas": { "width": 320, "height": 180, "duration": 2.0, "background": "#1e1e2e" },
+  "objects": [
+    { "id": "title", "type": "text", "text": "Hello", "size": 24, "position": [16, 16],
+      "color": "oklch(0.9 0.03 270)" },
+    { "id": "box", "type": "rect", "size": [40, 20], "radius": 4, "position": [16, 80] }
+  ],
+  "timeline": [
+    { "target": "title", "property": "opacity", "from": 0, "to": 1, "duration": 0.5 },
+    { "target": "box", "property": "position", "to": [200, 80], "easing": "linear" }
+  ]
+}"##;
+
+    const RON: &str = r##"(
+    canvas: (width: 320, height: 180, duration: 2.0, background: "#1e1e2e"),
+    objects: [
+        (id: "title", type: text, text: "Hello", size: 24, position: (16, 16),
+         color: "oklch(0.9 0.03 270)"),
+        (id: "box", type: rect, size: (40, 20), radius: 4, position: (16, 80)),
+    ],
+    timeline: [
+        (target: "title", property: "opacity", from: 0, to: 1, duration: 0.5),
+        (target: "box", property: "position", to: (200, 80), easing: linear),
+    ],
+)"##;
+
+    fn parse_str(source: &str, format: Format) -> Result<Scene, SceneFileError> {
+        parse(source, format, Path::new("."))
+    }
+
+    /// Where an `Invalid` error points, and its message.
+    fn invalid(source: &str, format: Format) -> (Option<(usize, usize)>, String) {
+        match parse_str(source, format) {
+            Err(SceneFileError::Invalid {
+                position, message, ..
+            }) => (position, message),
+            other => panic!("expected an invalid scene, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn every_format_builds_the_same_scene() {
+        for (source, format) in [
+            (TOML, Format::Toml),
+            (JSON, Format::Json),
+            (RON, Format::Ron),
+        ] {
+            let scene = parse_str(source, format).unwrap_or_else(|e| panic!("{format:?}: {e}"));
+            assert_eq!((scene.width, scene.height), (320, 180));
+            assert_eq!(scene.frame_count(), 60);
+            assert_eq!(scene.background, Color::new(30, 30, 46, 255));
+
+            let title = scene.find("title").unwrap();
+            let boxed = scene.find("box").unwrap();
+            assert_eq!(boxed, scene.nodes().nth(1).unwrap().0);
+            assert_eq!(scene.state_at(title, 0.0).opacity, 0.0);
+            assert_eq!(scene.state_at(title, 0.5).opacity, 1.0);
+            // the second step starts when the first ends
+            assert_eq!(scene.state_at(boxed, 0.5).position, [16.0, 80.0]);
+            assert_eq!(scene.state_at(boxed, 1.0).position, [108.0, 80.0]);
+            assert!(matches!(
+                scene.node(boxed).unwrap().shape,
+                Shape::Rect {
+                    size: [40.0, 20.0],
+                    radius: 4.0
+                }
+            ));
+        }
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let bad_target = TOML.replace("target = \"box\"", "target = \"bx\"");
+        let (pos, msg) = invalid(&bad_target, Format::Toml);
+        assert_eq!(msg, "unknown object `bx`");
+        // the second [[timeline]] header
+        assert_eq!(pos, Some((30, 1)));
+
+        let bad_value = JSON.replace(r#""from": 0"#, r##""from": "#fff""##);
+        let (pos, msg) = invalid(&bad_value, Format::Json);
+        assert!(msg.starts_with("`opacity` takes a number"), "{msg}");
+        assert_eq!(pos, Some((9, 5)));
+
+        let bad_color = RON.replace("oklch(0.9 0.03 270)", "oklch(0.9 0.03)");
+        let (pos, msg) = invalid(&bad_color, Format::Ron);
+        assert!(msg.starts_with("invalid color"), "{msg}");
+        assert_eq!(pos.map(|p| p.0), Some(5), "{pos:?}");
+
+        let syntax = JSON.replace("\"duration\": 2.0,", "\"duration\": 2.0,,");
+        let (pos, _) = invalid(&syntax, Format::Json);
+        assert_eq!(pos.map(|p| p.0), Some(2));
+
+        let (_, msg) = invalid(
+            &TOML.replace("radius = 4", "radius = 4\nend = [1, 1]"),
+            Format::Toml,
+        );
+        assert_eq!(msg, "`end` doesn't apply to rect objects");
+        let (_, msg) = invalid(
+            &TOML.replace("id = \"box\"", "id = \"title\""),
+            Format::Toml,
+        );
+        assert_eq!(msg, "duplicate object id `title`");
+        let (_, msg) = invalid(
+            &TOML.replace("duration = 2.0", "duration = 0"),
+            Format::Toml,
+        );
+        assert_eq!(msg, "expected a positive number, got 0");
+    }
+
+    #[test]
+    fn objects_turn_down_bad_sizes() {
+        // a comment after the header doesn't lose the position
+        let box_header = "[[objects]] # box\nid = \"box\"";
+        let commented = TOML.replace("[[objects]]\nid = \"box\"", box_header);
+        let (pos, msg) = invalid(
+            &commented.replace("radius = 4", "radius = -4"),
+            Format::Toml,
+        );
+        assert_eq!(msg, "`radius` of a rect object can't be negative");
+        assert_eq!(pos, Some((16, 1)));
+
+        let cases = [
+            (
+                "size = [40, 20]",
+                "size = [-40, 20]",
+                "`size` of a rect object can't be negative",
+            ),
+            (
+                "size = 24",
+                "size = [24, 24]",
+                "`size` of a text object is a positive number",
+            ),
+            (
+                "size = 24",
+                "size = 0",
+                "`size` of a text object is a positive number",
+            ),
+            (
+                "radius = 4",
+                "radius = nan",
+                "`radius` of a rect object can't be negative",
+            ),
+            (
+                "radius = 4",
+                "radius = 4\nopacity = nan",
+                "`opacity` has to be a finite number, got NaN",
+            ),
+            (
+                "radius = 4",
+                "radius = 4\nscale = inf",
+                "`scale` has to be a finite number, got inf",
+            ),
+            (
+                "radius = 4",
+                "radius = 4\nrotation = -inf",
+                "`rotation` has to be a finite number, got -inf",
+            ),
+            (
+                "type = \"rect\"\nsize = [40, 20]\nradius = 4",
+                "type = \"line\"\nend = [8, 8]\nwidth = -2",
+                "`width` of a line object can't be negative",
+            ),
+        ];
+        for (from, to, expected) in cases {
+            let (_, msg) = invalid(&TOML.replace(from, to), Format::Toml);
+            assert_eq!(msg, expected, "{to}");
+        }
+    }
+
+    #[test]
+    fn load_reads_files_next_to_the_scene() {
+        let dir = std::env::temp_dir().join(format!("codimate-scene-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("hello.py"), "print('hi')\n").unwrap();
+        let scene_path = dir.join("code.toml");
+        fs::write(
+            &scene_path,
+            "[canvas]\nwidth = 64\nheight = 64\nduration = 1\n\n\
+             [[objects]]\ntype = \"code\"\nfile = \"hello.py\"\n\n\
+             [[objects]]\ntype = \"code\"\nfile = \"missing.py\"\n",
+        )
+        .unwrap();
+
+        let err = load(&scene_path).unwrap_err().to_string();
+        let prefix = format!("{}:10:1:", scene_path.display());
+        assert!(err.starts_with(&prefix), "{err}");
+        assert!(err.contains("missing.py"), "{err}");
+        // missing files still count, so a watcher notices when they show up
+        let (_, read) = load_tracked(&scene_path);
+        assert_eq!(
+            read,
+            [
+                Dependency::Code(dir.join("hello.py")),
+                Dependency::Code(dir.join("missing.py"))
+            ]
+        );
+
+        fs::write(dir.join("missing.py"), "x = 1").unwrap();
+        let scene = load(&scene_path).unwrap();
+        let (_, node) = scene.nodes().next().unwrap();
+        assert!(matches!(&node.shape, Shape::Code { source, .. } if source == "print('hi')"));
+
+        assert!(matches!(
+            load(&dir.join("scene.yaml")),
+            Err(SceneFileError::UnknownFormat(_))
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
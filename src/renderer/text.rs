@@ -0,0 +1,179 @@
+#![allow(dead_code)]
+
+use std::sync::OnceLock;
+
+use ab_glyph::{Font, FontArc, FontRef, OutlineCurve};
+
+use crate::renderer::path::Path;
+
+/// The font scenes use when they don't bring their own (Inter, bundled with the crate).
+///
+/// There's no monospace font bundled yet, so code blocks use it too unless told otherwise.
+#[must_use]
+pub fn default_font() -> FontArc {
+    static INTER: OnceLock<FontArc> = OnceLock::new();
+    INTER
+        .get_or_init(|| {
+            let bytes = include_bytes!("../../fonts/Inter_28pt-Regular.ttf");
+            FontArc::new(FontRef::try_from_slice(bytes).expect("the bundled font is valid"))
+        })
+        .clone()
+}
+
+/// Turn a run of text into glyph outlines, with the first baseline starting at `origin`.
+///
+/// `size` is the line height in pixels (ascent to descent), the same as `ab_glyph::PxScale`.
+/// `\n` starts a new line. Pairs are kerned, nothing else is shaped.
+///
+/// Text stays vector until it's filled, so transforming the returned path
+/// (or zooming the camera into a code block) re-rasterizes crisp glyphs at the new size.
+///
+/// # Arguments
+///
+/// - `font` (`&impl Font`) - Any `ab_glyph` font.
+/// - `text` (`&str`) - What to lay out.
+/// - `size` (`f32`) - Line height in pixels.
+/// - `origin` (`[f32; 2]`) - Where the first baseline starts.
+///
+/// # Returns
+///
+/// - `Path` - Every glyph's outline in one path.
+///
+/// # Examples
+///
+/// ```
+/// use ab_glyph::FontRef;
+/// use codimate::renderer::text::text_path;
+///
+/// let font = FontRef::try_from_slice(include_bytes!("../../fonts/Inter_28pt-Regular.ttf")).unwrap();
+/// let hello = text_path(&font, "fn main() {}", 32.0, [40.0, 80.0]);
+/// ```
+#[must_use]
+pub fn text_path(font: &impl Font, text: &str, size: f32, origin: [f32; 2]) -> Path {
+    let scale = size / font.height_unscaled();
+    let line_advance = (font.height_unscaled() + font.line_gap_unscaled()) * scale;
+
+    let mut path = Path::new();
+    let mut baseline = origin[1];
+    for line in text.split('\n') {
+        let mut caret = origin[0];
+        let mut prev = None;
+        for ch in line.chars() {
+            let id = font.glyph_id(ch);
+            if let Some(prev) = prev {
+                caret += font.kern_unscaled(prev, id) * scale;
+            }
+            prev = Some(id);
+
+            if let Some(outline) = font.outline(id) {
+                // font units are y up, device space is y down
+                let map = |p: ab_glyph::Point| [caret + p.x * scale, baseline - p.y * scale];
+                append_curves(&mut path, &outline.curves, map);
+            }
+            caret += font.h_advance_unscaled(id) * scale;
+        }
+        baseline += line_advance;
+    }
+    path
+}
+
+/// How far `text_path` moves the caret for one line of text, in pixels.
+#[must_use]
+pub fn text_width(font: &impl Font, line: &str, size: f32) -> f32 {
+    let scale = size / font.height_unscaled();
+    let mut width = 0.0;
+    let mut prev = None;
+    for ch in line.chars() {
+        let id = font.glyph_id(ch);
+        if let Some(prev) = prev {
+            width += font.kern_unscaled(prev, id) * scale;
+        }
+        prev = Some(id);
+        width += font.h_advance_unscaled(id) * scale;
+    }
+    width
+}
+
+/// Where the first baseline sits below the top of a text block, and how far apart
+/// baselines are, for a line height of `size` px.
+#[must_use]
+pub fn line_metrics(font: &impl Font, size: f32) -> (f32, f32) {
+    let scale = size / font.height_unscaled();
+    (
+        font.ascent_unscaled() * scale,
+        (font.height_unscaled() + font.line_gap_unscaled()) * scale,
+    )
+}
+
+/// Outline curves come as a flat list, a new contour starts wherever one curve
+/// doesn't pick up where the last one ended.
+fn append_curves(
+    path: &mut Path,
+    curves: &[OutlineCurve],
+    map: impl Fn(ab_glyph::Point) -> [f32; 2],
+) {
+    let mut cur: Option<[f32; 2]> = None;
+    for curve in curves {
+        let (start, end) = match *curve {
+            OutlineCurve::Line(a, b) => (map(a), map(b)),
+            OutlineCurve::Quad(a, _, b) => (map(a), map(b)),
+            OutlineCurve::Cubic(a, _, _, b) => (map(a), map(b)),
+        };
+        if cur != Some(start) {
+            if cur.is_some() {
+                path.close();
+            }
+            path.move_to(start);
+        }
+
+        match *curve {
+            OutlineCurve::Line(_, _) => path.line_to(end),
+            OutlineCurve::Quad(_, c, _) => path.quad_to(map(c), end),
+            OutlineCurve::Cubic(_, c1, c2, _) => path.cubic_to(map(c1), map(c2), end),
+        };
+        cur = Some(end);
+    }
+    if cur.is_some() {
+        path.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ab_glyph::FontRef;
+
+    use super::*;
+    use crate::renderer::{command::PixelRect, transform::Transform2D};
+
+    fn inter() -> FontRef<'static> {
+        FontRef::try_from_slice(include_bytes!("../../fonts/Inter_28pt-Regular.ttf")).unwrap()
+    }
+
+    #[test]
+    fn glyphs_sit_on_the_baseline() {
+        let font = inter();
+        let p = text_path(&font, "Hx", 40.0, [10.0, 100.0]);
+        let [x0, y0, x1, y1] = p.bounds().unwrap();
+        assert!(x0 >= 10.0 && x1 <= 10.0 + text_width(&font, "Hx", 40.0) + 1.0);
+        // cap height is somewhere below the line height, nothing hangs under the baseline
+        assert!(y0 > 60.0 && y0 < 90.0, "{y0}");
+        assert!((y1 - 100.0).abs() < 0.5, "{y1}");
+
+        // filled glyphs actually cover something
+        let cov = p.coverage(PixelRect::new(0, 0, 128, 128));
+        assert!(cov.iter().sum::<f32>() > 100.0);
+    }
+
+    #[test]
+    fn scaled_text_matches_bigger_text() {
+        let font = inter();
+        let small = text_path(&font, "code", 16.0, [0.0, 0.0]);
+        let zoomed = small.transformed(&Transform2D::scale(4.0, 4.0));
+        let big = text_path(&font, "code", 64.0, [0.0, 0.0]);
+
+        let (a, b) = (zoomed.bounds().unwrap(), big.bounds().unwrap());
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() < 1e-3, "{a} vs {b}");
+        }
+    }
+}
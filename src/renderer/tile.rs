@@ -0,0 +1,455 @@
+#![allow(dead_code)]
+
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::renderer::{
+    command::{self, DrawCmd, PixelRect},
+    frame::Frame,
+};
+
+/// How `Renderer::end_frame` rasterizes the recorded commands.
+///
+/// # Variants
+///
+/// - `Serial` - Replay every command over the whole frame on the calling thread.
+/// - `Tiled` - Split the frame into `tile_size` squares, bin the commands per tile
+///   and rasterize the tiles on up to `threads` worker threads
+///   (`None` uses every available core).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tiling {
+    Serial,
+    Tiled {
+        tile_size: u16,
+        threads: Option<NonZeroUsize>,
+    },
+}
+
+impl Tiling {
+    pub const DEFAULT_TILE_SIZE: u16 = 64;
+}
+
+impl Default for Tiling {
+    fn default() -> Self {
+        Tiling::Tiled {
+            tile_size: Self::DEFAULT_TILE_SIZE,
+            threads: None,
+        }
+    }
+}
+
+/// A frame cut into a grid of equally sized tiles (the last row/column may be smaller).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileGrid {
+    width: u16,
+    height: u16,
+    tile_size: u16,
+    cols: u16,
+    rows: u16,
+}
+
+impl TileGrid {
+    #[must_use]
+    pub fn new(width: u16, height: u16, tile_size: u16) -> Self {
+        let tile_size = tile_size.max(1);
+        Self {
+            width,
+            height,
+            tile_size,
+            cols: width.div_ceil(tile_size),
+            rows: height.div_ceil(tile_size),
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cols as usize * self.rows as usize
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The device space area of tile `i` (tiles are numbered row by row).
+    #[must_use]
+    #[inline]
+    pub fn tile(&self, i: usize) -> PixelRect {
+        let col = (i % self.cols as usize) as u16;
+        let row = (i / self.cols as usize) as u16;
+        let x0 = col * self.tile_size;
+        let y0 = row * self.tile_size;
+        PixelRect::new(
+            x0,
+            y0,
+            x0.saturating_add(self.tile_size).min(self.width),
+            y0.saturating_add(self.tile_size).min(self.height),
+        )
+    }
+
+    /// Sort commands into per-tile lists of indices, keeping submission order inside each tile.
+    #[must_use]
+    pub fn bin(&self, cmds: &[DrawCmd]) -> Vec<Vec<usize>> {
+        let mut bins = vec![Vec::new(); self.len()];
+        if self.is_empty() {
+            return bins;
+        }
+
+        let ts = self.tile_size;
+        for (i, cmd) in cmds.iter().enumerate() {
+            let b = match cmd.bounds() {
+                Some(b) => b.intersect(PixelRect::new(0, 0, self.width, self.height)),
+                None => PixelRect::new(0, 0, self.width, self.height),
+            };
+            if b.is_empty() {
+                continue;
+            }
+
+            // only walk the tiles the command can actually reach
+            for row in b.y0 / ts..=(b.y1 - 1) / ts {
+                for col in b.x0 / ts..=(b.x1 - 1) / ts {
+                    bins[row as usize * self.cols as usize + col as usize].push(i);
+                }
+            }
+        }
+        bins
+    }
+}
+
+/// Rasterize commands over the whole frame, in order, on the calling thread.
+///
+/// This is the reference path, tiled output must match it bit for bit.
+pub fn rasterize_serial(fb: &mut Frame, cmds: &[DrawCmd]) {
+    let area = PixelRect::new(0, 0, fb.width(), fb.height());
+    let data = fb.as_bytes_mut();
+    for cmd in cmds {
+        cmd.rasterize(area, data);
+    }
+}
+
+/// Rasterize commands tile by tile on a pool of scoped worker threads.
+///
+/// Every tile starts from the frame's current contents, replays only the commands
+/// binned to it and is copied back, so the result is identical to `rasterize_serial`.
+pub fn rasterize_tiled(
+    fb: &mut Frame,
+    cmds: &[DrawCmd],
+    tile_size: u16,
+    threads: Option<NonZeroUsize>,
+) {
+    let grid = TileGrid::new(fb.width(), fb.height(), tile_size);
+    if grid.is_empty() || cmds.is_empty() {
+        return;
+    }
+
+    let bins = grid.bin(cmds);
+    let threads = threads
+        .or_else(|| thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get)
+        .min(grid.len());
+
+    // nothing to gain from spinning up workers
+    if threads <= 1 {
+        for (i, bin) in bins.iter().enumerate() {
+            let area = grid.tile(i);
+            let mut tile = read_tile(fb, area);
+            for &c in bin {
+                cmds[c].rasterize(area, &mut tile);
+            }
+            write_tile(fb, area, &tile);
+        }
+        return;
+    }
+
+    let next = AtomicUsize::new(0);
+    let src: &Frame = fb;
+    let finished: Vec<(usize, Vec<u8>)> = thread::scope(|s| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= grid.len() {
+                            break;
+                        }
+                        if bins[i].is_empty() {
+                            continue;
+                        }
+
+                        let area = grid.tile(i);
+                        let mut tile = read_tile(src, area);
+                        for &c in &bins[i] {
+                            cmds[c].rasterize(area, &mut tile);
+                        }
+                        done.push((i, tile));
+                    }
+                    done
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().expect("tile worker panicked"))
+            .collect()
+    });
+
+    for (i, tile) in finished {
+        write_tile(fb, grid.tile(i), &tile);
+    }
+}
+
+/// Copy a tile's pixels out of a frame into a tightly packed buffer.
+fn read_tile(fb: &Frame, area: PixelRect) -> Vec<u8> {
+    let full = PixelRect::new(0, 0, fb.width(), fb.height());
+    command::copy_out(full, fb.as_slice(), area)
+}
+
+/// Copy a tightly packed tile buffer back into its place in a frame.
+fn write_tile(fb: &mut Frame, area: PixelRect, tile: &[u8]) {
+    let full = PixelRect::new(0, 0, fb.width(), fb.height());
+    command::copy_in(full, fb.as_bytes_mut(), area, tile);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{ColorFloat, model::Color};
+    use crate::renderer::clip::{Clip, ClipChain, Mask, MaskMode};
+    use crate::renderer::gradient::{ColorStop, Gradient, SpreadMode};
+    use crate::renderer::paint::Paint;
+    use crate::renderer::path::Path;
+    use crate::renderer::sample::{Filter, Texture};
+    use crate::renderer::transform::Transform2D;
+    use std::sync::Arc;
+
+    /// A little deterministic xorshift so the scenes don't need a rand dependency.
+    fn next(state: &mut u32) -> u32 {
+        *state ^= *state << 13;
+        *state ^= *state >> 17;
+        *state ^= *state << 5;
+        *state
+    }
+
+    fn random_scene(width: u16, height: u16, count: usize, seed: u32) -> Vec<DrawCmd> {
+        let mut s = seed;
+        let color = |s: &mut u32| {
+            let v = next(s).to_le_bytes();
+            Paint::from(Color::from_rgba(v))
+        };
+        let glow = Paint::from(
+            Gradient::radial(
+                [width as ColorFloat / 2.0, height as ColorFloat / 2.0],
+                width.max(height) as ColorFloat / 3.0,
+                &[
+                    ColorStop::new(0.0, Color::new(137, 180, 250, 255)),
+                    ColorStop::new(1.0, Color::new(30, 30, 46, 255)),
+                ],
+            )
+            .with_spread(SpreadMode::Reflect)
+            .with_dither(true),
+        );
+
+        // a soft horizontal fade to mask with
+        let mut fade = Frame::new(97, 61);
+        for (i, px) in fade.as_bytes_mut().chunks_exact_mut(4).enumerate() {
+            let v = ((i % 97) * 255 / 96) as u8;
+            px.copy_from_slice(&[v, v, v, 255 - v / 2]);
+        }
+        let fade = Arc::new(fade);
+        // images resample every pixel they touch, keep them small
+        let mut badge = Frame::new(23, 13);
+        for (i, px) in badge.as_bytes_mut().chunks_exact_mut(4).enumerate() {
+            let v = (i * 37 % 256) as u8;
+            px.copy_from_slice(&[v, 255 - v, 90, if i % 5 == 0 { 0 } else { 200 }]);
+        }
+        let badge = Arc::new(Texture::new(&badge));
+        let window = Arc::new(ClipChain::new(vec![
+            Clip::Rect(PixelRect::new(8, 4, width.saturating_sub(8), height)),
+            Path::rounded_rect(20.5, 10.25, width as f32 * 0.7, height as f32 * 0.6, 24.0).into(),
+        ]));
+
+        let mut cmds = vec![DrawCmd::Clear(Color::new(30, 30, 46, 255).into())];
+        for _ in 0..count {
+            // allow some commands to hang off the right/bottom edges
+            let x = (next(&mut s) % (width as u32 + 16)) as u16;
+            let y = (next(&mut s) % (height as u32 + 16)) as u16;
+            let cmd = match next(&mut s) % 9 {
+                0 => DrawCmd::Pixel {
+                    x,
+                    y,
+                    paint: color(&mut s),
+                },
+                1 => DrawCmd::HSpan {
+                    y,
+                    x0: x,
+                    x1: (next(&mut s) % (width as u32 + 16)) as u16,
+                    paint: color(&mut s),
+                },
+                2 => DrawCmd::Rect {
+                    rect: PixelRect::from_xywh(
+                        x,
+                        y,
+                        (next(&mut s) % 200) as u16,
+                        (next(&mut s) % 200) as u16,
+                    ),
+                    paint: color(&mut s),
+                },
+                3 => DrawCmd::Rect {
+                    rect: PixelRect::from_xywh(
+                        x,
+                        y,
+                        (next(&mut s) % 200) as u16,
+                        (next(&mut s) % 200) as u16,
+                    ),
+                    paint: glow.clone(),
+                },
+                4 => DrawCmd::FillPath {
+                    path: Arc::new(Path::circle(
+                        x as f32 + 0.3,
+                        y as f32 + 0.7,
+                        (next(&mut s) % 150) as f32 + 0.5,
+                    )),
+                    paint: if next(&mut s).is_multiple_of(2) {
+                        glow.clone()
+                    } else {
+                        color(&mut s)
+                    },
+                },
+                5 => DrawCmd::Clipped {
+                    cmd: Box::new(DrawCmd::Clear(color(&mut s))),
+                    clip: Arc::clone(&window),
+                },
+                6 => DrawCmd::Clipped {
+                    cmd: Box::new(DrawCmd::Rect {
+                        rect: PixelRect::from_xywh(x, y, 150, 90),
+                        paint: color(&mut s),
+                    }),
+                    clip: Arc::new(ClipChain::new(vec![
+                        Clip::Path(Arc::new(Path::ellipse(
+                            x as f32 + 40.0,
+                            y as f32 + 30.0,
+                            60.0,
+                            33.3,
+                        ))),
+                        Clip::Mask(Mask::new(
+                            Arc::clone(&fade),
+                            x as i32 - 10,
+                            y as i32 + 5,
+                            if next(&mut s).is_multiple_of(2) {
+                                MaskMode::Alpha
+                            } else {
+                                MaskMode::Luminance
+                            },
+                        )),
+                    ])),
+                },
+                7 => DrawCmd::Image {
+                    image: Arc::clone(&badge),
+                    // mostly upscales, sometimes strong enough downscales to hit the mips
+                    transform: Transform2D::rotate(next(&mut s) as f32)
+                        .then(Transform2D::scale(
+                            3.7,
+                            2.5 / (next(&mut s) % 12 + 1) as f32,
+                        ))
+                        .then(Transform2D::translate(x as f32 + 0.25, y as f32)),
+                    filter: [
+                        Filter::Nearest,
+                        Filter::Bilinear,
+                        Filter::Bicubic,
+                        Filter::Lanczos3,
+                    ][(next(&mut s) % 4) as usize],
+                },
+                _ => DrawCmd::Clear(color(&mut s)),
+            };
+            cmds.push(cmd);
+        }
+        cmds
+    }
+
+    fn assert_tiled_matches_serial(
+        width: u16,
+        height: u16,
+        tile_size: u16,
+        threads: usize,
+        seed: u32,
+    ) {
+        // 4k frames get fewer commands, every full clear is 33MB of writes
+        let count = if width as usize * height as usize > 1 << 20 {
+            40
+        } else {
+            500
+        };
+        let cmds = random_scene(width, height, count, seed);
+
+        let mut serial = Frame::new(width, height);
+        rasterize_serial(&mut serial, &cmds);
+
+        let mut tiled = Frame::new(width, height);
+        rasterize_tiled(&mut tiled, &cmds, tile_size, NonZeroUsize::new(threads));
+
+        assert!(
+            serial.as_slice() == tiled.as_slice(),
+            "tiled output differs from serial ({width}x{height}, tile {tile_size}, {threads} threads)"
+        );
+    }
+
+    #[test]
+    fn tiled_matches_serial_on_uneven_grids() {
+        for (i, &(w, h, ts)) in [
+            (1, 1, 64),
+            (63, 65, 64),
+            (200, 113, 16),
+            (300, 7, 32),
+            (97, 301, 1),
+        ]
+        .iter()
+        .enumerate()
+        {
+            assert_tiled_matches_serial(w, h, ts, 4, 0x9E37_79B9 ^ i as u32);
+        }
+    }
+
+    #[test]
+    fn tiled_matches_serial_for_any_thread_count() {
+        for threads in [1, 2, 3, 8, 64] {
+            assert_tiled_matches_serial(320, 180, 64, threads, 0xC0DE + threads as u32);
+        }
+    }
+
+    #[test]
+    fn tiled_matches_serial_at_4k() {
+        assert_tiled_matches_serial(3840, 2160, Tiling::DEFAULT_TILE_SIZE, 0, 0xF00D);
+    }
+
+    #[test]
+    fn binning_only_touches_covered_tiles() {
+        let grid = TileGrid::new(256, 256, 64);
+        let cmds = [
+            DrawCmd::Pixel {
+                x: 70,
+                y: 10,
+                paint: Color::RED.into(),
+            },
+            DrawCmd::Rect {
+                rect: PixelRect::new(60, 60, 70, 70),
+                paint: Color::RED.into(),
+            },
+            DrawCmd::Rect {
+                rect: PixelRect::new(300, 300, 310, 310),
+                paint: Color::RED.into(),
+            },
+        ];
+        let bins = grid.bin(&cmds);
+
+        assert_eq!(bins[0], vec![1]);
+        assert_eq!(bins[1], vec![0, 1]);
+        assert_eq!(bins[4], vec![1]);
+        assert_eq!(bins[4 + 1], vec![1]);
+        assert_eq!(bins.iter().map(Vec::len).sum::<usize>(), 5);
+    }
+}
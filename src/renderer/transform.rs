@@ -0,0 +1,235 @@
+#![allow(dead_code)]
+
+use core::ops::Mul;
+
+/// A 2D affine transform in device pixels.
+///
+/// Maps `(x, y)` to `(a*x + c*y + e, b*x + d*y + f)`, the same layout as canvas' `setTransform`
+/// and SVG's `matrix()`.
+///
+/// Transforms compose right to left like matrices: `t1 * t2` applies `t2` first.
+/// `then` reads left to right instead.
+///
+/// # Examples
+///
+/// ```
+/// use codimate::renderer::transform::Transform2D;
+///
+/// // spin a code card around its own center
+/// let t = Transform2D::translate(-320.0, -180.0)
+///     .then(Transform2D::rotate_deg(15.0))
+///     .then(Transform2D::translate(960.0, 540.0));
+/// let [x, y] = t.apply([320.0, 180.0]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform2D {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Transform2D {
+    pub const IDENTITY: Self = Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    #[must_use]
+    #[inline]
+    pub const fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn translate(x: f32, y: f32) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn scale(x: f32, y: f32) -> Self {
+        Self::new(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+
+    /// Rotate clockwise on screen (y points down) by an angle in radians.
+    #[must_use]
+    #[inline]
+    pub fn rotate(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn rotate_deg(degrees: f32) -> Self {
+        Self::rotate(degrees.to_radians())
+    }
+
+    /// Shear along x by `x` radians and along y by `y` radians, like CSS `skew()`.
+    #[must_use]
+    #[inline]
+    pub fn skew(x: f32, y: f32) -> Self {
+        Self::new(1.0, y.tan(), x.tan(), 1.0, 0.0, 0.0)
+    }
+
+    /// Apply `self` first, then `next`.
+    #[must_use]
+    #[inline]
+    pub fn then(self, next: Transform2D) -> Self {
+        next * self
+    }
+
+    /// The transform that undoes this one, if it isn't degenerate.
+    #[must_use]
+    pub fn invert(&self) -> Option<Self> {
+        // only a determinant of exactly 0 is degenerate, strong downscales (0.001 a side is
+        // 1e-6) invert fine as long as 1 / det still fits in an f32
+        let det = self.determinant();
+        let inv = 1.0 / det;
+        if !det.is_finite() || !inv.is_finite() {
+            return None;
+        }
+        let a = self.d * inv;
+        let b = -self.b * inv;
+        let c = -self.c * inv;
+        let d = self.a * inv;
+        Some(Self::new(
+            a,
+            b,
+            c,
+            d,
+            -(a * self.e + c * self.f),
+            -(b * self.e + d * self.f),
+        ))
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn determinant(&self) -> f32 {
+        self.a * self.d - self.b * self.c
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    /// Transform a point.
+    #[must_use]
+    #[inline]
+    pub fn apply(&self, p: [f32; 2]) -> [f32; 2] {
+        [
+            self.a * p[0] + self.c * p[1] + self.e,
+            self.b * p[0] + self.d * p[1] + self.f,
+        ]
+    }
+
+    /// Transform a direction (ignores translation).
+    #[must_use]
+    #[inline]
+    pub fn apply_vector(&self, v: [f32; 2]) -> [f32; 2] {
+        [self.a * v[0] + self.c * v[1], self.b * v[0] + self.d * v[1]]
+    }
+
+    /// How much the transform enlarges things on average (1.0 = no change).
+    ///
+    /// Used to pick how much detail resampling needs.
+    #[must_use]
+    #[inline]
+    pub fn scale_factor(&self) -> f32 {
+        self.determinant().abs().sqrt()
+    }
+
+    /// The axis aligned box around a transformed rectangle, as `[x0, y0, x1, y1]`.
+    #[must_use]
+    pub fn map_rect(&self, r: [f32; 4]) -> [f32; 4] {
+        let corners = [
+            self.apply([r[0], r[1]]),
+            self.apply([r[2], r[1]]),
+            self.apply([r[2], r[3]]),
+            self.apply([r[0], r[3]]),
+        ];
+        corners.iter().fold(
+            [
+                f32::INFINITY,
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+                f32::NEG_INFINITY,
+            ],
+            |b, p| {
+                [
+                    b[0].min(p[0]),
+                    b[1].min(p[1]),
+                    b[2].max(p[0]),
+                    b[3].max(p[1]),
+                ]
+            },
+        )
+    }
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for Transform2D {
+    type Output = Transform2D;
+
+    /// Matrix product: the result applies `rhs` first, then `self`.
+    fn mul(self, rhs: Transform2D) -> Self::Output {
+        Transform2D::new(
+            self.a * rhs.a + self.c * rhs.b,
+            self.b * rhs.a + self.d * rhs.b,
+            self.a * rhs.c + self.c * rhs.d,
+            self.b * rhs.c + self.d * rhs.d,
+            self.a * rhs.e + self.c * rhs.f + self.e,
+            self.b * rhs.e + self.d * rhs.f + self.f,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: [f32; 2], b: [f32; 2]) -> bool {
+        (a[0] - b[0]).abs() < 1e-4 && (a[1] - b[1]).abs() < 1e-4
+    }
+
+    #[test]
+    fn then_applies_left_to_right() {
+        let t = Transform2D::scale(2.0, 2.0).then(Transform2D::translate(10.0, 0.0));
+        assert!(close(t.apply([1.0, 1.0]), [12.0, 2.0]));
+        assert_eq!(
+            t,
+            Transform2D::translate(10.0, 0.0) * Transform2D::scale(2.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn rotation_is_clockwise_on_screen() {
+        let t = Transform2D::rotate_deg(90.0);
+        assert!(close(t.apply([1.0, 0.0]), [0.0, 1.0]));
+    }
+
+    #[test]
+    fn invert_round_trips() {
+        let t = Transform2D::translate(3.0, -7.0)
+            .then(Transform2D::rotate(0.7))
+            .then(Transform2D::skew(0.2, -0.1))
+            .then(Transform2D::scale(1.5, 0.5));
+        let inv = t.invert().unwrap();
+        for p in [[0.0, 0.0], [12.5, -3.0], [100.0, 40.0]] {
+            assert!(close(inv.apply(t.apply(p)), p));
+        }
+        assert!(Transform2D::scale(0.0, 1.0).invert().is_none());
+
+        let tiny = Transform2D::scale(0.001, 0.001);
+        let inv = tiny.invert().expect("a strong downscale isn't degenerate");
+        assert!(close(inv.apply(tiny.apply([250.0, -40.0])), [250.0, -40.0]));
+    }
+}
@@ -0,0 +1,590 @@
+// watch mode: poll a scene file and every file it reads, and once they've stopped changing
+// for a moment, reload the scene and re-render only what looks different. polling (rather
+// than inotify and friends) behaves the same everywhere, including network drives and
+// editors that save by writing a temp file and renaming it, and a few stats every 100ms
+// cost nothing next to rendering. files are only read (and hashed) while their mtime is
+// recent enough that a second save might not have moved it, see `Watched::changed`.
+//
+// "looks different" is worked out from the scenes themselves: the canvas, every node's
+// shape and its animated state at each frame time. fonts are the exception, only the
+// parsed font ends up in the scene, so a font file changing re-renders everything.
+
+use core::{ops::ControlFlow, ops::Range};
+use std::{
+    fs,
+    hash::{DefaultHasher, Hasher},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::renderer::{
+    export::{self, ExportError, ExportOptions, OutputFormat},
+    scene::{NodeState, Scene, Shape},
+    scene_file::{self, Dependency, SceneFileError},
+};
+
+/// What `watch` keeps up to date.
+///
+/// # Variants
+///
+/// - `Export` - A full export as `export::export` writes it. PNG sequences only rewrite the
+///   frames that changed, GIFs and videos have to be encoded again from the start.
+/// - `Frame` - A single preview PNG of the scene at `time` seconds.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Target {
+    Export {
+        output: PathBuf,
+        options: ExportOptions,
+    },
+    Frame {
+        output: PathBuf,
+        time: f32,
+        size: Option<[u16; 2]>,
+        quality: u8,
+    },
+}
+
+/// How `watch` watches.
+///
+/// # Fields
+///
+/// - `poll` (`Duration`) - How often to look at the files.
+/// - `debounce` (`Duration`) - How long the files have to stay unchanged before reloading,
+///   so a burst of saves renders once.
+/// - `fps` (`Option<f32>`) - Overrides the scene's frame rate on every load.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WatchOptions {
+    pub poll: Duration,
+    pub debounce: Duration,
+    pub fps: Option<f32>,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            poll: Duration::from_millis(100),
+            debounce: Duration::from_millis(200),
+            fps: None,
+        }
+    }
+}
+
+/// What happened after a (re)load.
+///
+/// # Variants
+///
+/// - `Rendered` - These frames were written. For a `Target::Frame`, the frame its time
+///   falls in.
+/// - `Unchanged` - Files changed but nothing that gets drawn did, so nothing was written.
+///   PNG sequence frames past the end of a scene that got shorter are still deleted.
+/// - `SceneError` - The scene file doesn't load. The last good render stays as it is.
+/// - `RenderError` - Rendering or writing failed. The next change renders everything again.
+#[derive(Debug)]
+pub enum WatchEvent {
+    Rendered(Range<u32>),
+    Unchanged,
+    SceneError(SceneFileError),
+    RenderError(ExportError),
+}
+
+/// Render a scene file, then keep the output up to date as the file, its code files and its
+/// fonts change. Runs until `on_event` breaks.
+///
+/// # Arguments
+///
+/// - `path` (`&Path`) - The scene file.
+/// - `target` (`&Target`) - What to render.
+/// - `options` (`&WatchOptions`) - Polling and debouncing.
+/// - `on_event` (`impl FnMut(WatchEvent) -> ControlFlow<()>`) - Called after the first render
+///   and after every reload.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::ops::ControlFlow;
+/// use codimate::renderer::watch::{self, Target, WatchEvent};
+///
+/// let target = Target::Frame { output: "preview.png".into(), time: 2.0, size: None, quality: 0 };
+/// watch::watch("intro.toml".as_ref(), &target, &Default::default(), |event| {
+///     if let WatchEvent::SceneError(e) = event {
+///         eprintln!("{e}");
+///     }
+///     ControlFlow::Continue(())
+/// });
+/// ```
+pub fn watch(
+    path: &Path,
+    target: &Target,
+    options: &WatchOptions,
+    mut on_event: impl FnMut(WatchEvent) -> ControlFlow<()>,
+) {
+    let mut sources = Sources::new(path);
+    let mut last: Option<(Scene, Vec<Dependency>)> = None;
+    // set when the last render can't be trusted to diff against
+    let mut redo_all = true;
+
+    loop {
+        let (loaded, read) = scene_file::load_tracked(path);
+        let event = match loaded {
+            Err(e) => {
+                // keep watching what the good version used, plus whatever this one got to
+                sources.track(&read, true);
+                WatchEvent::SceneError(e)
+            }
+            Ok(mut scene) => {
+                if let Some(fps) = options.fps {
+                    scene.fps = fps;
+                }
+                sources.track(&read, false);
+                let old = match &last {
+                    Some((old, old_read)) if !redo_all && fonts(old_read).eq(fonts(&read)) => {
+                        Some(old)
+                    }
+                    _ => None,
+                };
+                let event = update(target, old, &scene);
+                redo_all = matches!(event, WatchEvent::RenderError(_));
+                last = Some((scene, read));
+                event
+            }
+        };
+        if on_event(event).is_break() {
+            return;
+        }
+        redo_all |= sources.wait(options);
+    }
+}
+
+fn fonts(read: &[Dependency]) -> impl Iterator<Item = &Dependency> {
+    read.iter().filter(|d| matches!(d, Dependency::Font(_)))
+}
+
+/// Render what changed between `old` and `scene`, or everything without an `old`.
+fn update(target: &Target, old: Option<&Scene>, scene: &Scene) -> WatchEvent {
+    match target {
+        Target::Export { output, options } => {
+            let count = scene.frame_count();
+            let wanted = options.frames.clone().unwrap_or(0..count);
+            let wanted = wanted.start..wanted.end.min(count);
+            let mut options = options.clone();
+            if let Some(old) = old {
+                let png = OutputFormat::from_path(output) == Some(OutputFormat::PngSequence);
+                if png {
+                    // a shorter scene leaves the old one's last frames behind
+                    let old_end = options.frames.as_ref().map_or(u32::MAX, |f| f.end);
+                    let stale = count.max(wanted.start)..old_end.min(old.frame_count());
+                    if let Err(e) = export::remove_png_frames(output, stale) {
+                        return WatchEvent::RenderError(e);
+                    }
+                }
+                let Some(changed) = affected_frames(old, scene) else {
+                    return WatchEvent::Unchanged;
+                };
+                if png {
+                    let frames = changed.start.max(wanted.start)..changed.end.min(wanted.end);
+                    if frames.is_empty() {
+                        return WatchEvent::Unchanged;
+                    }
+                    options.frames = Some(frames);
+                }
+            }
+            let frames = options.frames.clone().unwrap_or(wanted);
+            match export::export(scene, output, &options, |_, _| {}) {
+                Ok(_) => WatchEvent::Rendered(frames.start..frames.end.min(count)),
+                Err(e) => WatchEvent::RenderError(e),
+            }
+        }
+        Target::Frame {
+            output,
+            time,
+            size,
+            quality,
+        } => {
+            if let Some(old) = old
+                && !differs(old, scene, *time)
+            {
+                return WatchEvent::Unchanged;
+            }
+            let fb = export::render_frame(scene, *time, *size);
+            let frame = (*time * scene.fps) as u32;
+            match export::save_png(&fb, output, *quality) {
+                Ok(()) => WatchEvent::Rendered(frame..frame + 1),
+                Err(e) => WatchEvent::RenderError(e),
+            }
+        }
+    }
+}
+
+/// The frames of `new` that don't look the same as in `old`, from the first one that
+/// changed to the last. Frames `old` didn't have count as changed.
+///
+/// # Returns
+///
+/// - `Option<Range<u32>>` - The changed frames, `None` if every frame looks the same.
+///
+/// # Examples
+///
+/// ```no_run
+/// use codimate::renderer::{scene_file, watch};
+///
+/// let old = scene_file::load("intro.toml".as_ref())?;
+/// // ...edit intro.toml...
+/// let new = scene_file::load("intro.toml".as_ref())?;
+/// if let Some(frames) = watch::affected_frames(&old, &new) {
+///     println!("frames {frames:?} need rendering again");
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[must_use]
+pub fn affected_frames(old: &Scene, new: &Scene) -> Option<Range<u32>> {
+    let (old_count, count) = (old.frame_count(), new.frame_count());
+    let mut changed =
+        (0..count).filter(|&f| f >= old_count || differs(old, new, new.frame_time(f)));
+    let first = changed.next()?;
+    let last = changed.next_back().unwrap_or(first);
+    Some(first..last + 1)
+}
+
+/// Whether the two scenes draw anything differently at `time`.
+fn differs<'a>(old: &'a Scene, new: &'a Scene, time: f32) -> bool {
+    if (old.width, old.height, old.fps, old.background)
+        != (new.width, new.height, new.fps, new.background)
+    {
+        return true;
+    }
+    let visible = |s: &NodeState| s.opacity > 0.0 && s.color.into_rgba()[3] > 0;
+    let states = |scene: &'a Scene| {
+        scene
+            .nodes()
+            .map(move |(id, node)| (&node.shape, scene.state_at(id, time)))
+    };
+
+    // nodes are matched by position in the draw order, one that only exists in one of the
+    // scenes (or moved) changes the frame wherever it's visible
+    let (mut old, mut new) = (states(old), states(new));
+    loop {
+        let changed = match (old.next(), new.next()) {
+            (Some((a, sa)), Some((b, sb))) => {
+                (visible(&sa) || visible(&sb)) && (sa != sb || !same_shape(a, b))
+            }
+            (Some((_, s)), None) | (None, Some((_, s))) => visible(&s),
+            (None, None) => return false,
+        };
+        if changed {
+            return true;
+        }
+    }
+}
+
+/// Shapes compare by value, fonts only by whether there is one (font files changing is
+/// caught by watching them).
+fn same_shape(a: &Shape, b: &Shape) -> bool {
+    use Shape::*;
+    match (a, b) {
+        (
+            Text {
+                text: t1,
+                size: s1,
+                font: f1,
+            },
+            Text {
+                text: t2,
+                size: s2,
+                font: f2,
+            },
+        ) => t1 == t2 && s1 == s2 && f1.is_some() == f2.is_some(),
+        (
+            Code {
+                source: t1,
+                size: s1,
+                font: f1,
+                background: b1,
+                padding: p1,
+            },
+            Code {
+                source: t2,
+                size: s2,
+                font: f2,
+                background: b2,
+                padding: p2,
+            },
+        ) => t1 == t2 && s1 == s2 && f1.is_some() == f2.is_some() && b1 == b2 && p1 == p2,
+        (
+            Rect {
+                size: s1,
+                radius: r1,
+            },
+            Rect {
+                size: s2,
+                radius: r2,
+            },
+        ) => s1 == s2 && r1 == r2,
+        (Circle { radius: r1 }, Circle { radius: r2 }) => r1 == r2,
+        (Line { end: e1, width: w1 }, Line { end: e2, width: w2 }) => e1 == e2 && w1 == w2,
+        (Polygon { points: p1 }, Polygon { points: p2 }) => p1 == p2,
+        _ => false,
+    }
+}
+
+// mtime and length of a file, `None` while it doesn't exist
+type Stamp = Option<(SystemTime, u64)>;
+
+// mtimes can be as coarse as this (FAT, some network drives), so two saves of the same
+// length this close together only tell apart by their contents
+const MTIME_GRANULARITY: Duration = Duration::from_secs(2);
+
+fn stamp(path: &Path) -> Stamp {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+fn hash(path: &Path) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(&fs::read(path).ok()?);
+    Some(hasher.finish())
+}
+
+struct Watched {
+    path: PathBuf,
+    font: bool,
+    stamp: Stamp,
+    // the contents' hash, only kept while the mtime is recent
+    hash: Option<u64>,
+}
+
+impl Watched {
+    fn new(path: &Path, font: bool) -> Self {
+        let mut watched = Watched {
+            path: path.to_owned(),
+            font,
+            stamp: None,
+            hash: None,
+        };
+        watched.changed();
+        watched
+    }
+
+    /// Look at the file again.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - Whether it changed since the last look.
+    fn changed(&mut self) -> bool {
+        let stamp = stamp(&self.path);
+        // once the mtime is older than the granularity, any new save moves it
+        let recent = stamp.is_some_and(|(mtime, _)| {
+            mtime
+                .elapsed()
+                .ok()
+                .is_none_or(|age| age < MTIME_GRANULARITY)
+        });
+        let hash = if recent { hash(&self.path) } else { None };
+        let changed =
+            stamp != self.stamp || (hash.is_some() && self.hash.is_some() && hash != self.hash);
+        self.stamp = stamp;
+        self.hash = hash;
+        changed
+    }
+}
+
+/// The scene file (always first) and the files it reads.
+struct Sources(Vec<Watched>);
+
+impl Sources {
+    fn new(scene: &Path) -> Self {
+        Sources(vec![Watched::new(scene, false)])
+    }
+
+    /// Watch `read` too. Files already watched keep their stamp, so a change made while the
+    /// scene was loading still gets noticed.
+    fn track(&mut self, read: &[Dependency], keep_others: bool) {
+        if !keep_others {
+            let scene = self.0[0].path.clone();
+            self.0
+                .retain(|w| w.path == scene || read.iter().any(|d| d.path() == w.path));
+        }
+        for dependency in read {
+            if !self.0.iter().any(|w| w.path == dependency.path()) {
+                let font = matches!(dependency, Dependency::Font(_));
+                self.0.push(Watched::new(dependency.path(), font));
+            }
+        }
+    }
+
+    /// Block until something changes and then stays put for `options.debounce`.
+    ///
+    /// # Returns
+    ///
+    /// - `bool` - Whether a font was among the changes.
+    fn wait(&mut self, options: &WatchOptions) -> bool {
+        let (mut changed, mut fonts) = (false, false);
+        let mut quiet_since = Instant::now();
+        loop {
+            thread::sleep(options.poll);
+            for watched in &mut self.0 {
+                if watched.changed() {
+                    changed = true;
+                    fonts |= watched.font;
+                    quiet_since = Instant::now();
+                }
+            }
+            if changed && quiet_since.elapsed() >= options.debounce {
+                return fonts;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::{
+        color::model::Color,
+        renderer::{
+            easing::Easing,
+            scene::{Node, Property, Tween, Value},
+        },
+    };
+
+    fn scene(label: &str) -> Scene {
+        let mut scene = Scene::new(32, 32);
+        scene.fps = 10.0;
+        scene.duration = 2.0;
+        let dot = scene.add(Node::new(
+            Shape::Circle { radius: 4.0 },
+            [8.0, 8.0],
+            Color::RED,
+        ));
+        let label = scene.add(Node {
+            opacity: 0.0,
+            ..Node::new(
+                Shape::Text {
+                    text: label.into(),
+                    size: 8.0,
+                    font: None,
+                },
+                [0.0, 16.0],
+                Color::WHITE,
+            )
+        });
+        for (node, property, to, start) in [
+            (dot, Property::X, Value::Number(24.0), 0.0),
+            (label, Property::Opacity, Value::Number(1.0), 1.0),
+        ] {
+            scene
+                .animate(Tween {
+                    node,
+                    property,
+                    from: None,
+                    to,
+                    start,
+                    duration: 0.5,
+                    easing: Easing::Linear,
+                })
+                .unwrap();
+        }
+        scene
+    }
+
+    #[test]
+    fn only_frames_that_look_different_are_affected() {
+        let old = scene("a");
+        assert_eq!(affected_frames(&old, &scene("a")), None);
+        // the label is hidden until its fade starts at 1s
+        assert_eq!(affected_frames(&old, &scene("b")), Some(11..20));
+
+        let mut slower = scene("a");
+        slower.duration = 3.0;
+        assert_eq!(affected_frames(&old, &slower), Some(20..30));
+        let mut moved = scene("a");
+        let dot = moved.nodes().next().unwrap().0;
+        moved.node_mut(dot).unwrap().position[1] = 9.0;
+        assert_eq!(affected_frames(&old, &moved), Some(0..20));
+        moved.background = Color::BLUE;
+        assert_eq!(affected_frames(&old, &moved), Some(0..20));
+    }
+
+    #[test]
+    fn same_length_saves_are_seen() {
+        let path = std::env::temp_dir().join(format!("codimate-stamp-{}", std::process::id()));
+        fs::write(&path, "x = 1").unwrap();
+        let mut watched = Watched::new(&path, false);
+        let mtime = fs::metadata(&path).unwrap().modified().unwrap();
+        fs::write(&path, "x = 2").unwrap();
+        // what a filesystem with coarse mtimes shows for a second save in the same tick
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(mtime).unwrap();
+        assert!(watched.changed());
+        assert!(!watched.changed());
+
+        // files that haven't been saved in a while aren't read at all
+        file.set_modified(mtime - Duration::from_secs(60)).unwrap();
+        assert!(watched.changed());
+        assert_eq!(watched.hash, None);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rerenders_changed_frames_once_saves_settle() {
+        let dir = std::env::temp_dir().join(format!("codimate-watch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let scene_path = dir.join("scene.toml");
+        let source = "[canvas]\nwidth = 32\nheight = 32\nfps = 10\nduration = 1\n\n\
+                      [[objects]]\nid = \"code\"\ntype = \"code\"\nfile = \"code.py\"\nsize = 8\nopacity = 0\n\n\
+                      [[timeline]]\ntarget = \"code\"\nproperty = \"opacity\"\nto = 1\nstart = 0.5\nduration = 0\n";
+        fs::write(&scene_path, source).unwrap();
+        fs::write(dir.join("code.py"), "x = 1").unwrap();
+
+        let target = Target::Export {
+            output: dir.join("out/"),
+            options: ExportOptions::default(),
+        };
+        let options = WatchOptions {
+            poll: Duration::from_millis(10),
+            // well clear of the gap between the two quick saves below, even on a busy machine
+            debounce: Duration::from_millis(500),
+            fps: None,
+        };
+        let (tx, rx) = mpsc::channel();
+        let watcher = thread::spawn(move || {
+            let mut events = 0;
+            watch(&scene_path, &target, &options, |event| {
+                tx.send(event).unwrap();
+                events += 1;
+                if events == 4 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+        });
+        let next = || rx.recv_timeout(Duration::from_secs(10)).unwrap();
+
+        assert!(matches!(next(), WatchEvent::Rendered(r) if r == (0..10)));
+        // two quick saves, one render, and only of the frames the code shows up in
+        fs::write(dir.join("code.py"), "x = 22").unwrap();
+        thread::sleep(Duration::from_millis(20));
+        fs::write(dir.join("code.py"), "x = 333").unwrap();
+        assert!(matches!(next(), WatchEvent::Rendered(r) if r == (5..10)));
+        assert!(dir.join("out/frame_0009.png").exists());
+
+        // cutting the scene short draws nothing new but drops the frames past its end
+        fs::write(
+            dir.join("scene.toml"),
+            source.replace("duration = 1\n", "duration = 0.8\n"),
+        )
+        .unwrap();
+        assert!(matches!(next(), WatchEvent::Unchanged));
+        assert!(dir.join("out/frame_0007.png").exists());
+        assert!(!dir.join("out/frame_0008.png").exists());
+
+        fs::write(dir.join("scene.toml"), "[canvas]\nwidth = 0").unwrap();
+        assert!(matches!(next(), WatchEvent::SceneError(_)));
+        watcher.join().unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
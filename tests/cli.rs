@@ -0,0 +1,142 @@
+// Runs the `codimate` binary on small scene files and checks what it writes and
+// how it exits (0 ok, 1 bad scene, 2 bad arguments, 3 render/output failure).
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+const SCENE: &str = r##"
+[canvas]
+width = 64
+height = 32
+fps = 10
+duration = 1
+background = "#1e1e2e"
+
+[[objects]]
+id = "dot"
+type = "circle"
+radius = 8
+position = [16, 16]
+color = "tomato"
+
+[[timeline]]
+target = "dot"
+property = "x"
+to = 48
+"##;
+
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("codimate-cli-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn codimate(args: &[&str], cwd: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_codimate"))
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .expect("the binary should run")
+}
+
+fn stderr(out: &Output) -> String {
+    String::from_utf8_lossy(&out.stderr).into_owned()
+}
+
+/// Width and height from a PNG's IHDR chunk.
+fn png_size(path: &Path) -> (u32, u32) {
+    let bytes = fs::read(path).unwrap();
+    assert!(
+        bytes.starts_with(b"\x89PNG"),
+        "{} isn't a png",
+        path.display()
+    );
+    let be = |i: usize| u32::from_be_bytes(bytes[i..i + 4].try_into().unwrap());
+    (be(16), be(20))
+}
+
+#[test]
+fn renders_frames_and_sequences() {
+    let dir = dir("render");
+    fs::write(dir.join("dot.toml"), SCENE).unwrap();
+
+    let out = codimate(&["frame", "dot.toml", "-t", "0.5", "--width", "128"], &dir);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(png_size(&dir.join("dot.png")), (128, 64));
+
+    let out = codimate(
+        &[
+            "render", "dot.toml", "-o", "seq/", "--fps", "4", "--range", "0.5..",
+        ],
+        &dir,
+    );
+    assert!(out.status.success(), "{}", stderr(&out));
+    let mut frames: Vec<_> = fs::read_dir(dir.join("seq"))
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    frames.sort();
+    assert_eq!(frames, ["frame_0002.png", "frame_0003.png"]);
+
+    let out = codimate(&["render", "dot.toml", "-o", "dot.gif", "-q", "30"], &dir);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(
+        fs::read(dir.join("dot.gif"))
+            .unwrap()
+            .starts_with(b"GIF89a")
+    );
+
+    let out = codimate(&["info", "dot.toml"], &dir);
+    let text = String::from_utf8_lossy(&out.stdout);
+    assert!(text.contains("64x32 @ 10 fps"), "{text}");
+    assert!(text.contains("dot") && text.contains("circle"), "{text}");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn failures_exit_with_readable_errors() {
+    let dir = dir("fail");
+    fs::write(
+        dir.join("bad.toml"),
+        SCENE.replace("target = \"dot\"", "target = \"dto\""),
+    )
+    .unwrap();
+    fs::write(dir.join("dot.toml"), SCENE).unwrap();
+
+    let out = codimate(&["info", "bad.toml"], &dir);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(
+        stderr(&out).starts_with("error: bad.toml:16:1: unknown object `dto`"),
+        "{}",
+        stderr(&out)
+    );
+
+    let out = codimate(&["info", "missing.toml"], &dir);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(
+        stderr(&out).contains("couldn't read missing.toml"),
+        "{}",
+        stderr(&out)
+    );
+
+    let out = codimate(&["render", "dot.toml", "--range", "2..1"], &dir);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(
+        stderr(&out).contains("the range `2..1` is empty"),
+        "{}",
+        stderr(&out)
+    );
+
+    let out = codimate(&["render", "dot.toml", "-o", "dot.txt"], &dir);
+    assert_eq!(out.status.code(), Some(3));
+    assert!(
+        stderr(&out).contains("don't know how to write dot.txt"),
+        "{}",
+        stderr(&out)
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,48 @@
+// The color module has to build without std (and without alloc) so embedded preview tools
+// can reuse it. This builds the crate as a plain rlib for each feature set, since the
+// cdylib needs std for its panic handler.
+//
+// Unit tests always link std, so the color tests use std's float methods even without the
+// std feature. `math::tests::libm_matches_std` is what checks the libm math against them:
+//
+//   cargo test --lib --no-default-features --features alloc color::math
+
+use std::process::Command;
+
+fn build(features: &str) {
+    let manifest = env!("CARGO_MANIFEST_DIR");
+    let out = Command::new(env!("CARGO"))
+        .args([
+            "rustc",
+            "--lib",
+            "--crate-type",
+            "rlib",
+            "--no-default-features",
+        ])
+        .args(["--features", features])
+        // its own target dir, the outer cargo may still be holding the default one
+        .env("CARGO_TARGET_DIR", format!("{manifest}/target/no_std"))
+        .current_dir(manifest)
+        .output()
+        .expect("cargo should run");
+    assert!(
+        out.status.success(),
+        "no_std build with features [{features}] failed:\n{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
+
+#[test]
+fn color_builds_without_std() {
+    for features in [
+        "",
+        "alloc",
+        "srgb_lut",
+        "color_double_precision",
+        "alloc,srgb_lut",
+        "srgb_lut_16,color_double_precision",
+        "serde",
+    ] {
+        build(features);
+    }
+}
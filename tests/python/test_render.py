@@ -0,0 +1,52 @@
+from codimate import BlendMode, Color, Frame, Rect, Renderer, Timeline
+
+
+def test_drawing_into_frames(tmp_path):
+    renderer = Renderer(8, 4)
+    frame = renderer.new_frame()
+    assert (frame.width, frame.height) == (8, 4)
+    assert frame.get_pixel(0, 0) == Color.TRANSPARENT
+
+    renderer.clear(frame, "white")
+    renderer.rect(frame, 2, 1, 100, 100, Color.RED)
+    assert frame.get_pixel(0, 0) == Color.WHITE
+    assert frame.get_pixel(7, 3) == Color.RED
+    assert frame.get_pixel(8, 0) is None
+
+    source = Frame(2, 2)
+    renderer.clear(source, Color.BLUE)
+    renderer.blit_over(frame, source, -1, -1)
+    assert frame.get_pixel(0, 0) == Color.BLUE
+    assert frame.get_pixel(1, 1) == Color.WHITE
+
+    data = frame.to_bytes()
+    assert len(data) == 8 * 4 * 4
+    assert data[:4] == bytes([0, 0, 255, 255])
+
+    frame.save(tmp_path / "frame.png")
+    assert (tmp_path / "frame.png").stat().st_size > 0
+
+
+def test_rendering_a_timeline():
+    timeline = Timeline(16, 16, background="black")
+    timeline.add(Rect(8, 16, color="white"))
+    renderer = Renderer(32, 32)
+    frame = renderer.render(timeline, 0.0)
+    assert (frame.width, frame.height) == (32, 32)
+    assert frame.get_pixel(4, 4) == Color.WHITE
+    assert frame.get_pixel(28, 4) == Color.BLACK
+    # the same renderer draws every frame, each one into a frame of its own
+    assert renderer.render(timeline, 0.0).to_bytes() == frame.to_bytes()
+    # and the scaling it sets up doesn't leak into drawing by hand
+    renderer.rect(frame, 0, 0, 1, 1, "red")
+    assert frame.get_pixel(0, 0) == Color.RED
+    assert frame.get_pixel(1, 1) == Color.WHITE
+
+
+def test_blend_modes():
+    assert Color.RED.over("white") == Color.RED
+    assert Color(255, 0, 0, 0).over("white") == Color.WHITE
+    assert Color.WHITE.blend_over(Color.RED, BlendMode.MULTIPLY) == Color.RED
+    assert Color.WHITE.blend_over(Color.RED) == Color.WHITE
+    assert BlendMode.SCREEN != BlendMode.MULTIPLY
+    assert len({BlendMode.NORMAL, BlendMode.NORMAL}) == 1
@@ -0,0 +1,84 @@
+# Tests for the Python API. Build the extension first (`maturin develop`), then run
+# `pytest tests/python`.
+
+import pytest
+
+from codimate import (
+    Animation,
+    Circle,
+    Color,
+    FadeIn,
+    FadeOut,
+    Line,
+    Rect,
+    RenderError,
+    Scene,
+    Shift,
+    Text,
+    Timeline,
+)
+
+
+class Slide(Scene):
+    def construct(self):
+        title = Text("Hello", 16, position=(4, 4), color="tomato")
+        dot = Circle(4, position=(8, 24))
+        self.add(dot)
+        self.play(FadeIn(title), run_time=0.5)
+        self.wait(0.5)
+        self.play(Shift(dot, (16, 0)), FadeOut(title))
+
+
+def test_render_runs_construct_on_a_fresh_timeline(tmp_path):
+    scene = Slide(32, 32, fps=10, background="#1e1e2e")
+    (tmp_path / "frames").mkdir()
+    assert scene.render(tmp_path / "frames") == 20
+    assert scene.time == 2.0
+    assert sorted(p.name for p in (tmp_path / "frames").iterdir())[-1] == "frame_0019.png"
+    # rendering again starts over instead of adding to the first run
+    assert scene.render(tmp_path / "slide.gif", size=(16, 16)) == 20
+    assert (tmp_path / "slide.gif").read_bytes().startswith(b"GIF89a")
+
+
+def test_timeline_calls_move_the_playhead():
+    timeline = Timeline(64, 64)
+    dot = Circle(4)
+    timeline.add(dot)
+    timeline.play(Shift(dot, (10, 0)), run_time=0.25)
+    timeline.wait()
+    timeline.remove(dot)
+    assert timeline.time == 1.25
+    timeline.clear()
+    assert timeline.time == 0.0
+
+
+def test_colors():
+    assert Color.parse("#ff0000") == Color.RED == Color(255, 0, 0)
+    assert str(Color(1, 2, 3, 4)) == "#01020304"
+    assert Color.WHITE.with_alpha(0).a == 0
+    assert Color.RED.mix("blue", 0.0) == Color.RED
+    assert len({Color.RED, Color(255, 0, 0)}) == 1
+
+
+def test_bad_input_raises():
+    text = Text("x")
+    with pytest.raises(NotImplementedError):
+        Scene().render("x.png")
+    with pytest.raises(ValueError, match="at least one animation"):
+        Timeline().play()
+    with pytest.raises(ValueError, match="invalid color"):
+        Color.parse("nope")
+    with pytest.raises(ValueError, match="unknown easing"):
+        FadeIn(text, easing="bouncy")
+    with pytest.raises(ValueError, match="takes a number"):
+        Animation(text, "x", (1, 2))
+    with pytest.raises(ValueError, match="`size` of a rect object can't be negative"):
+        Rect(-4, 2)
+    with pytest.raises(ValueError, match="`radius` of a circle object"):
+        Circle(float("nan"))
+    with pytest.raises(ValueError, match="`width` of a line object"):
+        Line((4, 4), float("inf"))
+    with pytest.raises(ValueError, match="`size` of a text object is a positive number"):
+        Text("x", 0)
+    with pytest.raises(RenderError, match="don't know how to write"):
+        Timeline().export("x.txt")
@@ -0,0 +1,402 @@
+# Checks python/codimate/_codimate.pyi against the built extension: every class, base,
+# member and signature in one has to be in the other, and a sample call of every function
+# has to take and return what its annotations say, so the stubs can't drift from the
+# bindings.
+
+import ast
+import inspect
+from os import PathLike
+from pathlib import Path
+
+import codimate
+from codimate import _codimate
+from codimate._codimate import (
+    Animation,
+    BlendMode,
+    Circle,
+    Code,
+    Color,
+    FadeIn,
+    FadeOut,
+    FadeToColor,
+    Frame,
+    Line,
+    MoveTo,
+    Polygon,
+    Rect,
+    Renderer,
+    Rotate,
+    ScaleInPlace,
+    Shift,
+    Text,
+    Timeline,
+)
+
+STUBS = Path(__file__).parents[2] / "python" / "codimate" / "_codimate.pyi"
+
+# `Py_TPFLAGS_BASETYPE`, set on classes that can be subclassed
+BASETYPE = 1 << 10
+
+
+def stub_aliases():
+    tree = ast.parse(STUBS.read_text())
+    return {
+        node.target.id: node.value
+        for node in tree.body
+        if isinstance(node, ast.AnnAssign) and isinstance(node.target, ast.Name)
+    }
+
+
+def stub_classes():
+    tree = ast.parse(STUBS.read_text())
+    return {node.name: node for node in tree.body if isinstance(node, ast.ClassDef)}
+
+
+def runtime_classes():
+    return {
+        name: value
+        for name, value in vars(_codimate).items()
+        if isinstance(value, type) and not name.startswith("_")
+    }
+
+
+def decorators(node):
+    names = set()
+    for decorator in node.decorator_list:
+        if isinstance(decorator, ast.Name):
+            names.add(decorator.id)
+        elif isinstance(decorator, ast.Attribute):
+            # `@name.setter`
+            names.add(decorator.attr)
+    return names
+
+
+def stub_members(node):
+    """Public members of a stub class, by kind: "method", "staticmethod", "property" or
+    "attribute"."""
+    members = {}
+    for item in node.body:
+        if isinstance(item, ast.FunctionDef) and not item.name.startswith("_"):
+            found = decorators(item)
+            if "setter" in found:
+                continue
+            if "staticmethod" in found:
+                members[item.name] = "staticmethod"
+            elif "property" in found:
+                members[item.name] = "property"
+            else:
+                members[item.name] = "method"
+        elif isinstance(item, ast.AnnAssign) and isinstance(item.target, ast.Name):
+            members[item.target.id] = "attribute"
+    return members
+
+
+def runtime_members(cls):
+    members = {}
+    for name, value in vars(cls).items():
+        if name.startswith("_"):
+            continue
+        if isinstance(value, staticmethod):
+            members[name] = "staticmethod"
+        elif inspect.isgetsetdescriptor(value):
+            members[name] = "property"
+        elif inspect.ismethoddescriptor(value):
+            members[name] = "method"
+        else:
+            members[name] = "attribute"
+    return members
+
+
+def stub_signature(function, drop_first=True):
+    """`(name, kind, default)` for each parameter, with `...` for a default that isn't a
+    literal."""
+    args = function.args
+    params = []
+    positional = args.posonlyargs + args.args
+    defaults = [None] * (len(positional) - len(args.defaults)) + args.defaults
+    for arg, default in zip(positional, defaults):
+        params.append((arg.arg, "positional", default))
+    if args.vararg:
+        params.append((args.vararg.arg, "varargs", None))
+    for arg, default in zip(args.kwonlyargs, args.kw_defaults):
+        params.append((arg.arg, "keyword", default))
+    if args.kwarg:
+        params.append((args.kwarg.arg, "varkw", None))
+    if drop_first:
+        params = params[1:]
+
+    def value(default):
+        if default is None:
+            return inspect.Parameter.empty
+        try:
+            return ast.literal_eval(default)
+        except ValueError:
+            return ...
+
+    return [(name, kind, value(default)) for name, kind, default in params]
+
+
+KINDS = {
+    inspect.Parameter.POSITIONAL_ONLY: "positional",
+    inspect.Parameter.POSITIONAL_OR_KEYWORD: "positional",
+    inspect.Parameter.VAR_POSITIONAL: "varargs",
+    inspect.Parameter.KEYWORD_ONLY: "keyword",
+    inspect.Parameter.VAR_KEYWORD: "varkw",
+}
+
+
+def runtime_signature(function, drop_first=True):
+    params = list(inspect.signature(function).parameters.values())
+    if drop_first:
+        params = params[1:]
+    return [(p.name, KINDS[p.kind], p.default) for p in params]
+
+
+def same_signature(stub, runtime):
+    if len(stub) != len(runtime):
+        return False
+    for (name, kind, default), (rt_name, rt_kind, rt_default) in zip(stub, runtime):
+        if (name, kind) != (rt_name, rt_kind):
+            return False
+        if (default is inspect.Parameter.empty) != (rt_default is inspect.Parameter.empty):
+            return False
+        # non literal defaults (enum members, tuples) come through as `...`
+        if default is not ... and rt_default is not ... and default != rt_default:
+            return False
+    return True
+
+
+# python's numeric tower: an int is fine where a float is expected
+BUILTINS = {
+    "bool": bool,
+    "int": int,
+    "float": (int, float),
+    "str": str,
+    "bytes": bytes,
+    "object": object,
+}
+
+
+def conforms(value, annotation, aliases):
+    """Whether `value` fits a stub annotation, for the forms the stubs use."""
+    if isinstance(annotation, ast.Constant) and annotation.value is None:
+        return value is None
+    if isinstance(annotation, ast.BinOp) and isinstance(annotation.op, ast.BitOr):
+        return conforms(value, annotation.left, aliases) or conforms(
+            value, annotation.right, aliases
+        )
+    if isinstance(annotation, ast.Name):
+        name = annotation.id
+        if name in aliases:
+            return conforms(value, aliases[name], aliases)
+        if name in BUILTINS:
+            return isinstance(value, BUILTINS[name])
+        return isinstance(value, getattr(_codimate, name))
+    if isinstance(annotation, ast.Subscript) and isinstance(annotation.value, ast.Name):
+        outer, inner = annotation.value.id, annotation.slice
+        items = inner.elts if isinstance(inner, ast.Tuple) else [inner]
+        if outer == "ClassVar":
+            return conforms(value, inner, aliases)
+        if outer == "Literal":
+            return value in [ast.literal_eval(item) for item in items]
+        if outer == "PathLike":
+            return isinstance(value, PathLike)
+        if outer == "list":
+            return isinstance(value, list) and all(conforms(v, inner, aliases) for v in value)
+        if outer == "tuple":
+            return (
+                isinstance(value, tuple)
+                and len(value) == len(items)
+                and all(conforms(v, item, aliases) for v, item in zip(value, items))
+            )
+    raise AssertionError(f"can't check the stub annotation {ast.unparse(annotation)}")
+
+
+def sample_calls(tmp_path):
+    """One call of every function in the stubs, `"Class.name"` to `(target, args, kwargs)`.
+    `target` is the class for constructors and static methods, and setters are
+    `"Class.name ="` with the value to set."""
+    color, frame, renderer = Color(10, 20, 30), Frame(4, 4), Renderer(4, 4)
+    timeline = Timeline(8, 8, 10.0, "black")
+    rect = Rect(4, 2, radius=1.0, name="box")
+    animation = Animation(rect, "opacity", 0.5)
+    shape = dict(position=(1.0, 2.0), color="red", opacity=0.5, name="shape")
+    return {
+        "BlendMode.__int__": (BlendMode.SCREEN, (), {}),
+        "BlendMode.__eq__": (BlendMode.SCREEN, (BlendMode.NORMAL,), {}),
+        "BlendMode.__hash__": (BlendMode.SCREEN, (), {}),
+        "Color.__init__": (Color, (1, 2, 3), {"a": 4}),
+        "Color.parse": (Color, ("oklch(0.7 0.1 30)",), {}),
+        "Color.r": (color, (), {}),
+        "Color.g": (color, (), {}),
+        "Color.b": (color, (), {}),
+        "Color.a": (color, (), {}),
+        "Color.with_alpha": (color, (128,), {}),
+        "Color.mix": (color, ("blue", 0.5), {}),
+        "Color.over": (color, (Color.WHITE,), {}),
+        "Color.blend_over": (color, ("white", BlendMode.MULTIPLY), {}),
+        "Color.__eq__": (color, (Color.RED,), {}),
+        "Color.__hash__": (color, (), {}),
+        "Frame.__init__": (Frame, (2, 2), {}),
+        "Frame.width": (frame, (), {}),
+        "Frame.height": (frame, (), {}),
+        "Frame.get_pixel": (frame, (1, 1), {}),
+        "Frame.to_bytes": (frame, (), {}),
+        "Frame.save": (frame, (tmp_path / "frame.png",), {"quality": 50}),
+        "Renderer.__init__": (Renderer, (2, 2), {}),
+        "Renderer.width": (renderer, (), {}),
+        "Renderer.height": (renderer, (), {}),
+        "Renderer.new_frame": (renderer, (), {}),
+        "Renderer.clear": (renderer, (frame, "black"), {}),
+        "Renderer.set_pixel": (renderer, (frame, 0, 0, Color.RED), {}),
+        "Renderer.rect": (renderer, (frame, 0, 0, 2, 2, "red"), {}),
+        "Renderer.blit_over": (renderer, (frame, Frame(2, 2), 1, 1), {}),
+        "Renderer.render": (renderer, (timeline, 0.0), {}),
+        "Object.kind": (rect, (), {}),
+        "Object.name": (rect, (), {}),
+        "Object.name =": (rect, ("title",), {}),
+        "Object.position": (rect, (), {}),
+        "Object.position =": (rect, ((2.0, 3.0),), {}),
+        "Object.color": (rect, (), {}),
+        "Object.color =": (rect, ("blue",), {}),
+        "Object.opacity": (rect, (), {}),
+        "Object.opacity =": (rect, (0.5,), {}),
+        "Object.scale": (rect, (), {}),
+        "Object.scale =": (rect, (2.0,), {}),
+        "Object.rotation": (rect, (), {}),
+        "Object.rotation =": (rect, (45.0,), {}),
+        "Text.__init__": (Text, ("hi", 12.0), shape),
+        "Code.__init__": (
+            Code,
+            ("x = 1", 12.0),
+            {"background": Color.BLACK, "padding": 4.0, **shape},
+        ),
+        "Rect.__init__": (Rect, (4.0, 2.0), {"radius": 1.0, **shape}),
+        "Circle.__init__": (Circle, (3.0,), shape),
+        "Line.__init__": (Line, ((4.0, 0.0), 1.0), shape),
+        "Polygon.__init__": (Polygon, ([(0.0, 0.0), (4.0, 0.0), (0.0, 4.0)],), shape),
+        "Animation.__init__": (
+            Animation,
+            (rect, "color", "blue"),
+            {"from_": Color.RED, "easing": "linear"},
+        ),
+        "Animation.target": (animation, (), {}),
+        "FadeIn.__init__": (FadeIn, (rect,), {"easing": "ease_in"}),
+        "FadeOut.__init__": (FadeOut, (rect,), {"easing": "ease_out"}),
+        "MoveTo.__init__": (MoveTo, (rect, (1.0, 1.0)), {}),
+        "Shift.__init__": (Shift, (rect, (1.0, 0.0)), {}),
+        "Rotate.__init__": (Rotate, (rect, 90.0), {}),
+        "ScaleInPlace.__init__": (ScaleInPlace, (rect, 2.0), {}),
+        "FadeToColor.__init__": (FadeToColor, (rect, Color.BLUE), {}),
+        "Timeline.__init__": (Timeline, (16, 16, 24.0, None), {}),
+        "Timeline.width": (timeline, (), {}),
+        "Timeline.height": (timeline, (), {}),
+        "Timeline.fps": (timeline, (), {}),
+        "Timeline.fps =": (timeline, (12.0,), {}),
+        "Timeline.background": (timeline, (), {}),
+        "Timeline.background =": (timeline, ("white",), {}),
+        "Timeline.time": (timeline, (), {}),
+        "Timeline.add": (timeline, (rect, Circle(2)), {}),
+        "Timeline.remove": (timeline, (rect,), {}),
+        "Timeline.play": (timeline, (animation, FadeIn(rect)), {"run_time": 0.5}),
+        "Timeline.wait": (timeline, (0.5,), {}),
+        "Timeline.clear": (Timeline(), (), {}),
+        "Timeline.export": (timeline, (tmp_path / "out.gif",), {"quality": 50, "size": (4, 4)}),
+    }
+
+
+def test_stubs_cover_every_class():
+    assert set(stub_classes()) == set(runtime_classes())
+
+
+def test_bases_and_final_classes_match():
+    runtime = runtime_classes()
+    for name, node in stub_classes().items():
+        cls = runtime[name]
+        bases = [base.id for base in node.bases if isinstance(base, ast.Name)]
+        assert bases == [base.__name__ for base in cls.__bases__ if base is not object], name
+        final = "final" in decorators(node)
+        assert final == (not cls.__flags__ & BASETYPE), name
+
+
+def test_members_match():
+    runtime = runtime_classes()
+    for name, node in stub_classes().items():
+        assert stub_members(node) == runtime_members(runtime[name]), name
+
+
+def test_signatures_match():
+    runtime = runtime_classes()
+    for name, node in stub_classes().items():
+        cls = runtime[name]
+        for item in node.body:
+            if not isinstance(item, ast.FunctionDef):
+                continue
+            found = decorators(item)
+            if item.name == "__init__":
+                stub = stub_signature(item)
+                actual = runtime_signature(cls, drop_first=False)
+            elif item.name.startswith("_") or found & {"property", "setter"}:
+                continue
+            elif "staticmethod" in found:
+                stub = stub_signature(item, drop_first=False)
+                actual = runtime_signature(getattr(cls, item.name), drop_first=False)
+            else:
+                stub = stub_signature(item)
+                actual = runtime_signature(vars(cls)[item.name])
+            assert same_signature(stub, actual), f"{name}.{item.name}: {stub} != {actual}"
+
+
+def test_package_reexports_the_extension():
+    assert set(runtime_classes()) <= set(codimate.__all__)
+
+
+def test_annotations_match(tmp_path):
+    aliases = stub_aliases()
+    runtime = runtime_classes()
+    calls = sample_calls(tmp_path)
+    checked = set()
+    for name, node in stub_classes().items():
+        cls = runtime[name]
+        for item in node.body:
+            if isinstance(item, ast.AnnAssign):
+                value = getattr(cls, item.target.id)
+                assert conforms(value, item.annotation, aliases), f"{name}.{item.target.id}"
+            if not isinstance(item, ast.FunctionDef):
+                continue
+            found = decorators(item)
+            key = f"{name}.{item.name}" + (" =" if "setter" in found else "")
+            assert key in calls, f"no sample call of {key}"
+            checked.add(key)
+            target, args, kwargs = calls[key]
+            positional = item.args.posonlyargs + item.args.args
+            if "staticmethod" not in found:
+                positional = positional[1:]
+            annotations = {
+                param.arg: param.annotation for param in positional + item.args.kwonlyargs
+            }
+
+            if "property" in found:
+                arguments, result = [], getattr(target, item.name)
+            elif "setter" in found:
+                arguments = [(positional[0].arg, args[0])]
+                result = setattr(target, item.name, args[0])
+            else:
+                # matched up by the stub's parameter names, the call itself checks the
+                # runtime takes them
+                arguments = list(zip((param.arg for param in positional), args))
+                if item.args.vararg:
+                    vararg = item.args.vararg
+                    annotations[vararg.arg] = vararg.annotation
+                    arguments += [(vararg.arg, value) for value in args[len(positional) :]]
+                arguments += kwargs.items()
+                function = target if item.name == "__init__" else getattr(target, item.name)
+                result = function(*args, **kwargs)
+
+            for param, value in arguments:
+                assert conforms(value, annotations[param], aliases), f"{key}: {param}={value!r}"
+            if item.name == "__init__":
+                assert isinstance(result, cls), key
+            else:
+                assert conforms(result, item.returns, aliases), f"{key} returned {result!r}"
+    assert checked == set(calls)